@@ -0,0 +1,154 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to run the program without a window, for computers which have
+//! no monitor.
+//!
+//! The system interface runs exactly as it does with a window (with the
+//! system connections, the event queue, the backup and the logs). The program
+//! is controlled remotely through the web console (if specified in the
+//! configuration) and with signals: SIGHUP reloads the configuration (keeping
+//! the live state), while SIGINT and SIGTERM close the program. As there is
+//! no operator to ask, any lingering backup is resumed automatically with the
+//! headless resume policy of the configuration, and any problems are noted
+//! in the logs.
+
+// Import the relevant structures into the correct namespace
+use super::system_interface::{
+    Clock, EventUpdate, InterfaceUpdate, StartupOptions, SystemInterface, SystemSend, SystemUpdate,
+    WindowType,
+};
+
+// Import standard library features
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// Import the signal handling library
+extern crate signal_hook;
+use self::signal_hook::iterator::Signals;
+
+// Define module constants
+const CHECK_DELAY: u64 = 100; // the delay between checks for the end of the program, in ms
+
+/// The structure to run the program without a window
+///
+pub struct Daemon {}
+
+// Implement the daemon functionality
+impl Daemon {
+    /// A function to run the program without a window until it receives a
+    /// signal to close. If a configuration is provided in the startup
+    /// options, it is loaded in place of the default configuration.
+    ///
+    pub fn run(options: StartupOptions) {
+        // Create the system interface with a line to receive interface updates
+        let (interface_send, interface_receive) = mpsc::channel();
+        let (system_interface, system_send) =
+            SystemInterface::new(interface_send, Clock::new(), options)
+                .expect("Unable To Create System Interface.");
+
+        // Run the system interface in a new thread, noting when it stops
+        let (done_send, done_receive) = mpsc::channel();
+        thread::spawn(move || {
+            system_interface.run();
+            done_send.send(()).unwrap_or(());
+        });
+
+        // Listen for signals on a separate thread
+        Daemon::watch_signals(system_send.clone());
+
+        // Answer the interface updates until the system interface stops
+        loop {
+            match interface_receive.recv_timeout(Duration::from_millis(CHECK_DELAY)) {
+                Ok(update) => Daemon::answer(&system_send, update),
+                Err(mpsc::RecvTimeoutError::Timeout) => (),
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            // Stop once the system interface has closed
+            if done_receive.try_recv().is_ok() {
+                break;
+            }
+        }
+    }
+
+    /// An internal function to pass the signals of the operating system to
+    /// the system interface
+    ///
+    fn watch_signals(system_send: SystemSend) {
+        // Register for the signals (without them, the program is closed by the operating system)
+        let signals = match Signals::new([
+            signal_hook::SIGHUP,
+            signal_hook::SIGINT,
+            signal_hook::SIGTERM,
+        ]) {
+            Ok(signals) => signals,
+            Err(error) => {
+                update!(err &system_send => "Unable To Listen For Signals: {}", error);
+                return;
+            }
+        };
+
+        // Reload the configuration or close the program when signalled
+        thread::spawn(move || {
+            for signal in signals.forever() {
+                if signal == signal_hook::SIGHUP {
+                    system_send.send(SystemUpdate::ReloadConfig);
+                } else {
+                    system_send.send(SystemUpdate::Close);
+                    break;
+                }
+            }
+        });
+    }
+
+    /// An internal function to answer the interface updates which would
+    /// otherwise wait for an operator (the notifications are already in the
+    /// logs)
+    ///
+    fn answer(system_send: &SystemSend, update: InterfaceUpdate) {
+        match update {
+            // Resume a lingering backup with the headless resume policy
+            InterfaceUpdate::LaunchWindow {
+                window_type: WindowType::ResumeBackup(summary),
+            } => {
+                // Note the parts which will be resumed
+                let policy = summary.headless_resume;
+                update!(update system_send => "Resuming Lingering Backup Without An Operator (Scene: {}, Statuses: {}, Events: {}).", policy.scene, policy.statuses, policy.events);
+
+                // Resume or discard each part of the backup
+                system_send.send(SystemUpdate::ResumeBackup {
+                    scene: policy.scene,
+                    statuses: policy.statuses,
+                    events: policy.events,
+                    resume_mode: summary.resume_mode,
+                });
+            }
+
+            // Note any string which could not be requested
+            InterfaceUpdate::LaunchWindow {
+                window_type: WindowType::PromptString(event),
+            } => {
+                update!(warn system_send => "Unable To Request A String For {} Without A Window.", event.description())
+            }
+
+            // Ignore the other updates
+            _ => (),
+        }
+    }
+}
@@ -17,64 +17,103 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 //! The main module of the minerva program which pulls from the other modules.
+//!
+//! The program runs with a window unless it was built without the gui
+//! feature or it was launched with the --headless option. See the options
+//! module (or run with --help) for the other command line options.
 
 // Import YAML processing libraries
 #[macro_use]
 extern crate serde;
 
-// Define program modules
+// Define program modules (the system interface first, to share its update macro)
+#[macro_use]
 mod system_interface;
+mod daemon;
+mod options;
+#[cfg(feature = "gui")]
 #[macro_use]
 mod user_interface;
 
 // Import the relevant structures into the correct namespace
-use self::system_interface::SystemInterface;
+use self::daemon::Daemon;
+use self::options::Options;
+#[cfg(feature = "gui")]
+use self::system_interface::{ChangeSettings, Clock, DisplaySetting, SystemInterface};
+#[cfg(feature = "gui")]
 use self::user_interface::UserInterface;
 
 // Import standard library features
-use std::env::args;
+use std::process;
+#[cfg(feature = "gui")]
 use std::sync::mpsc;
+#[cfg(feature = "gui")]
 use std::thread;
 
 // Import failure features
 #[macro_use]
 extern crate failure;
 
+// Import the command line library
+extern crate structopt;
+use self::structopt::StructOpt;
+
 // Import GTK and GIO libraries
+#[cfg(feature = "gui")]
 extern crate gdk;
+#[cfg(feature = "gui")]
 extern crate gio;
+#[cfg(feature = "gui")]
 extern crate gtk;
+#[cfg(feature = "gui")]
 use self::gio::prelude::*;
+#[cfg(feature = "gui")]
 use self::gtk::prelude::*;
+#[cfg(feature = "gui")]
 use self::gtk::SettingsExt;
 
 // Define program constants
+#[cfg(feature = "gui")]
 const LOGO_SQUARE: &str = "logo_square.png";
+#[cfg(feature = "gui")]
 const LOGO_WIDE: &str = "logo_wide.png";
+#[cfg(feature = "gui")]
 const GTK_THEME: &str = "Materia-dark";
+#[cfg(feature = "gui")]
 const FONT: &str = "Inter";
+#[cfg(feature = "gui")]
 const WINDOW_TITLE: &str = "Minerva";
 
 /// The Minerva structure to contain the program launching and overall
 /// communication code.
 ///
+#[cfg(feature = "gui")]
 pub struct Minerva {}
 
 // Implement the Minerva functionality
+#[cfg(feature = "gui")]
 impl Minerva {
-    /// A function to build the main program and the user interface
+    /// A function to build the main program and the user interface with
+    /// the provided command line options
     ///
-    pub fn build_program(application: &gtk::Application) {
+    pub fn build_program(application: &gtk::Application, options: &Options) {
         // Load the gtk theme for this application
         if let Some(settings) = gtk::Settings::get_default() {
             settings.set_property_gtk_theme_name(Some(GTK_THEME));
             settings.set_property_gtk_font_name(Some(FONT));
         }
 
+        // Create the clock shared by the system interface and the user interface
+        let clock = Clock::new();
+
         // Launch the background thread to monitor and handle events
         let (interface_send, interface_receive) = mpsc::channel();
-        let (system_interface, system_send) = SystemInterface::new(interface_send.clone())
-            .expect("Unable To Create System Interface.");
+        let (system_interface, system_send) = SystemInterface::new(
+            interface_send.clone(),
+            clock.clone(),
+            options.startup_options(),
+        )
+        .expect("Unable To Create System Interface.");
 
         // Open the system interface in a new thread
         thread::spawn(move || {
@@ -101,8 +140,26 @@ impl Minerva {
             system_send,
             interface_send,
             interface_receive,
+            clock,
+            options.read_only,
         );
 
+        // Start fullscreen or in debug mode, if requested
+        if options.fullscreen {
+            interface_send
+                .send(ChangeSettings {
+                    display_setting: DisplaySetting::FullScreen(true),
+                })
+                .unwrap_or(());
+        }
+        if options.debug {
+            interface_send
+                .send(ChangeSettings {
+                    display_setting: DisplaySetting::DebugMode(true),
+                })
+                .unwrap_or(());
+        }
+
         // Show all the available windows
         window.show_all();
     }
@@ -111,19 +168,51 @@ impl Minerva {
 /// The main function of the program, simplified to as high a level as possible
 /// to allow GTK+ to work its startup magic.
 ///
+#[cfg(feature = "gui")]
 fn main() {
+    // Read the command line options
+    let options = Options::from_args();
+
+    // Work on a configuration file without a window, if requested
+    if let Some(ref command) = options.command {
+        process::exit(command.run());
+    }
+
+    // Run without a window if requested
+    if options.headless {
+        Daemon::run(options.startup_options());
+        return;
+    }
+
     // Create the gtk application window. Failure results in immediate panic!
     let application = gtk::Application::new(None, gio::ApplicationFlags::empty())
         .expect("Initialization Failed For Unknown Reasons.");
 
     // Create the program and launch the background thread
     application.connect_startup(move |gtk_app| {
-        Minerva::build_program(gtk_app);
+        Minerva::build_program(gtk_app, &options);
     });
 
     // Connect the activate-specific function (as compared with open-specific function)
     application.connect_activate(|_| {});
 
-    // Run the application until all the windows are closed
-    application.run(&args().collect::<Vec<_>>());
+    // Run the application until all the windows are closed (the options are not passed to GTK)
+    application.run(&[]);
+}
+
+/// The main function of the program without the gui feature, which always
+/// runs without a window.
+///
+#[cfg(not(feature = "gui"))]
+fn main() {
+    // Read the command line options
+    let options = Options::from_args();
+
+    // Work on a configuration file, if requested
+    if let Some(ref command) = options.command {
+        process::exit(command.run());
+    }
+
+    // Otherwise, run without a window
+    Daemon::run(options.startup_options());
 }
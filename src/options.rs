@@ -0,0 +1,262 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to read the command line options of the program.
+//!
+//! The options choose the configuration, the log folder and how the window
+//! starts. The subcommands check, graph or migrate a configuration file
+//! without opening a window, so that configurations can be checked in
+//! scripts (the program exits with a non-zero status on failure). The report
+//! subcommand composes the game report of a past session from its game log,
+//! and the schema subcommand writes the JSON Schema of the configuration
+//! files for editors.
+
+// Import the relevant structures into the correct namespace
+use super::system_interface::{
+    compose_schema, process_config, ConfigTask, EventUpdate, StartupOptions,
+};
+
+// Import standard library features
+use std::fs;
+use std::path::PathBuf;
+
+// Import the command line library
+extern crate structopt;
+use self::structopt::StructOpt;
+
+// Import the serde json library
+extern crate serde_json;
+
+/// The command line options of the program
+///
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "minerva",
+    about = "A control panel for networks of microcontrollers."
+)]
+pub struct Options {
+    /// The configuration to load (default.mnv in the current directory otherwise)
+    #[structopt(parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// The folder for the game logs and the error log (log/ in the current directory otherwise)
+    #[structopt(long, parse(from_os_str))]
+    pub log_dir: Option<PathBuf>,
+
+    /// Start with the window fullscreen
+    #[cfg(feature = "gui")]
+    #[structopt(long)]
+    pub fullscreen: bool,
+
+    /// Start in debug mode
+    #[cfg(feature = "gui")]
+    #[structopt(long)]
+    pub debug: bool,
+
+    /// Run in operator mode (the configuration cannot be edited or saved)
+    #[structopt(long)]
+    pub read_only: bool,
+
+    /// Run without a window
+    #[cfg(feature = "gui")]
+    #[structopt(long)]
+    pub headless: bool,
+
+    /// Reload the configuration (keeping the current scene, statuses and queue) whenever its files change
+    #[structopt(long)]
+    pub watch: bool,
+
+    /// Work on a configuration file without running it
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Implement the options features
+impl Options {
+    /// A method to return the options of the system interface
+    ///
+    pub fn startup_options(&self) -> StartupOptions {
+        StartupOptions {
+            config_path: self.config.clone(),
+            log_folder: self.log_dir.clone(),
+            is_read_only: self.read_only,
+            is_watching: self.watch,
+        }
+    }
+}
+
+// The subcommands which work on a configuration file without a window (a
+// doc comment here would replace the description of the program)
+#[derive(StructOpt, Debug)]
+pub enum Command {
+    /// Check a configuration for errors and inconsistencies
+    Validate {
+        /// The configuration to check
+        #[structopt(parse(from_os_str))]
+        config: PathBuf,
+    },
+
+    /// Export the event graph of a configuration in the Graphviz DOT format
+    Graph {
+        /// The configuration to graph
+        #[structopt(parse(from_os_str))]
+        config: PathBuf,
+
+        /// The file for the graph (the configuration with a .dot extension otherwise)
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Rewrite a configuration in the layout of the current version
+    Migrate {
+        /// The configuration to migrate
+        #[structopt(parse(from_os_str))]
+        config: PathBuf,
+
+        /// The file for the new configuration (the configuration is replaced otherwise)
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Compose the game report of a past session from its structured game log
+    Report {
+        /// The configuration of the game
+        #[structopt(parse(from_os_str))]
+        config: PathBuf,
+
+        /// The structured game log (the .jsonl file beside the game log)
+        #[structopt(parse(from_os_str))]
+        log: PathBuf,
+
+        /// The start of the session, as listed in the game log (the most recent session otherwise)
+        #[structopt(short, long)]
+        session: Option<String>,
+
+        /// The file for the report, without extension (the game log name with _report otherwise)
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Write the JSON Schema of the configuration files (for autocomplete and checks in an editor)
+    Schema {
+        /// The file for the schema (printed otherwise)
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+}
+
+// Implement the subcommand features
+impl Command {
+    /// A method to run the subcommand, printing any errors and warnings.
+    /// The method returns the exit code of the program: zero on success and
+    /// one if the configuration has errors (or, for validate, warnings).
+    ///
+    pub fn run(&self) -> i32 {
+        // Compose the task for the configuration
+        let (config, task) = match self {
+            Command::Schema { output } => return Command::write_schema(output),
+            Command::Validate { config } => (config, ConfigTask::Validate),
+            Command::Graph { config, output } => (
+                config,
+                ConfigTask::Graph(
+                    output
+                        .clone()
+                        .unwrap_or_else(|| config.with_extension("dot")),
+                ),
+            ),
+            Command::Migrate { config, output } => (
+                config,
+                ConfigTask::Migrate(output.clone().unwrap_or_else(|| config.clone())),
+            ),
+            Command::Report {
+                config,
+                log,
+                session,
+                output,
+            } => (
+                config,
+                ConfigTask::Report {
+                    log_path: log.clone(),
+                    session: session.clone(),
+                    report_path: output.clone().unwrap_or_else(|| {
+                        let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+                        log.with_file_name(format!("{}_report", stem))
+                    }),
+                },
+            ),
+        };
+
+        // Complete the task and print the errors and warnings
+        let is_validate = task == ConfigTask::Validate;
+        let (is_complete, updates) = match process_config(config.clone(), task) {
+            Ok(updates) => (true, updates),
+            Err(updates) => (false, updates),
+        };
+        for update in updates.iter() {
+            eprintln!("{}: {}", config.display(), update);
+        }
+
+        // Report the result (a valid configuration has no warnings)
+        let problems = updates
+            .iter()
+            .filter(|update| matches!(update, EventUpdate::Error(..) | EventUpdate::Warning(..)))
+            .count();
+        if !is_complete || (is_validate && problems > 0) {
+            eprintln!("{}: {} Problem(s) Found.", config.display(), problems);
+            1
+        } else {
+            println!("{}: Done.", config.display());
+            0
+        }
+    }
+
+    /// An internal function to write the JSON Schema of the configuration
+    /// files to the provided file (or to print it otherwise). The function
+    /// returns the exit code of the program.
+    ///
+    fn write_schema(output: &Option<PathBuf>) -> i32 {
+        // Compose the schema
+        let schema = match serde_json::to_string_pretty(&compose_schema()) {
+            Ok(schema) => schema,
+            Err(error) => {
+                eprintln!("Unable To Compose Schema: {}", error);
+                return 1;
+            }
+        };
+
+        // Print the schema if there is no file
+        let path = match output {
+            Some(path) => path,
+            None => {
+                println!("{}", schema);
+                return 0;
+            }
+        };
+
+        // Otherwise, try to write the schema to the file
+        match fs::write(path, schema) {
+            Ok(_) => {
+                println!("{}: Done.", path.display());
+                0
+            }
+            Err(error) => {
+                eprintln!("{}: Unable To Write Schema: {}", path.display(), error);
+                1
+            }
+        }
+    }
+}
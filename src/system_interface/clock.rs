@@ -0,0 +1,277 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to provide the shared clock for the program. Every part of the
+//! event system (the queue, the event handler, and the timeline) reads the
+//! time from a copy of this clock rather than from the system directly. This
+//! allows the whole program to run on a virtual clock, either accelerated for
+//! simulation or advanced manually for testing.
+
+// Import standard library features
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Define module constants
+#[cfg(test)]
+const MANUAL_POLLING: u64 = 1; // the polling rate for a manual clock in ms
+
+/// An enum to specify how the clock advances.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClockMode {
+    /// A variant for a clock that follows the system time
+    RealTime,
+
+    /// A variant for a clock that runs faster than the system time by the
+    /// provided factor (e.g. a rate of 10 plays ten seconds every second)
+    Simulated { rate: u32 },
+
+    /// A variant for a clock that only advances when it is told to (used
+    /// for deterministic testing)
+    #[cfg(test)]
+    Manual,
+}
+
+/// An internal structure to hold the anchor points of the clock.
+///
+/// The current virtual time is always calculated relative to the moment the
+/// clock mode was last changed so that the time remains continuous across
+/// changes in mode.
+///
+#[derive(Copy, Clone, Debug)]
+struct ClockState {
+    mode: ClockMode,         // the current mode of the clock
+    anchor_real: Instant,    // the system time when the mode was last changed
+    anchor_virtual: Instant, // the virtual time when the mode was last changed
+}
+
+// Implement key features of the clock state
+impl ClockState {
+    /// A method to calculate the current virtual time
+    ///
+    fn now(&self) -> Instant {
+        match self.mode {
+            // Follow the system time from the anchor
+            ClockMode::RealTime => self.anchor_virtual + self.anchor_real.elapsed(),
+
+            // Multiply the system time since the anchor
+            ClockMode::Simulated { rate } => {
+                self.anchor_virtual + (self.anchor_real.elapsed() * rate.max(1))
+            }
+
+            // Only move when advanced
+            #[cfg(test)]
+            ClockMode::Manual => self.anchor_virtual,
+        }
+    }
+}
+
+/// A structure to provide the shared, injectable clock for the program.
+///
+/// Copies of the clock share the same underlying time, so changing the mode
+/// of one copy changes the mode of all of them. The clock produces standard
+/// Instants so that times may be freely passed between the system interface
+/// and the user interface.
+///
+#[derive(Clone, Debug)]
+pub struct Clock {
+    state: Arc<Mutex<ClockState>>, // the shared state of the clock
+}
+
+// Implement key features of the clock
+impl Clock {
+    /// A function to create a new clock which follows the system time.
+    ///
+    pub fn new() -> Clock {
+        Clock::new_with_mode(ClockMode::RealTime)
+    }
+
+    /// A function to create a new clock with the provided mode.
+    ///
+    pub fn new_with_mode(mode: ClockMode) -> Clock {
+        // Anchor the clock to the current system time
+        let now = Instant::now();
+        Clock {
+            state: Arc::new(Mutex::new(ClockState {
+                mode,
+                anchor_real: now,
+                anchor_virtual: now,
+            })),
+        }
+    }
+
+    /// A method to return the current time of the clock.
+    ///
+    pub fn now(&self) -> Instant {
+        match self.state.lock() {
+            Ok(state) => state.now(),
+
+            // Fall back to the system time if the clock has failed
+            _ => Instant::now(),
+        }
+    }
+
+    /// A method to return the amount of time that has passed on this clock
+    /// since the provided time. Returns zero if the time is in the future.
+    ///
+    pub fn elapsed(&self, since: &Instant) -> Duration {
+        // Compare the current time with the provided time
+        let now = self.now();
+        if now > *since {
+            now - *since
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+
+    /// A method to calculate the amount of time remaining before the provided
+    /// delay has passed since the start time. Returns None if the delay has
+    /// already passed (or is passing at this moment, which matters for a
+    /// manual clock that will not move on its own).
+    ///
+    pub fn remaining(&self, start_time: &Instant, delay: &Duration) -> Option<Duration> {
+        match delay.checked_sub(self.elapsed(start_time)) {
+            Some(remaining) if remaining > Duration::from_secs(0) => Some(remaining),
+            _ => None,
+        }
+    }
+
+    /// A method to return the current mode of the clock.
+    ///
+    pub fn mode(&self) -> ClockMode {
+        match self.state.lock() {
+            Ok(state) => state.mode,
+            _ => ClockMode::RealTime,
+        }
+    }
+
+    /// A method to check if the clock is running on virtual time (i.e. the
+    /// program is being simulated or tested).
+    ///
+    pub fn is_simulated(&self) -> bool {
+        self.mode() != ClockMode::RealTime
+    }
+
+    /// A method to change the mode of the clock. The current time of the clock
+    /// is preserved across the change.
+    ///
+    pub fn set_mode(&self, mode: ClockMode) {
+        if let Ok(mut state) = self.state.lock() {
+            // Re-anchor the clock at the current time
+            state.anchor_virtual = state.now();
+            state.anchor_real = Instant::now();
+            state.mode = mode;
+        }
+    }
+
+    /// A method to move the clock forward by the provided duration. This is
+    /// usually used with a manual clock, but works in any mode.
+    ///
+    #[cfg(test)]
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut state) = self.state.lock() {
+            state.anchor_virtual += duration;
+        }
+    }
+
+    /// A method to convert a duration on this clock to the equivalent amount
+    /// of system time. A manual clock is polled regularly, so the result is
+    /// never longer than the manual polling rate.
+    ///
+    pub fn to_real(&self, duration: Duration) -> Duration {
+        match self.mode() {
+            ClockMode::RealTime => duration,
+            ClockMode::Simulated { rate } => duration / rate.max(1),
+            #[cfg(test)]
+            ClockMode::Manual => duration.min(Duration::from_millis(MANUAL_POLLING)),
+        }
+    }
+
+    /// A method to wait on the provided receiver for at most the provided
+    /// duration of clock time.
+    ///
+    /// # Note
+    ///
+    /// A timeout does not guarantee that the full duration has passed on the
+    /// clock (for example, if the clock is manual or the mode changed during
+    /// the wait). The caller should recheck the time after a timeout.
+    ///
+    pub fn recv_timeout<T>(
+        &self,
+        receiver: &mpsc::Receiver<T>,
+        timeout: Duration,
+    ) -> Result<T, mpsc::RecvTimeoutError> {
+        receiver.recv_timeout(self.to_real(timeout))
+    }
+}
+
+// Tests of the clock module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the manual clock
+    #[test]
+    fn manual_clock() {
+        // Create a new manual clock
+        let clock = Clock::new_with_mode(ClockMode::Manual);
+        let start = clock.now();
+
+        // Verify that time does not pass on its own
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.elapsed(&start), Duration::from_secs(0));
+
+        // Advance the clock and check the remaining time
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(clock.elapsed(&start), Duration::from_secs(30));
+        assert_eq!(
+            clock.remaining(&start, &Duration::from_secs(45)),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(clock.remaining(&start, &Duration::from_secs(20)), None);
+        assert_eq!(clock.remaining(&start, &Duration::from_secs(30)), None);
+
+        // Verify that copies of the clock share the same time
+        let copy = clock.clone();
+        copy.advance(Duration::from_secs(30));
+        assert_eq!(clock.elapsed(&start), Duration::from_secs(60));
+    }
+
+    // Test the simulated clock
+    #[test]
+    fn simulated_clock() {
+        // Create a new clock running at 100x
+        let clock = Clock::new_with_mode(ClockMode::Simulated { rate: 100 });
+        let start = clock.now();
+
+        // Verify that time passes faster than the system time
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(clock.elapsed(&start) >= Duration::from_secs(2));
+        assert_eq!(
+            clock.to_real(Duration::from_secs(60)),
+            Duration::from_millis(600)
+        );
+
+        // Verify that the time is continuous when changing modes
+        clock.set_mode(ClockMode::Manual);
+        let paused = clock.elapsed(&start);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.elapsed(&start), paused);
+        assert!(clock.is_simulated());
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to watch the configuration files for changes.
+//!
+//! The watcher checks the modification times of the configuration and of
+//! any included files from a background thread and requests that the system
+//! interface reload the configuration (keeping the live state) whenever one
+//! of them changes.
+
+// Import the relevant structures into the correct namespace
+use super::{GeneralUpdate, ReloadConfig};
+
+// Import standard library features
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// Define module constants
+const WATCH_RATE: u64 = 500; // the time between checks of the configuration files in ms
+
+/// A helper function to find the modification times of the provided files.
+/// Returns None if any of the files is missing (e.g. while an editor
+/// replaces it).
+///
+fn modified_times(paths: &[PathBuf]) -> Option<Vec<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+/// A helper function to compare the latest modification times with the
+/// noted ones. Returns true (and notes the latest times) if any of the files
+/// changed. Missing files are ignored until they reappear.
+///
+fn has_changed(latest: Option<Vec<SystemTime>>, modified: &mut Option<Vec<SystemTime>>) -> bool {
+    // Ignore the check if any of the files is missing
+    if latest.is_none() || (latest == *modified) {
+        return false;
+    }
+
+    // Otherwise, note the latest times
+    *modified = latest;
+    true
+}
+
+/// A structure to watch the configuration files from a background thread.
+/// The watcher stops when the structure is dropped.
+///
+pub struct ConfigWatcher {
+    _stop: mpsc::Sender<()>, // the line to stop the background thread when dropped
+}
+
+// Implement key features of the configuration watcher
+impl ConfigWatcher {
+    /// A function to start watching the provided configuration files. The
+    /// current modification times are noted immediately, so only later
+    /// changes trigger a reload.
+    ///
+    pub fn new(paths: Vec<PathBuf>, general_update: GeneralUpdate) -> ConfigWatcher {
+        // Note the current modification times
+        let mut modified = modified_times(&paths);
+        let (stop, receive) = mpsc::channel::<()>();
+
+        // Spawn the background thread
+        thread::spawn(move || {
+            // Check the files until the watcher is dropped
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                receive.recv_timeout(Duration::from_millis(WATCH_RATE))
+            {
+                // Reload the configuration if any of the files changed (waiting for any missing file to reappear)
+                if has_changed(modified_times(&paths), &mut modified) {
+                    general_update.send_system(ReloadConfig);
+                }
+            }
+        });
+
+        // Return the watcher
+        ConfigWatcher { _stop: stop }
+    }
+}
+
+// Tests of the configuration watcher module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that only a change in the modification times is noted
+    #[test]
+    fn detect_change() {
+        // Note some initial modification times
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let later = start + Duration::from_secs(1);
+        let mut modified = Some(vec![start, start]);
+
+        // Verify that unchanged or missing files are not a change
+        assert!(!has_changed(Some(vec![start, start]), &mut modified));
+        assert!(!has_changed(None, &mut modified));
+        assert_eq!(modified, Some(vec![start, start]));
+
+        // Verify that a change is noted only once
+        assert!(has_changed(Some(vec![start, later]), &mut modified));
+        assert!(!has_changed(Some(vec![start, later]), &mut modified));
+        assert_eq!(modified, Some(vec![start, later]));
+    }
+
+    // Test that the modification times are read from the files
+    #[test]
+    fn read_times() {
+        // Import features for testing
+        use std::env;
+
+        // Write a configuration file
+        let path = env::temp_dir().join(format!("minerva_watch_test_{}.mnv", std::process::id()));
+        fs::write(&path, "version: 0.0.0\n").unwrap();
+
+        // Verify that the times are found only while every file exists
+        let missing = path.with_extension("missing");
+        let times = modified_times(std::slice::from_ref(&path));
+        let partial = modified_times(&[path.clone(), missing]);
+        fs::remove_file(&path).unwrap_or(());
+        assert_eq!(times.map(|times| times.len()), Some(1));
+        assert_eq!(partial, None);
+    }
+}
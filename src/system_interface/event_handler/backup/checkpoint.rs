@@ -0,0 +1,95 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to define the named checkpoints created by an operator during a
+//! game. Each checkpoint is a snapshot of the current scene, the status states
+//! and the event queue which can be restored later (e.g. to rewind the game
+//! after a prop malfunction).
+//!
+//! Checkpoints are stored alongside the rest of the backup, together with an
+//! index of the checkpoint names in the order they were saved.
+
+// Import the relevant structures into the correct namespace
+use super::{ItemId, QueuedEvent};
+
+// Import standard library features
+use std::time::SystemTime;
+
+/// A helper function to compose the key of the checkpoint index for the
+/// provided identifier
+///
+pub fn index_key(identifier: &ItemId) -> String {
+    format!("{}:checkpoints", identifier)
+}
+
+/// A helper function to compose the key of the named checkpoint for the
+/// provided identifier
+///
+pub fn checkpoint_key(identifier: &ItemId, name: &str) -> String {
+    format!("{}:checkpoint:{}", identifier, name)
+}
+
+/// A structure to hold a snapshot of the state of the system
+///
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub name: String,                    // the name of the checkpoint
+    pub saved: SystemTime,               // the wall-clock time when the checkpoint was saved
+    pub scene: ItemId,                   // the current scene at the checkpoint
+    pub statuses: Vec<(ItemId, ItemId)>, // the status id and state pairs at the checkpoint
+    pub events: Vec<QueuedEvent>,        // the queued events at the checkpoint
+}
+
+// Tests of the checkpoint module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Test that a checkpoint survives the round trip through the backup
+    #[test]
+    fn serialize_checkpoint() {
+        // Import the YAML processing library
+        extern crate serde_yaml;
+
+        // Create a checkpoint with a status and a queued event
+        let checkpoint = Checkpoint {
+            name: "Before The Vault".to_string(),
+            saved: SystemTime::now(),
+            scene: ItemId::new(100).unwrap(),
+            statuses: vec![(ItemId::new(20).unwrap(), ItemId::new(21).unwrap())],
+            events: vec![QueuedEvent {
+                remaining: Duration::from_secs(30),
+                deadline: None,
+                event_id: ItemId::new(110).unwrap(),
+            }],
+        };
+
+        // Verify that the checkpoint is unchanged after reading it back
+        let checkpoint_string = serde_yaml::to_string(&checkpoint).unwrap();
+        let restored: Checkpoint = serde_yaml::from_str(checkpoint_string.as_str()).unwrap();
+        assert!(restored == checkpoint);
+
+        // Verify the keys of the checkpoint and the index
+        let identifier = ItemId::new(1).unwrap();
+        assert_eq!(index_key(&identifier), "1:checkpoints");
+        assert_eq!(
+            checkpoint_key(&identifier, &checkpoint.name),
+            "1:checkpoint:Before The Vault"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to store the backup in a local file, for systems without a
+//! Redis server.
+//!
+//! Every change rewrites the whole backup to a temporary file which then
+//! replaces the backup file. Because the replacement is atomic, a crash at
+//! any moment leaves either the previous or the new backup on disk, never a
+//! partially written one.
+
+// Import the relevant structures into the correct namespace
+use super::BackupBackend;
+
+// Import standard library features
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Import the failure features
+use failure::Error;
+
+// Import FNV HashMap
+extern crate fnv;
+use self::fnv::FnvHashMap;
+
+// Import YAML processing library
+extern crate serde_yaml;
+
+/// A structure to hold the local backup file and a copy of its contents
+///
+pub struct FileBackup {
+    path: PathBuf,                              // the location of the backup file
+    entries: Mutex<FnvHashMap<String, String>>, // the current contents of the backup
+}
+
+// Implement key functionality for the file structure
+impl FileBackup {
+    /// A function to open the backup file at the provided location, reading
+    /// any backup that is already there. The file is created when the first
+    /// value is written.
+    ///
+    pub fn new(path: PathBuf) -> Result<FileBackup, Error> {
+        // Read the existing backup, if it exists
+        let entries = if path.exists() {
+            let backup_string = fs::read_to_string(&path)?;
+            serde_yaml::from_str(backup_string.as_str())?
+        } else {
+            FnvHashMap::default()
+        };
+
+        // Return the new backup
+        Ok(FileBackup {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// An internal function to atomically replace the backup file with the
+    /// provided entries. An empty backup removes the file.
+    ///
+    fn save(&self, entries: &FnvHashMap<String, String>) -> Result<(), Error> {
+        // Remove the file if there is nothing to backup
+        if entries.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+
+        // Write the entries to a temporary file and flush it to the disk
+        let backup_string = serde_yaml::to_string(entries)?;
+        let temporary = self.path.with_extension("tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(backup_string.as_bytes())?;
+        file.sync_all()?;
+
+        // Replace the backup file with the temporary file
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+// Implement the backup backend trait for FileBackup
+impl BackupBackend for FileBackup {
+    /// A method to read a value from the backup
+    ///
+    fn read(&self, key: &str) -> Option<String> {
+        match self.entries.lock() {
+            Ok(entries) => entries.get(key).cloned(),
+            _ => None,
+        }
+    }
+
+    /// A method to write a value to the backup
+    ///
+    fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+        // Update the entries and save them to the file
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|_| format_err!("Backup file lock failed."))?;
+        entries.insert(key.to_string(), value.to_string());
+        self.save(&entries)
+    }
+
+    /// A method to remove a value from the backup
+    ///
+    fn remove(&self, key: &str) {
+        // Update the entries and save them to the file (ignore any errors)
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.remove(key).is_some() {
+                self.save(&entries).unwrap_or(());
+            }
+        }
+    }
+}
+
+// Tests of the file backup module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that the backup survives reopening the file
+    #[test]
+    fn reopen_backup() {
+        // Create a new backup in the temporary directory
+        let path = std::env::temp_dir().join(format!("minerva_backup_{}.yaml", std::process::id()));
+        let backup = FileBackup::new(path.clone()).unwrap();
+
+        // Write several values and remove one
+        backup.write("1:current", "100").unwrap();
+        backup.write("1:20", "21").unwrap();
+        backup.write("1:30", "31").unwrap();
+        backup.remove("1:30");
+
+        // Verify that a new backup (as after a crash) reads the same values
+        let reopened = FileBackup::new(path.clone()).unwrap();
+        assert_eq!(reopened.read("1:current"), Some("100".to_string()));
+        assert_eq!(reopened.read("1:20"), Some("21".to_string()));
+        assert_eq!(reopened.read("1:30"), None);
+
+        // Verify that removing every value removes the file
+        reopened.remove("1:current");
+        reopened.remove("1:20");
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,1103 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements the connection to a Redis backup server or a local
+//! backup file to maintain a backup of the system state. This handler syncs
+//! the system status, current scene, and queue to the backup. This module does
+//! nothing if neither a Redis server nor a backup file is connected.
+//!
+//! The backup also holds any named checkpoints saved by the operator (see
+//! the checkpoint module).
+//!
+//! WARNING: This module assumes no authorized systems/operators are compromised.
+
+// Define private submodules
+mod checkpoint;
+mod file_backup;
+mod redis_backup;
+mod standby;
+
+// Reexport the key structures and types
+pub use self::checkpoint::Checkpoint;
+
+// Import the relevant structures into the correct namespace
+use self::file_backup::FileBackup;
+use self::redis_backup::RedisBackup;
+use self::standby::{Heartbeat, StandbyMonitor};
+use super::super::{Clock, GeneralUpdate};
+use super::{ComingEvent, EventUpdate, ItemId, ItemPair};
+
+// Import standard library features
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Import the failure features
+use failure::Error;
+
+// Import FNV HashSet
+extern crate fnv;
+use self::fnv::FnvHashSet;
+
+// Import YAML processing library
+extern crate serde_yaml;
+
+/// An enum to specify how queued events are resumed from a backup. The
+/// remaining time of each event is frozen at the moment of the backup, so
+/// resuming with the deadlines accounts for the time the program was down.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResumeMode {
+    /// A variant to resume each event at its original wall-clock deadline.
+    /// Events whose deadline passed during the outage are either triggered
+    /// immediately or skipped.
+    Deadlines { skip_expired: bool },
+
+    /// A variant to resume each event with the remaining time at the moment
+    /// of the backup (as if no time passed during the outage)
+    Frozen,
+}
+
+/// A structure to specify which parts of a lingering backup are resumed when
+/// there is no operator to ask (i.e. when running without a window). Any
+/// part which is not selected is discarded.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumePolicy {
+    #[serde(default)]
+    pub scene: bool, // a flag to resume the current scene
+    #[serde(default)]
+    pub statuses: bool, // a flag to resume the status states
+    #[serde(default)]
+    pub events: bool, // a flag to resume the queued events
+}
+
+/// An enum to describe how a particular queued event should be resumed
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResumedEvent {
+    /// A variant for an event to queue with the provided delay
+    Delay(Duration),
+
+    /// A variant for an event whose deadline passed during the outage and
+    /// should be triggered immediately
+    Expired,
+
+    /// A variant for an event whose deadline passed during the outage and
+    /// should be skipped
+    Skipped,
+}
+
+/// A type to hold the data reloaded from a backup: the current scene, the
+/// status pairs, and the queued events
+///
+pub type BackupData = (ItemId, Vec<(ItemId, ItemId)>, Vec<QueuedEvent>);
+
+/// A structure to summarize lingering backup data for the operator before it
+/// is resumed. The statuses only include those which differ from the current
+/// (default) state, and each event has both the remaining time at the moment
+/// of the backup and the remaining time until its deadline (or None if it
+/// expired during the outage), so that the operator can choose how to resume.
+///
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BackupSummary {
+    pub age: Option<Duration>, // the time since the backup was last updated, if known
+    pub scene: ItemPair,       // the current scene of the backup
+    pub statuses: Vec<(ItemPair, ItemPair)>, // the status and state pairs which differ from the defaults
+    pub events: Vec<(ItemPair, Duration, Option<Duration>)>, // the queued events with their frozen and deadline remaining times
+    pub resume_mode: ResumeMode, // the default way to resume the queued events (from the configuration)
+    pub headless_resume: ResumePolicy, // the parts to resume without an operator (from the configuration)
+}
+
+/// An internal structure to store queued events
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedEvent {
+    pub remaining: Duration, // the remaining time before the event is triggered
+    #[serde(default)]
+    pub deadline: Option<SystemTime>, // the wall-clock time when the event is triggered
+    pub event_id: ItemId,    // id of the event to launch
+}
+
+// Implement key features of the queued event
+impl QueuedEvent {
+    /// A method to decide how to resume the queued event at the provided
+    /// wall-clock time. Events backed up without a deadline are always
+    /// resumed with the frozen remaining time.
+    ///
+    pub fn resume(&self, resume_mode: ResumeMode, now: SystemTime) -> ResumedEvent {
+        match (resume_mode, self.deadline) {
+            // Compare the deadline with the current time
+            (ResumeMode::Deadlines { skip_expired }, Some(deadline)) => {
+                match deadline.duration_since(now) {
+                    // Resume with the time left until the deadline
+                    Ok(remaining) => ResumedEvent::Delay(remaining),
+
+                    // Otherwise, the event expired during the outage
+                    Err(_) => {
+                        if skip_expired {
+                            ResumedEvent::Skipped
+                        } else {
+                            ResumedEvent::Expired
+                        }
+                    }
+                }
+            }
+
+            // Otherwise, resume with the frozen remaining time
+            _ => ResumedEvent::Delay(self.remaining),
+        }
+    }
+}
+
+/// An internal enum to hold the different types of backup. Each backup stores
+/// string values under string keys.
+///
+enum LiveBackup {
+    /// A variant to store the backup on a Redis server
+    Redis {
+        connection: RedisBackup, // the Redis connection
+    },
+
+    /// A variant to store the backup in a local file
+    File {
+        connection: FileBackup, // the local backup file
+    },
+}
+
+// Implement the backup backend for LiveBackup
+impl BackupBackend for LiveBackup {
+    /// The read method
+    fn read(&self, key: &str) -> Option<String> {
+        match self {
+            &LiveBackup::Redis { ref connection } => connection.read(key),
+            &LiveBackup::File { ref connection } => connection.read(key),
+        }
+    }
+
+    /// The write method
+    fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+        match self {
+            &LiveBackup::Redis { ref connection } => connection.write(key, value),
+            &LiveBackup::File { ref connection } => connection.write(key, value),
+        }
+    }
+
+    /// The remove method
+    fn remove(&self, key: &str) {
+        match self {
+            &LiveBackup::Redis { ref connection } => connection.remove(key),
+            &LiveBackup::File { ref connection } => connection.remove(key),
+        }
+    }
+}
+
+/// A structure which holds a reference to the backup (if it exists) and
+/// syncronizes local data to and from the backup.
+///
+/// # Notes
+///
+/// When created, the backup handler will attempt to connect to the requested
+/// redis server. If the backup handler cannot make the connection, it will
+/// fall back to the local backup file (if specified) or otherwise raise an
+/// error and return none.
+///
+/// While on standby, the backup handler only reads the backup of the primary
+/// instance and never modifies it. Once another instance has taken over as
+/// the primary, the backup handler likewise stops modifying the backup. The
+/// backup is also held unchanged while the operator decides what to do with
+/// lingering backup data, and paused while a simulation is running.
+///
+pub struct BackupHandler {
+    identifier: ItemId,              // the identifier for this instance of the program
+    connection: Option<LiveBackup>,  // the backup connection, if it exists
+    server_location: Option<String>, // the location of the Redis server, if connected
+    update_line: GeneralUpdate,      // the update line for posting any warnings
+    backup_items: FnvHashSet<ItemId>, // items currently backed up in the system
+    token: String,                   // the owner token of this instance on the Redis server
+    is_held: bool,                   // a flag to hold the backup until the operator decides
+    is_paused: bool,                 // a flag to pause the backup during a simulation
+    heartbeat: Option<Heartbeat>,    // the heartbeat of this instance, if it is the primary
+    standby: Option<StandbyMonitor>, // the monitor of the primary, if this instance is on standby
+}
+
+// Implement key features for the status handler
+impl BackupHandler {
+    /// A function to create and return a new backup handler.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// Redis server provided and there is no backup file to fall back to, or
+    /// if it is unable to read the backup file provided.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line and returning
+    /// None.
+    ///
+    pub fn new(
+        update_line: GeneralUpdate,
+        identifier: ItemId,
+        server_location: Option<String>,
+        backup_path: Option<PathBuf>,
+    ) -> Result<BackupHandler, Error> {
+        // If a server location was specified
+        let mut connection = None;
+        let mut redis_location = None;
+        if let Some(location) = server_location {
+            // Try to connect to the Redis server
+            match RedisBackup::new(location.as_str()) {
+                Ok(redis) => {
+                    connection = Some(LiveBackup::Redis { connection: redis });
+                    redis_location = Some(location);
+                }
+
+                // Indicate that there was a failure to connect to the server
+                Err(_) => {
+                    update!(err &update_line => "Unable To Connect To Backup Server: {}.", location);
+
+                    // Indicate failure if there is no backup file to fall back to
+                    if backup_path.is_none() {
+                        return Err(format_err!(
+                            "Unable To Connect To Backup Server: {}.",
+                            location
+                        ));
+                    }
+                }
+            }
+        }
+
+        // If there is no server connection and a backup file was specified
+        if let (None, Some(path)) = (&connection, backup_path) {
+            // Try to open the backup file
+            match FileBackup::new(path.clone()) {
+                Ok(file) => {
+                    update!(update &update_line => "Using Local Backup File: {}", path.to_str().unwrap_or(""));
+                    connection = Some(LiveBackup::File { connection: file });
+                }
+
+                // Indicate failure
+                Err(error) => {
+                    update!(err &update_line => "Unable To Open Local Backup File: {}.", error);
+                    return Err(format_err!("Unable To Open Local Backup File: {}.", error));
+                }
+            }
+        }
+
+        // Return the new backup handler (with or without a connection)
+        Ok(BackupHandler {
+            identifier,
+            connection,
+            server_location: redis_location,
+            update_line,
+            backup_items: FnvHashSet::default(),
+            token: standby::owner_token(),
+            is_held: false,
+            is_paused: false,
+            heartbeat: None,
+            standby: None,
+        })
+    }
+
+    /// A method to check if this instance is on standby
+    ///
+    pub fn is_standby(&self) -> bool {
+        self.standby.is_some()
+    }
+
+    /// A method to hold the backup unchanged (or release the hold). While
+    /// held, lingering backup data is not overwritten by the running instance.
+    ///
+    pub fn set_held(&mut self, is_held: bool) {
+        self.is_held = is_held;
+    }
+
+    /// A method to pause the backup (or resume it). While paused, for example
+    /// during a simulation, the running instance writes nothing to the backup.
+    ///
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    /// A method to check if this instance must not modify the backup: either
+    /// it is on standby, another instance has taken over as the primary, or
+    /// the backup is held or paused
+    ///
+    pub fn is_fenced(&self) -> bool {
+        // Check for standby, a hold, a pause or a lost lease
+        if self.is_standby() || self.is_held || self.is_paused {
+            return true;
+        }
+        match self.heartbeat {
+            Some(ref heartbeat) => heartbeat.is_lost(),
+            None => false,
+        }
+    }
+
+    /// A method to claim the Redis server as the primary instance. If the
+    /// claim succeeds, this method fences every later write with the owner
+    /// token of this instance and starts renewing the lease. Returns false if
+    /// another (primary) instance currently holds the lease. This method
+    /// always succeeds without a Redis server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to reach the Redis
+    /// server.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line (and running
+    /// as the primary).
+    ///
+    pub fn claim_primary(&mut self) -> bool {
+        // Only a Redis server is shared between instances
+        let key = standby::heartbeat_key(&self.identifier);
+        if let Some(LiveBackup::Redis { ref connection }) = self.connection {
+            // Try to claim the lease
+            match connection.claim(&key, &self.token, standby::lease_lifetime()) {
+                Ok(true) => (),
+                Ok(false) => return false,
+                Err(_) => {
+                    update!(err &self.update_line => "Unable To Claim Backup Server As The Primary Instance.");
+                    return true;
+                }
+            }
+        } else {
+            return true;
+        }
+
+        // Fence the connection and start the heartbeat
+        self.start_heartbeat();
+        true
+    }
+
+    /// A method to fence the connection with the owner token of this instance
+    /// and start renewing the lease. This method does nothing without a
+    /// Redis server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// Redis server.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    fn start_heartbeat(&mut self) {
+        // Require the lease for every later write
+        let key = standby::heartbeat_key(&self.identifier);
+        if let Some(LiveBackup::Redis { ref mut connection }) = self.connection {
+            connection.fence(&key, &self.token);
+        }
+
+        // If the Redis server exists
+        if let Some(ref location) = self.server_location {
+            // Try to start the heartbeat
+            match Heartbeat::new(
+                location,
+                self.identifier,
+                self.token.clone(),
+                self.update_line.clone(),
+            ) {
+                Ok(heartbeat) => self.heartbeat = Some(heartbeat),
+                Err(_) => {
+                    update!(err &self.update_line => "Unable To Start Heartbeat On Backup Server.")
+                }
+            }
+        }
+    }
+
+    /// A method to place this instance on standby, watching the heartbeat of
+    /// the primary instance. This method does nothing without a Redis server.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// Redis server.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn start_standby(&mut self, general_update: GeneralUpdate) {
+        // If the Redis server exists
+        if let Some(ref location) = self.server_location {
+            // Try to start monitoring the primary
+            match StandbyMonitor::new(
+                location,
+                self.identifier,
+                self.token.clone(),
+                general_update,
+            ) {
+                Ok(monitor) => self.standby = Some(monitor),
+                Err(_) => {
+                    update!(err &self.update_line => "Unable To Monitor Primary Instance On Backup Server.")
+                }
+            }
+        }
+    }
+
+    /// A method to take over from the primary instance once the monitor has
+    /// taken the lease: stop monitoring the primary, fence the connection and
+    /// start renewing the lease of this instance.
+    ///
+    pub fn take_over(&mut self) {
+        self.standby = None;
+        self.start_heartbeat();
+    }
+
+    /// A method to backup the current scene of the system
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to write to the
+    /// backup.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn backup_current_scene(&self, current_scene: &ItemId) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to copy the current scene to the backup
+            let result = connection.write(
+                &format!("{}:current", self.identifier),
+                &current_scene.as_string(),
+            );
+
+            // Unpack the result from the operation
+            if let Err(..) = result {
+                // Warn that it wasn't possible to update the current scene
+                update!(err self.update_line => "Unable To Backup Current Scene.");
+            } else {
+                self.mark_updated(connection);
+            }
+        }
+    }
+
+    /// A method to backup a status state on the backup server based on the
+    /// provided status id and new state.
+    ///
+    /// # Note
+    ///
+    /// As the backup handler does not hold a copy of the status map, this
+    /// method does not verify the validity of the new state in any way.
+    /// It is expected that the calling module will perform this check.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to write to the
+    /// backup.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn backup_status(&mut self, status_id: &ItemId, new_state: &ItemId) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to copy the state to the backup
+            let result = connection.write(
+                &format!("{}:{}", self.identifier, status_id),
+                &new_state.as_string(),
+            );
+
+            // Warn that the particular status was not set
+            if let Err(..) = result {
+                update!(warn &self.update_line => "Unable To Backup Status: {}.", status_id);
+
+            // Otherwise, add the id to the backup items
+            } else {
+                self.backup_items.insert(status_id.clone());
+                self.mark_updated(connection);
+            }
+        }
+    }
+
+    /// A method to backup the event queue on the backup server based on the
+    /// provided coming events and the current time of the provided clock.
+    ///
+    /// # Note
+    ///
+    /// As the backup handler does not hold a copy of the configuration, this
+    /// method does not verify the validity of the event queue in any way.
+    /// It is expected that the calling module will perform this check.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to write to the
+    /// backup.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn backup_events(&self, coming_events: Vec<ComingEvent>, clock: &Clock) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Covert the coming events to queued events
+            let queued_events = BackupHandler::queued_events(coming_events, clock);
+
+            // Try to serialize the coming events
+            let event_string = match serde_yaml::to_string(&queued_events) {
+                Ok(string) => string,
+                Err(error) => {
+                    update!(err &self.update_line => "Unable To Parse Coming Events: {}", error);
+                    return;
+                }
+            };
+
+            // Try to copy the event to the backup
+            let result = connection.write(&format!("{}:queue", self.identifier), &event_string);
+
+            // Warn that the event queue was not set
+            if let Err(..) = result {
+                update!(warn &self.update_line => "Unable To Backup Events.");
+            } else {
+                self.mark_updated(connection);
+            }
+        }
+    }
+
+    /// A method to remove the backup of the provided statuses (e.g. when the
+    /// operator chooses not to resume them).
+    ///
+    /// # Errors
+    ///
+    /// This method will ignore any errors.
+    ///
+    pub fn discard_statuses(&mut self, status_ids: Vec<ItemId>) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to delete each of the statuses
+            for status_id in status_ids {
+                connection.remove(&format!("{}:{}", self.identifier, status_id));
+                self.backup_items.remove(&status_id);
+            }
+        }
+    }
+
+    /// A method to remove the backup of the event queue (e.g. when the
+    /// operator chooses not to resume the queued events).
+    ///
+    /// # Errors
+    ///
+    /// This method will ignore any errors.
+    ///
+    pub fn discard_events(&self) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists, try to delete the queue
+        if let &Some(ref connection) = &self.connection {
+            connection.remove(&format!("{}:queue", self.identifier));
+        }
+    }
+
+    /// A method to return the time since the backup was last updated, if
+    /// the backup exists and the time is known.
+    ///
+    /// # Errors
+    ///
+    /// This function will not raise any errors. A backup from an earlier
+    /// version without an update time has an unknown age.
+    ///
+    pub fn backup_age(&self) -> Option<Duration> {
+        // Read the time of the last update from the backup
+        let updated = match &self.connection {
+            &Some(ref connection) => connection.read(&format!("{}:updated", self.identifier))?,
+            &None => return None,
+        };
+
+        // Compare the time of the last update with the current time
+        let millis = updated.parse::<u64>().ok()?;
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_millis(millis))
+            .ok()
+    }
+
+    /// An internal method to record the time of the latest update to the
+    /// backup (used to report the age of lingering backup data).
+    ///
+    fn mark_updated(&self, connection: &LiveBackup) {
+        // Write the current time in ms since the epoch (ignore any errors)
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::from_secs(0));
+        let millis = now.as_secs() * 1000 + u64::from(now.subsec_millis());
+        connection
+            .write(&format!("{}:updated", self.identifier), &millis.to_string())
+            .unwrap_or(());
+    }
+
+    /// A method to save a named checkpoint of the provided scene, status pairs
+    /// and coming events (timed by the provided clock). A checkpoint with the
+    /// same name is replaced.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if there is no backup connected or
+    /// if it is unable to write to the backup.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn save_checkpoint(
+        &self,
+        name: &str,
+        scene: &ItemId,
+        statuses: Vec<(ItemId, ItemId)>,
+        coming_events: Vec<ComingEvent>,
+        clock: &Clock,
+    ) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            update!(warn &self.update_line => "Unable To Save Checkpoint While On Standby.");
+            return;
+        }
+
+        // Checkpoints require a backup connection
+        let connection = match &self.connection {
+            &Some(ref connection) => connection,
+            &None => {
+                update!(warn &self.update_line => "Checkpoints Require A Backup Server Or Backup File.");
+                return;
+            }
+        };
+
+        // Compose the checkpoint
+        let checkpoint = Checkpoint {
+            name: name.to_string(),
+            saved: SystemTime::now(),
+            scene: *scene,
+            statuses,
+            events: BackupHandler::queued_events(coming_events, clock),
+        };
+
+        // Try to serialize the checkpoint
+        let checkpoint_string = match serde_yaml::to_string(&checkpoint) {
+            Ok(string) => string,
+            Err(error) => {
+                update!(err &self.update_line => "Unable To Parse Checkpoint: {}", error);
+                return;
+            }
+        };
+
+        // Try to copy the checkpoint to the backup
+        let key = checkpoint::checkpoint_key(&self.identifier, name);
+        if let Err(..) = connection.write(&key, &checkpoint_string) {
+            update!(err &self.update_line => "Unable To Save Checkpoint: {}.", name);
+            return;
+        }
+
+        // Add the name to the checkpoint index, if it is new
+        let mut names = self.list_checkpoints();
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+        let result = match serde_yaml::to_string(&names) {
+            Ok(string) => connection.write(&checkpoint::index_key(&self.identifier), &string),
+            Err(error) => Err(error.into()),
+        };
+
+        // Notify of the result
+        match result {
+            Ok(_) => update!(update &self.update_line => "Saved Checkpoint: {}", name),
+            Err(..) => update!(err &self.update_line => "Unable To Save Checkpoint: {}.", name),
+        }
+    }
+
+    /// A method to delete the named checkpoint and remove it from the
+    /// checkpoint index.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the checkpoint does not exist or
+    /// if it is unable to write to the backup.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line.
+    ///
+    pub fn delete_checkpoint(&self, name: &str) {
+        // Never modify the backup of another primary instance
+        if self.is_fenced() {
+            update!(warn &self.update_line => "Unable To Delete Checkpoint While On Standby.");
+            return;
+        }
+
+        // Check that the checkpoint exists
+        let mut names = self.list_checkpoints();
+        if !names.iter().any(|existing| existing == name) {
+            update!(warn &self.update_line => "Checkpoint Not Found: {}", name);
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Remove the name from the checkpoint index
+            names.retain(|existing| existing != name);
+            let result = match serde_yaml::to_string(&names) {
+                Ok(string) => connection.write(&checkpoint::index_key(&self.identifier), &string),
+                Err(error) => Err(error.into()),
+            };
+
+            // Remove the checkpoint itself and notify of the result
+            match result {
+                Ok(_) => {
+                    connection.remove(&checkpoint::checkpoint_key(&self.identifier, name));
+                    update!(update &self.update_line => "Deleted Checkpoint: {}", name);
+                }
+                Err(..) => {
+                    update!(err &self.update_line => "Unable To Delete Checkpoint: {}.", name)
+                }
+            }
+        }
+    }
+
+    /// A method to list the names of the saved checkpoints, in the order they
+    /// were first saved.
+    ///
+    /// # Errors
+    ///
+    /// This function will not raise any errors. If the backup is unavailable,
+    /// the list is empty.
+    ///
+    pub fn list_checkpoints(&self) -> Vec<String> {
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to read and parse the checkpoint index
+            if let Some(index_string) = connection.read(&checkpoint::index_key(&self.identifier)) {
+                if let Ok(names) = serde_yaml::from_str(index_string.as_str()) {
+                    return names;
+                }
+            }
+        }
+
+        // Otherwise, return an empty list
+        Vec::new()
+    }
+
+    /// A method to load the named checkpoint from the backup.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the checkpoint does not exist or
+    /// could not be parsed.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line and returning
+    /// None.
+    ///
+    pub fn load_checkpoint(&self, name: &str) -> Option<Checkpoint> {
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to read the checkpoint
+            if let Some(checkpoint_string) =
+                connection.read(&checkpoint::checkpoint_key(&self.identifier, name))
+            {
+                // Try to parse the checkpoint
+                match serde_yaml::from_str(checkpoint_string.as_str()) {
+                    Ok(checkpoint) => return Some(checkpoint),
+                    Err(error) => {
+                        update!(err &self.update_line => "Unable To Parse Checkpoint: {}", error);
+                        return None;
+                    }
+                }
+            }
+        }
+
+        // Warn that the checkpoint was not found
+        update!(warn &self.update_line => "Checkpoint Not Found: {}.", name);
+        None
+    }
+
+    /// An internal function to convert the provided coming events (timed by
+    /// the provided clock) to queued events with a wall-clock deadline
+    ///
+    fn queued_events(coming_events: Vec<ComingEvent>, clock: &Clock) -> Vec<QueuedEvent> {
+        // Convert each event that has not yet been triggered
+        let now = SystemTime::now();
+        let mut queued_events = Vec::new();
+        for event in coming_events {
+            if let Some(remaining) = event.remaining(clock) {
+                queued_events.push(QueuedEvent {
+                    remaining,
+                    deadline: now.checked_add(remaining),
+                    event_id: event.id(),
+                });
+            }
+        }
+        queued_events
+    }
+
+    /// A function to reload an existing backup from the backup. If the data
+    /// exists, this function returns the existing backup data.
+    ///
+    /// # Errors
+    ///
+    /// This function will not raise any errors. If the backup is unavailable,
+    /// it is treated as empty.
+    ///
+    /// Like all BackupHandler functions and methods, this function will fail
+    /// gracefully by notifying of any errors on the update line and returning
+    /// None.
+    ///
+    pub fn reload_backup(&self, mut status_ids: Vec<ItemId>) -> Option<BackupData> {
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Check to see if there is an existing scene
+            let result = connection.read(&format!("{}:current", self.identifier));
+
+            // If the current scene exists
+            if let Some(current_str) = result {
+                // Try to read the exising event queue
+                let mut queued_events: Vec<QueuedEvent> = Vec::new();
+                let result = connection.read(&format!("{}:queue", self.identifier));
+
+                // If something was received
+                if let Some(queue_string) = result {
+                    // Try to parse the queue
+                    if let Ok(events) = serde_yaml::from_str(queue_string.as_str()) {
+                        queued_events = events;
+                    }
+                }
+
+                // Compile a list of valid status pairs
+                let mut status_pairs: Vec<(ItemId, ItemId)> = Vec::new();
+                for status_id in status_ids.drain(..) {
+                    // Try to read an existing status from the backup
+                    let result = connection.read(&format!("{}:{}", self.identifier, status_id));
+
+                    // If something was received
+                    if let Some(state_str) = result {
+                        // Try to parse the current state id
+                        if let Ok(state_id) = state_str.parse::<u32>() {
+                            // Try to compose the id into an item
+                            if let Some(new_state) = ItemId::new(state_id) {
+                                // Add the status id and new state to the status pairs
+                                status_pairs.push((status_id, new_state));
+                            }
+                        }
+                    }
+                }
+
+                // Try to parse the current scene id
+                if let Ok(current_id) = current_str.parse::<u32>() {
+                    // Try to compose the id into an item
+                    if let Some(current_scene) = ItemId::new(current_id) {
+                        // Return the current scene and status pairs
+                        return Some((current_scene, status_pairs, queued_events));
+                    }
+                }
+            }
+        }
+
+        // Silently return nothing if the connection does not exist or there was not a current scene
+        None
+    }
+}
+
+// Implement the drop trait for the backup handler struct.
+impl Drop for BackupHandler {
+    /// This method removes all the the existing statuses from the backup
+    /// (unless this instance is on standby). The named checkpoints are kept
+    /// until they are deleted by the operator.
+    ///
+    /// # Errors
+    ///
+    /// This method will ignore any errors as it is called only when the backup
+    /// connection is being closed.
+    ///
+    fn drop(&mut self) {
+        // Leave the backup of the primary instance in place
+        if self.is_fenced() {
+            return;
+        }
+
+        // If the backup connection exists
+        if let &Some(ref connection) = &self.connection {
+            // Try to delete the current scene if it exists
+            connection.remove(&format!("{}:current", self.identifier));
+
+            // Try to delete the queue if it exists
+            connection.remove(&format!("{}:queue", self.identifier));
+
+            // Try to delete the time of the last update
+            connection.remove(&format!("{}:updated", self.identifier));
+
+            // Try to delete all the items that were backed up
+            for item in self.backup_items.drain() {
+                connection.remove(&format!("{}:{}", self.identifier, item));
+            }
+        }
+    }
+}
+
+/// Define the BackupBackend Trait
+///
+/// This is a convience trait to standardize reading from and writing to the
+/// backup across all backup types.
+///
+pub trait BackupBackend {
+    /// The read method (returns None if the key does not exist)
+    fn read(&self, key: &str) -> Option<String>;
+
+    /// The write method
+    fn write(&self, key: &str, value: &str) -> Result<(), Error>;
+
+    /// The remove method (ignores any errors)
+    fn remove(&self, key: &str);
+}
+
+// Tests of the status module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test resuming queued events after an outage
+    #[test]
+    fn resume_events() {
+        // Create a queued event which was backed up a minute before the outage ended
+        let now = SystemTime::now();
+        let backed_up = now - Duration::from_secs(60);
+        let event_id = ItemId::new(10).unwrap();
+        let coming = QueuedEvent {
+            remaining: Duration::from_secs(90),
+            deadline: backed_up.checked_add(Duration::from_secs(90)),
+            event_id,
+        };
+        let expired = QueuedEvent {
+            remaining: Duration::from_secs(30),
+            deadline: backed_up.checked_add(Duration::from_secs(30)),
+            event_id,
+        };
+
+        // Verify that the frozen mode ignores the outage
+        assert_eq!(
+            coming.resume(ResumeMode::Frozen, now),
+            ResumedEvent::Delay(Duration::from_secs(90))
+        );
+        assert_eq!(
+            expired.resume(ResumeMode::Frozen, now),
+            ResumedEvent::Delay(Duration::from_secs(30))
+        );
+
+        // Verify that the deadline mode accounts for the outage
+        let fire = ResumeMode::Deadlines {
+            skip_expired: false,
+        };
+        let skip = ResumeMode::Deadlines { skip_expired: true };
+        assert_eq!(
+            coming.resume(fire, now),
+            ResumedEvent::Delay(Duration::from_secs(30))
+        );
+        assert_eq!(expired.resume(fire, now), ResumedEvent::Expired);
+        assert_eq!(expired.resume(skip, now), ResumedEvent::Skipped);
+
+        // Verify that an older backup without a deadline is frozen
+        let old = QueuedEvent {
+            deadline: None,
+            ..expired
+        };
+        assert_eq!(
+            old.resume(skip, now),
+            ResumedEvent::Delay(Duration::from_secs(30))
+        );
+    }
+
+    // Test that checkpoints outlast the backup handler until deleted
+    #[test]
+    fn keep_checkpoints() {
+        // Create a backup handler with a local backup file
+        let path = std::env::temp_dir().join(format!(
+            "minerva_checkpoint_test_{}.mnv",
+            std::process::id()
+        ));
+        let (update_line, _receive) = GeneralUpdate::new();
+        let identifier = ItemId::new(1).unwrap();
+        let scene = ItemId::new(100).unwrap();
+        let open = || {
+            BackupHandler::new(update_line.clone(), identifier, None, Some(path.clone())).unwrap()
+        };
+
+        // Save a checkpoint and close the handler
+        let backup = open();
+        backup.save_checkpoint("Start", &scene, Vec::new(), Vec::new(), &Clock::new());
+        drop(backup);
+
+        // Verify that the checkpoint remains, then delete it
+        let backup = open();
+        assert_eq!(backup.list_checkpoints(), vec!["Start".to_string()]);
+        assert!(backup.load_checkpoint("Start").is_some());
+        backup.delete_checkpoint("Start");
+        assert!(backup.list_checkpoints().is_empty());
+        assert!(backup.load_checkpoint("Start").is_none());
+        drop(backup);
+        std::fs::remove_file(&path).unwrap_or(());
+    }
+
+    // Test that a held backup is left unchanged
+    #[test]
+    fn hold_backup() {
+        // Create a backup handler with a local backup file
+        let path =
+            std::env::temp_dir().join(format!("minerva_hold_test_{}.mnv", std::process::id()));
+        let (update_line, _receive) = GeneralUpdate::new();
+        let identifier = ItemId::new(1).unwrap();
+        let open = || {
+            BackupHandler::new(update_line.clone(), identifier, None, Some(path.clone())).unwrap()
+        };
+
+        // Back up a scene, then hold the backup and change the scene
+        let mut backup = open();
+        backup.backup_current_scene(&ItemId::new(100).unwrap());
+        backup.set_held(true);
+        backup.backup_current_scene(&ItemId::new(200).unwrap());
+
+        // Verify that the original scene survives, even after closing
+        drop(backup);
+        let backup = open();
+        let (scene, _, _) = backup.reload_backup(Vec::new()).unwrap();
+        assert_eq!(scene, ItemId::new(100).unwrap());
+        drop(backup);
+        std::fs::remove_file(&path).unwrap_or(());
+    }
+
+    // FIXME Define tests of this module
+    #[test]
+    fn test_status() {
+        // FIXME: Implement this
+        unimplemented!();
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to store the backup on a Redis server
+//!
+//! The Redis server is also shared between a primary instance and any
+//! standby instances. The primary holds a lease on the server (a key with
+//! its owner token which expires unless renewed) and, once fenced, every
+//! write checks the lease on the server so that an instance which has lost
+//! the lease can never overwrite the backup of the new primary.
+
+// Import the relevant structures into the correct namespace
+use super::BackupBackend;
+
+// Import standard library features
+use std::time::Duration;
+
+// Import the failure features
+use failure::Error;
+
+// Imprt redis client library
+extern crate redis;
+use self::redis::{Commands, RedisResult};
+
+// Define module constants
+const CLOSED: &str = "closed"; // the value of a lease released by a primary which closed normally
+const CLAIM_SCRIPT: &str = "local owner = redis.call('GET', KEYS[1]) \
+    if owner == false or owner == ARGV[3] then \
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2]) return 1 end return 0"; // claim a free or released lease
+const RENEW_SCRIPT: &str = "local owner = redis.call('GET', KEYS[1]) \
+    if owner == ARGV[1] or owner == false then \
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2]) return 1 end return 0"; // renew a lease (unless taken by another)
+const RELEASE_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+    redis.call('SET', KEYS[1], ARGV[2]) return 1 end return 0"; // release a lease, if still held
+const FENCED_WRITE_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+    redis.call('SET', KEYS[2], ARGV[2]) return 1 end return 0"; // write only while holding the lease
+const FENCED_REMOVE_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+    redis.call('DEL', KEYS[2]) return 1 end return 0"; // remove only while holding the lease
+
+/// A structure to hold the connection to the Redis server
+///
+pub struct RedisBackup {
+    connection: redis::Connection,   // the Redis connection
+    fence: Option<(String, String)>, // the lease key and owner token required to write, if fenced
+}
+
+// Implement key functionality for the Redis structure
+impl RedisBackup {
+    /// A function to create a new connection to the Redis server at the
+    /// provided location
+    ///
+    pub fn new(location: &str) -> Result<RedisBackup, Error> {
+        // Try to connect to the Redis server
+        let client = redis::Client::open(location)?;
+
+        // Try to get a copy of the Redis connection
+        let connection = client.get_connection()?;
+        Ok(RedisBackup {
+            connection,
+            fence: None,
+        })
+    }
+
+    /// A method to claim the provided lease for the provided owner if it is
+    /// free or was released (i.e. when an instance starts). Returns true if
+    /// the lease was claimed.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to reach the server.
+    ///
+    pub fn claim(&self, key: &str, owner: &str, lifetime: Duration) -> Result<bool, Error> {
+        let claimed: i64 = redis::cmd("EVAL")
+            .arg(CLAIM_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(owner)
+            .arg(millis(lifetime))
+            .arg(CLOSED)
+            .query(&self.connection)?;
+        Ok(claimed == 1)
+    }
+
+    /// A method to take the provided lease for the provided owner only if
+    /// it has expired (i.e. the previous owner stopped renewing it). Returns
+    /// true if the lease was taken.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to reach the server.
+    ///
+    pub fn take(&self, key: &str, owner: &str, lifetime: Duration) -> Result<bool, Error> {
+        let taken: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(owner)
+            .arg("NX")
+            .arg("PX")
+            .arg(millis(lifetime))
+            .query(&self.connection)?;
+        Ok(taken.is_some())
+    }
+
+    /// A method to renew the provided lease of the provided owner. Returns
+    /// false if another owner holds the lease.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to reach the server.
+    ///
+    pub fn renew(&self, key: &str, owner: &str, lifetime: Duration) -> Result<bool, Error> {
+        let renewed: i64 = redis::cmd("EVAL")
+            .arg(RENEW_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(owner)
+            .arg(millis(lifetime))
+            .query(&self.connection)?;
+        Ok(renewed == 1)
+    }
+
+    /// A method to release the provided lease of the provided owner, marking
+    /// it as released rather than expired (so that no standby takes over)
+    ///
+    /// # Errors
+    ///
+    /// This method will ignore any errors.
+    ///
+    pub fn release(&self, key: &str, owner: &str) {
+        let _: RedisResult<i64> = redis::cmd("EVAL")
+            .arg(RELEASE_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(owner)
+            .arg(CLOSED)
+            .query(&self.connection);
+    }
+
+    /// A method to require the provided lease and owner for every later
+    /// write or removal from this connection
+    ///
+    pub fn fence(&mut self, key: &str, owner: &str) {
+        self.fence = Some((key.to_string(), owner.to_string()));
+    }
+}
+
+/// A helper function to convert a duration to whole milliseconds
+///
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+// Implement the backup backend trait for RedisBackup
+impl BackupBackend for RedisBackup {
+    /// A method to read a value from the server
+    ///
+    fn read(&self, key: &str) -> Option<String> {
+        let result: RedisResult<String> = self.connection.get(key);
+        result.ok()
+    }
+
+    /// A method to write a value to the server
+    ///
+    fn write(&self, key: &str, value: &str) -> Result<(), Error> {
+        // Write the value directly if the connection is not fenced
+        let (lease, owner) = match self.fence {
+            Some((ref lease, ref owner)) => (lease, owner),
+            None => {
+                let _: bool = self.connection.set(key, value)?;
+                return Ok(());
+            }
+        };
+
+        // Otherwise, write the value only while holding the lease
+        let written: i64 = redis::cmd("EVAL")
+            .arg(FENCED_WRITE_SCRIPT)
+            .arg(2)
+            .arg(lease)
+            .arg(key)
+            .arg(owner)
+            .arg(value)
+            .query(&self.connection)?;
+        if written != 1 {
+            return Err(format_err!("This instance is no longer the primary."));
+        }
+        Ok(())
+    }
+
+    /// A method to remove a value from the server
+    ///
+    fn remove(&self, key: &str) {
+        // Ignore any errors (unable to manually specify types)
+        match self.fence {
+            // Remove the value only while holding the lease, if fenced
+            Some((ref lease, ref owner)) => {
+                let _: RedisResult<i64> = redis::cmd("EVAL")
+                    .arg(FENCED_REMOVE_SCRIPT)
+                    .arg(2)
+                    .arg(lease)
+                    .arg(key)
+                    .arg(owner)
+                    .query(&self.connection);
+            }
+
+            // Otherwise, remove the value directly
+            None => {
+                let _: RedisResult<bool> = self.connection.del(key);
+            }
+        }
+    }
+}
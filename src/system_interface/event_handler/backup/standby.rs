@@ -0,0 +1,237 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to run a second instance of the program as a hot standby.
+//!
+//! The primary instance holds a lease on the Redis server: a heartbeat key
+//! with the owner token of the primary which expires unless the primary
+//! renews it. An instance which starts while the lease is held becomes a
+//! standby: it mirrors the backup of the primary and, if the lease expires
+//! (i.e. the primary crashed), it takes the lease and takes over. Because
+//! only the expiry on the server is used, the clocks of the two machines do
+//! not need to agree. A primary which closes normally releases its lease, and
+//! the standby does not take over. A primary which finds its lease taken by
+//! another instance stops writing to the backup.
+
+// Import the relevant structures into the correct namespace
+use super::super::super::{GeneralUpdate, MirrorPrimary, TakeOver};
+use super::redis_backup::RedisBackup;
+use super::{EventUpdate, ItemId};
+
+// Import standard library features
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Import the failure features
+use failure::Error;
+
+// Define module constants
+const HEARTBEAT_RATE: u64 = 1000; // the time between heartbeats in ms
+const FAILOVER_TIMEOUT: u64 = 5000; // the lifetime of the lease (the time without a heartbeat before taking over) in ms
+
+/// A helper function to compose the heartbeat key for the provided identifier
+///
+pub fn heartbeat_key(identifier: &ItemId) -> String {
+    format!("{}:heartbeat", identifier)
+}
+
+/// A helper function to compose a unique owner token for this instance
+///
+pub fn owner_token() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0));
+    format!("{}-{}-{}", process::id(), now.as_secs(), now.subsec_nanos())
+}
+
+/// A helper function to return the lifetime of the lease
+///
+pub fn lease_lifetime() -> Duration {
+    Duration::from_millis(FAILOVER_TIMEOUT)
+}
+
+/// A structure to renew the lease of the primary instance from a background
+/// thread. The lease is released when the structure is dropped, unless
+/// another instance has already taken it.
+///
+pub struct Heartbeat {
+    stop: Option<mpsc::Sender<()>>, // the line to stop the background thread
+    handle: Option<JoinHandle<()>>, // the handle of the background thread
+    is_lost: Arc<AtomicBool>,       // the flag to indicate that another instance took the lease
+}
+
+// Implement key features of the heartbeat
+impl Heartbeat {
+    /// A function to start renewing the lease of the provided owner on the
+    /// Redis server at the provided location.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// Redis server.
+    ///
+    pub fn new(
+        location: &str,
+        identifier: ItemId,
+        owner: String,
+        update_line: GeneralUpdate,
+    ) -> Result<Heartbeat, Error> {
+        // Open a separate connection for the background thread
+        let connection = RedisBackup::new(location)?;
+        let key = heartbeat_key(&identifier);
+        let (stop, receive) = mpsc::channel();
+        let is_lost = Arc::new(AtomicBool::new(false));
+        let thread_lost = is_lost.clone();
+
+        // Spawn the background thread
+        let handle = thread::spawn(move || {
+            // Renew the lease until the heartbeat is dropped
+            let mut is_failing = false;
+            loop {
+                match connection.renew(&key, &owner, lease_lifetime()) {
+                    Ok(true) => is_failing = false,
+
+                    // Stop writing if another instance took the lease
+                    Ok(false) => {
+                        update!(err &update_line => "Another Instance Has Taken Over As The Primary. No Longer Writing To The Backup.");
+                        thread_lost.store(true, Ordering::SeqCst);
+                        return;
+                    }
+
+                    // Warn only once if the heartbeat fails
+                    Err(_) => {
+                        if !is_failing {
+                            update!(err &update_line => "Unable To Send Heartbeat To Backup Server.");
+                        }
+                        is_failing = true;
+                    }
+                }
+
+                // Wait for the next heartbeat or the signal to stop
+                match receive.recv_timeout(Duration::from_millis(HEARTBEAT_RATE)) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    _ => break,
+                }
+            }
+
+            // Release the lease so that a standby does not take over
+            connection.release(&key, &owner);
+        });
+
+        // Return the heartbeat
+        Ok(Heartbeat {
+            stop: Some(stop),
+            handle: Some(handle),
+            is_lost,
+        })
+    }
+
+    /// A method to check if another instance has taken the lease
+    ///
+    pub fn is_lost(&self) -> bool {
+        self.is_lost.load(Ordering::SeqCst)
+    }
+}
+
+// Implement drop for the heartbeat
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        // Signal the background thread to stop and wait for it to finish
+        self.stop = None;
+        if let Some(handle) = self.handle.take() {
+            handle.join().unwrap_or(());
+        }
+    }
+}
+
+/// A structure to watch the lease of the primary instance from a background
+/// thread. While the primary holds the lease, the monitor regularly requests
+/// that the standby mirror the backup. If the lease expires, the monitor
+/// takes the lease for the provided owner, requests that the standby take
+/// over and then finishes.
+///
+pub struct StandbyMonitor {
+    _stop: mpsc::Sender<()>, // the line to stop the background thread when dropped
+}
+
+// Implement key features of the standby monitor
+impl StandbyMonitor {
+    /// A function to start watching the primary instance on the Redis server
+    /// at the provided location.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to connect to the
+    /// Redis server.
+    ///
+    pub fn new(
+        location: &str,
+        identifier: ItemId,
+        owner: String,
+        general_update: GeneralUpdate,
+    ) -> Result<StandbyMonitor, Error> {
+        // Open a separate connection for the background thread
+        let connection = RedisBackup::new(location)?;
+        let key = heartbeat_key(&identifier);
+        let (stop, receive) = mpsc::channel::<()>();
+
+        // Spawn the background thread
+        thread::spawn(move || {
+            // Check the lease until the monitor is dropped
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                receive.recv_timeout(Duration::from_millis(HEARTBEAT_RATE))
+            {
+                match connection.take(&key, &owner, lease_lifetime()) {
+                    // If the lease has expired and is now ours, take over
+                    Ok(true) => {
+                        update!(err &general_update => "Primary Instance Stopped Responding. Taking Over ...");
+                        general_update.send_system(TakeOver);
+                        break;
+                    }
+
+                    // Otherwise, mirror the primary (or wait for a new primary)
+                    _ => general_update.send_system(MirrorPrimary),
+                }
+            }
+        });
+
+        // Return the monitor
+        Ok(StandbyMonitor { _stop: stop })
+    }
+}
+
+// Tests of the standby module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that each instance receives a different owner token
+    #[test]
+    fn unique_owner() {
+        // Compose several tokens
+        let first = owner_token();
+        thread::sleep(Duration::from_millis(1));
+        let second = owner_token();
+
+        // Verify that they are different and never match the released lease
+        assert_ne!(first, second);
+        assert_ne!(first, "closed");
+    }
+}
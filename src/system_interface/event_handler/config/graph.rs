@@ -0,0 +1,362 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module converts a configuration into a Graphviz DOT graph of the
+//! event flow. Scenes, events, and statuses become nodes and each event
+//! action which links one item to another becomes an edge.
+//!
+//! The resulting text can be rendered with any Graphviz-compatible tool
+//! (e.g. `dot -Tsvg flow.dot -o flow.svg`).
+
+// Import the relevant structures into the correct namespace
+use super::super::event::{
+    CancelEvent, EventDetail, GroupedEvent, ModifyStatus, NewScene, QueueEvent,
+};
+use super::super::item::{ItemDescription, ItemId};
+use super::status::StatusMap;
+use super::Scene;
+
+// Import standard library features
+use std::fmt::Write;
+use std::time::Duration;
+
+// Import FNV HashMap
+extern crate fnv;
+use self::fnv::FnvHashMap;
+
+/// A function to compose the event flow graph of a configuration in the DOT
+/// language.
+///
+/// Scenes are drawn as folders, statuses as ellipses (listing their allowed
+/// states), and events as boxes. Queued events are drawn as solid edges
+/// labelled with their delay, cancelled events as dashed edges, scene changes
+/// as bold edges, status changes as edges labelled with the new state, and
+/// each branch of a grouped event as a dotted edge labelled with the state
+/// that selects it.
+///
+/// All nodes and edges are sorted by id so that the output is stable between
+/// exports of the same configuration.
+///
+pub fn to_dot(
+    all_scenes: &FnvHashMap<ItemId, Scene>,
+    status_map: &StatusMap,
+    lookup: &FnvHashMap<ItemId, ItemDescription>,
+    events: &FnvHashMap<ItemId, EventDetail>,
+) -> String {
+    // Start the graph
+    let mut dot = String::new();
+    writeln!(dot, "digraph minerva {{").unwrap_or(());
+    writeln!(dot, "    rankdir=LR;").unwrap_or(());
+    writeln!(dot, "    node [fontname=\"Helvetica\"];").unwrap_or(());
+    writeln!(dot, "    edge [fontname=\"Helvetica\", fontsize=10];").unwrap_or(());
+
+    // Add a node for each scene
+    writeln!(dot).unwrap_or(());
+    writeln!(dot, "    // Scenes").unwrap_or(());
+    for scene_id in sorted_ids(all_scenes.keys()) {
+        writeln!(
+            dot,
+            "    {} [shape=folder, style=filled, fillcolor=\"#CCE3F5\", label=\"{}\"];",
+            node_name(&scene_id),
+            node_label(lookup, &scene_id)
+        )
+        .unwrap_or(());
+    }
+
+    // Add a node for each status, listing the allowed states
+    writeln!(dot).unwrap_or(());
+    writeln!(dot, "    // Statuses").unwrap_or(());
+    for status_id in sorted_ids(status_map.keys()) {
+        // Compose the list of allowed states
+        let mut states = String::new();
+        if let Some(detail) = status_map.get(&status_id) {
+            for state in detail.allowed() {
+                states.push_str(&format!("\\n- {}", node_label(lookup, &state)));
+            }
+        }
+
+        // Add the node
+        writeln!(
+            dot,
+            "    {} [shape=ellipse, style=filled, fillcolor=\"#F5E6B8\", label=\"{}{}\"];",
+            node_name(&status_id),
+            node_label(lookup, &status_id),
+            states
+        )
+        .unwrap_or(());
+    }
+
+    // Add a node for each event that is not already a scene or a status
+    writeln!(dot).unwrap_or(());
+    writeln!(dot, "    // Events").unwrap_or(());
+    let mut event_ids = sorted_ids(events.keys());
+    for scene in all_scenes.values() {
+        event_ids.extend(scene.events.iter().cloned());
+    }
+    event_ids.sort_unstable();
+    event_ids.dedup();
+    for event_id in event_ids.iter() {
+        if !all_scenes.contains_key(event_id) && !status_map.contains_key(event_id) {
+            writeln!(
+                dot,
+                "    {} [shape=box, label=\"{}\"];",
+                node_name(event_id),
+                node_label(lookup, event_id)
+            )
+            .unwrap_or(());
+        }
+    }
+
+    // Add the edges for each event detail
+    writeln!(dot).unwrap_or(());
+    writeln!(dot, "    // Actions").unwrap_or(());
+    for event_id in sorted_ids(events.keys()) {
+        // Unpack each action in the event detail
+        let source = node_name(&event_id);
+        for action in events.get(&event_id).into_iter().flatten() {
+            match action {
+                // Draw a bold edge to the new scene
+                &NewScene { ref new_scene } => {
+                    writeln!(
+                        dot,
+                        "    {} -> {} [style=bold, color=\"#338DD6\"];",
+                        source,
+                        node_name(new_scene)
+                    )
+                    .unwrap_or(());
+                }
+
+                // Draw an edge to the status, labelled with the new state
+                &ModifyStatus {
+                    ref status_id,
+                    ref new_state,
+                } => {
+                    writeln!(
+                        dot,
+                        "    {} -> {} [color=\"#C4A000\", label=\"{}\"];",
+                        source,
+                        node_name(status_id),
+                        node_label(lookup, new_state)
+                    )
+                    .unwrap_or(());
+                }
+
+                // Draw an edge to the queued event, labelled with the delay
+                &QueueEvent { ref event } => {
+                    let label = match event.delay() {
+                        Some(delay) => format_delay(&delay),
+                        None => "now".to_string(),
+                    };
+                    writeln!(
+                        dot,
+                        "    {} -> {} [label=\"{}\"];",
+                        source,
+                        node_name(&event.id()),
+                        label
+                    )
+                    .unwrap_or(());
+                }
+
+                // Draw a dashed edge to the cancelled event
+                &CancelEvent { ref event } => {
+                    writeln!(
+                        dot,
+                        "    {} -> {} [style=dashed, color=\"#E3240E\", label=\"cancel\"];",
+                        source,
+                        node_name(event)
+                    )
+                    .unwrap_or(());
+                }
+
+                // Draw a dotted edge for each branch of the grouped event
+                &GroupedEvent {
+                    ref status_id,
+                    ref event_map,
+                } => {
+                    for state in sorted_ids(event_map.keys()) {
+                        if let Some(target) = event_map.get(&state) {
+                            writeln!(
+                                dot,
+                                "    {} -> {} [style=dotted, label=\"{} = {}\"];",
+                                source,
+                                node_name(target),
+                                node_label(lookup, status_id),
+                                node_label(lookup, &state)
+                            )
+                            .unwrap_or(());
+                        }
+                    }
+                }
+
+                // Ignore any actions which do not link to other items
+                _ => (),
+            }
+        }
+    }
+
+    // Close the graph
+    writeln!(dot, "}}").unwrap_or(());
+    dot
+}
+
+/// A helper function to return a sorted copy of the provided ids
+///
+fn sorted_ids<'a, I>(ids: I) -> Vec<ItemId>
+where
+    I: Iterator<Item = &'a ItemId>,
+{
+    let mut sorted: Vec<ItemId> = ids.cloned().collect();
+    sorted.sort_unstable();
+    sorted
+}
+
+/// A helper function to compose the DOT node name for an item
+///
+fn node_name(id: &ItemId) -> String {
+    format!("item_{}", id)
+}
+
+/// A helper function to compose the escaped DOT label for an item
+///
+fn node_label(lookup: &FnvHashMap<ItemId, ItemDescription>, id: &ItemId) -> String {
+    // Use the description, if available
+    let label = match lookup.get(id) {
+        Some(description) => format!("{} ({})", description.description, id),
+        None => format!("{}", id),
+    };
+
+    // Escape any characters that are special inside a quoted string
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A helper function to format a delay as a short, human-readable label
+///
+fn format_delay(delay: &Duration) -> String {
+    // Split the delay into minutes, seconds, and milliseconds
+    let minutes = delay.as_secs() / 60;
+    let seconds = delay.as_secs() % 60;
+    let millis = delay.subsec_millis();
+
+    // Compose the label
+    let mut label = String::new();
+    if minutes > 0 {
+        label.push_str(&format!("{}m ", minutes));
+    }
+    if millis > 0 {
+        label.push_str(&format!("{}.{:03}s", seconds, millis));
+    } else {
+        label.push_str(&format!("{}s", seconds));
+    }
+    label
+}
+
+// Tests of the graph module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the generation of a simple event flow graph
+    #[test]
+    fn simple_graph() {
+        // Import features for testing
+        use super::super::super::event::EventDelay;
+        use super::super::super::item::Hidden;
+        use super::super::status::StatusDetail;
+        use std::iter::FromIterator;
+
+        // Create the ids
+        let scene = ItemId::new(100).unwrap();
+        let status = ItemId::new(20).unwrap();
+        let state_one = ItemId::new(21).unwrap();
+        let state_two = ItemId::new(22).unwrap();
+        let first = ItemId::new(2).unwrap();
+        let second = ItemId::new(3).unwrap();
+
+        // Create the lookup
+        let mut lookup = FnvHashMap::default();
+        lookup.insert(scene, ItemDescription::new("Scene", Hidden));
+        lookup.insert(status, ItemDescription::new("Status", Hidden));
+        lookup.insert(state_one, ItemDescription::new("One", Hidden));
+        lookup.insert(state_two, ItemDescription::new("Two", Hidden));
+        lookup.insert(first, ItemDescription::new("First \"Event\"", Hidden));
+        lookup.insert(second, ItemDescription::new("Second", Hidden));
+
+        // Create the scene and status map
+        let mut all_scenes = FnvHashMap::default();
+        all_scenes.insert(
+            scene,
+            Scene {
+                events: FromIterator::from_iter(vec![scene, first, second]),
+                key_map: None,
+            },
+        );
+        let mut status_map = StatusMap::default();
+        status_map.insert(
+            status,
+            serde_yaml::from_str::<StatusDetail>(
+                "MultiState: {current: {id: 21}, allowed: [{id: 21}, {id: 22}]}",
+            )
+            .unwrap(),
+        );
+
+        // Create the events
+        let mut event_map = FnvHashMap::default();
+        event_map.insert(state_one, first);
+        event_map.insert(state_two, second);
+        let mut events = FnvHashMap::default();
+        events.insert(
+            first,
+            vec![
+                QueueEvent {
+                    event: EventDelay::new(Some(Duration::from_secs(90)), second),
+                },
+                ModifyStatus {
+                    status_id: status,
+                    new_state: state_two,
+                },
+            ],
+        );
+        events.insert(
+            second,
+            vec![
+                CancelEvent { event: first },
+                GroupedEvent {
+                    status_id: status,
+                    event_map,
+                },
+            ],
+        );
+        events.insert(scene, vec![NewScene { new_scene: scene }]);
+
+        // Compose the graph
+        let dot = to_dot(&all_scenes, &status_map, &lookup, &events);
+
+        // Check the nodes
+        assert!(dot.starts_with("digraph minerva {"));
+        assert!(dot.contains("item_100 [shape=folder"));
+        assert!(dot.contains("item_20 [shape=ellipse"));
+        assert!(dot.contains("item_2 [shape=box, label=\"First \\\"Event\\\" (2)\"];"));
+
+        // Check the edges
+        assert!(dot.contains("item_2 -> item_3 [label=\"1m 30s\"];"));
+        assert!(dot.contains("item_2 -> item_20 [color=\"#C4A000\", label=\"Two (22)\"];"));
+        assert!(dot.contains("item_3 -> item_2 [style=dashed"));
+        assert!(dot.contains("item_3 -> item_2 [style=dotted, label=\"Status (20) = One (21)\"];"));
+        assert!(dot.contains("item_3 -> item_3 [style=dotted, label=\"Status (20) = Two (22)\"];"));
+        assert!(dot.contains("item_100 -> item_100 [style=bold"));
+    }
+}
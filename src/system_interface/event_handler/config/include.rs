@@ -0,0 +1,413 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module loads the parts of a configuration which are kept in separate
+//! files, so that a large configuration can be split up (e.g. one file for
+//! each area of a venue or each puzzle).
+//!
+//! The configuration lists the other files under includes (relative to the
+//! configuration file). Each included file may define system connections,
+//! scenes, statuses and items (with their event details) in the same layout
+//! as the configuration itself. Included files written for an older version
+//! are upgraded along with the configuration. These are merged into the
+//! configuration, and any scene, status or item defined in more than one file
+//! is an error. When the configuration is saved, each part is written back to
+//! the file it came from.
+
+// Import the relevant structures into the correct namespace
+use super::super::super::system_connection::ConnectionSet;
+use super::super::super::GeneralUpdate;
+use super::super::event::{EventDetail, EventUpdate};
+use super::super::item::{ItemId, ItemPair};
+use super::migration;
+use super::status::StatusMap;
+use super::{Scene, YamlConfig};
+
+// Import standard library features
+use std::collections::hash_map::Entry;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Import the failure crate
+use failure::Error;
+
+// Import FNV HashMap and HashSet
+extern crate fnv;
+use self::fnv::{FnvHashMap, FnvHashSet};
+
+// Import YAML processing library
+extern crate serde_yaml;
+use self::serde_yaml::Value;
+
+/// A structure to define the layout of an included file
+///
+#[derive(Serialize, Deserialize)]
+pub struct YamlInclude {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    system_connection: ConnectionSet, // the connection(s) to the underlying system
+    #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+    all_scenes: FnvHashMap<ItemId, Scene>, // hash map of the scenes in this file
+    #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+    status_map: StatusMap, // hash map of the statuses in this file
+    #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+    event_set: FnvHashMap<ItemPair, Option<EventDetail>>, // hash map of the item pairs and event details in this file
+}
+
+/// A structure to note the parts of the configuration which were loaded from
+/// an included file
+///
+#[derive(Clone)]
+pub struct Include {
+    pub path: PathBuf, // the location of the file, relative to the configuration
+    system_connection: ConnectionSet, // the connections defined in the file
+    scenes: FnvHashSet<ItemId>, // the scenes defined in the file
+    statuses: FnvHashSet<ItemId>, // the statuses defined in the file
+    items: FnvHashSet<ItemId>, // the items (and event details) defined in the file
+}
+
+/// A structure to hold an included file which has been read but not yet
+/// merged into the configuration
+///
+pub struct IncludedFile {
+    pub path: PathBuf,     // the location of the file, relative to the configuration
+    pub text: String,      // the text of the file
+    pub contents: Value,   // the contents of the file (upgraded and with any names replaced)
+    pub is_upgraded: bool, // a flag to indicate that the contents were upgraded
+    pub is_named: bool,    // a flag to indicate that names were replaced in the contents
+}
+
+/// A function to read the files included by the provided configuration.
+/// The included files are found relative to the provided configuration
+/// folder, and any file from an older version is upgraded (assuming the
+/// original version of the configuration, unless the file lists its own).
+///
+/// # Errors
+///
+/// This function will raise an error if it is unable to open one of the
+/// included files or if one of the files is not valid YAML.
+///
+pub fn read_includes(
+    general_update: &GeneralUpdate,
+    config: &Value,
+    config_folder: &Path,
+    config_version: &str,
+) -> Result<Vec<IncludedFile>, Error> {
+    // Find the list of included files (any other layout is reported when the configuration is parsed)
+    let paths: Vec<PathBuf> = match config.get("includes") {
+        Some(Value::Sequence(paths)) => paths
+            .iter()
+            .filter_map(|path| path.as_str().map(PathBuf::from))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // Read each included file in order
+    let mut included = Vec::new();
+    for path in paths {
+        // Try to read and parse the file
+        let mut text = String::new();
+        if let Err(error) = File::open(config_folder.join(&path))
+            .and_then(|mut file| file.read_to_string(&mut text))
+        {
+            update!(err general_update => "Unable To Open Included File {}: {}", path.display(), error);
+            return Err(format_err!(
+                "Unable to open included file {}: {}",
+                path.display(),
+                error
+            ));
+        }
+
+        // Upgrade a file from an older version, noting each change
+        let version = env!("CARGO_PKG_VERSION");
+        let upgrade = migration::upgrade_include(&text, config_version, version);
+        let (contents, is_upgraded) = match upgrade {
+            Some(upgrade) => {
+                update!(warn general_update => "Included File {} Upgraded From Version {} To {}. Save The Configuration To Keep The Changes.", path.display(), upgrade.version, version);
+                for change in upgrade.changes.iter() {
+                    update!(update general_update => "{}: {}", path.display(), change);
+                }
+                (upgrade.config, true)
+            }
+            None => match serde_yaml::from_str(&text) {
+                Ok(contents) => (contents, false),
+                Err(error) => {
+                    update!(err general_update => "Unable To Parse Included File {}: {}", path.display(), error);
+                    return Err(format_err!(
+                        "Unable to parse included file {}: {}",
+                        path.display(),
+                        error
+                    ));
+                }
+            },
+        };
+        included.push(IncludedFile {
+            path,
+            text,
+            contents,
+            is_upgraded,
+            is_named: false,
+        });
+    }
+
+    // Return the included files
+    Ok(included)
+}
+
+/// A function to merge the included files into the configuration.
+///
+/// # Errors
+///
+/// This function will raise an error if it is unable to parse one of the
+/// included files or if any scene, status or item is defined in more than
+/// one file.
+///
+pub fn merge_includes(
+    general_update: &GeneralUpdate,
+    yaml_config: &mut YamlConfig,
+    included: Vec<IncludedFile>,
+) -> Result<Vec<Include>, Error> {
+    // Merge each included file in order
+    let mut includes = Vec::new();
+    for included_file in included {
+        // Try to parse the file (from the text if possible, to report the location of any error)
+        let path = included_file.path;
+        let parsed = if included_file.is_upgraded || included_file.is_named {
+            serde_yaml::from_value(included_file.contents)
+        } else {
+            serde_yaml::from_str(&included_file.text)
+        };
+        let yaml_include: YamlInclude = match parsed {
+            Ok(yaml_include) => yaml_include,
+            Err(error) => {
+                update!(err general_update => "Unable To Parse Included File {}: {}", path.display(), error);
+                return Err(format_err!(
+                    "Unable to parse included file {}: {}",
+                    path.display(),
+                    error
+                ));
+            }
+        };
+
+        // Add the connections
+        let mut include = Include {
+            path: path.clone(),
+            system_connection: yaml_include.system_connection.clone(),
+            scenes: FnvHashSet::default(),
+            statuses: FnvHashSet::default(),
+            items: FnvHashSet::default(),
+        };
+        yaml_config
+            .system_connection
+            .extend(yaml_include.system_connection);
+
+        // Add the scenes (each may only be defined once)
+        for (id, scene) in yaml_include.all_scenes {
+            match yaml_config.all_scenes.entry(id) {
+                Entry::Occupied(_) => {
+                    update!(err general_update => "Scene {} Is Defined Again In {}.", id, path.display());
+                    return Err(format_err!(
+                        "Scene {} is defined again in {}.",
+                        id,
+                        path.display()
+                    ));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(scene);
+                    include.scenes.insert(id);
+                }
+            }
+        }
+
+        // Add the statuses (each may only be defined once)
+        for (id, status) in yaml_include.status_map {
+            match yaml_config.status_map.entry(id) {
+                Entry::Occupied(_) => {
+                    update!(err general_update => "Status {} Is Defined Again In {}.", id, path.display());
+                    return Err(format_err!(
+                        "Status {} is defined again in {}.",
+                        id,
+                        path.display()
+                    ));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(status);
+                    include.statuses.insert(id);
+                }
+            }
+        }
+
+        // Add the items (each may only be defined once, and item pairs match by id)
+        for (item_pair, detail) in yaml_include.event_set {
+            let id = item_pair.get_id();
+            match yaml_config.event_set.entry(item_pair) {
+                Entry::Occupied(_) => {
+                    update!(err general_update => "Item {} Is Defined Again In {}.", id, path.display());
+                    return Err(format_err!(
+                        "Item {} is defined again in {}.",
+                        id,
+                        path.display()
+                    ));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(detail);
+                    include.items.insert(id);
+                }
+            }
+        }
+        includes.push(include);
+    }
+
+    // Return the details of the included files
+    Ok(includes)
+}
+
+/// A function to separate the parts of a configuration which came from the
+/// included files, leaving the rest in the configuration. New parts of the
+/// configuration remain in the configuration.
+///
+pub fn split_includes(
+    yaml_config: &mut YamlConfig,
+    includes: &[Include],
+) -> Vec<(PathBuf, YamlInclude)> {
+    // Return each part to the file it came from
+    let mut yaml_includes = Vec::new();
+    for include in includes.iter() {
+        // Separate the scenes and statuses
+        let mut yaml_include = YamlInclude {
+            system_connection: include.system_connection.clone(),
+            all_scenes: FnvHashMap::default(),
+            status_map: FnvHashMap::default(),
+            event_set: FnvHashMap::default(),
+        };
+        for id in include.scenes.iter() {
+            if let Some(scene) = yaml_config.all_scenes.remove(id) {
+                yaml_include.all_scenes.insert(*id, scene);
+            }
+        }
+        for id in include.statuses.iter() {
+            if let Some(status) = yaml_config.status_map.remove(id) {
+                yaml_include.status_map.insert(*id, status);
+            }
+        }
+
+        // Separate the items
+        let item_pairs: Vec<ItemPair> = yaml_config
+            .event_set
+            .keys()
+            .filter(|item_pair| include.items.contains(&item_pair.get_id()))
+            .cloned()
+            .collect();
+        for item_pair in item_pairs {
+            if let Some(detail) = yaml_config.event_set.remove(&item_pair) {
+                yaml_include.event_set.insert(item_pair, detail);
+            }
+        }
+        yaml_includes.push((include.path.clone(), yaml_include));
+    }
+
+    // Remove the connections which came from the included files (only the
+    // last copy of each, as these follow the connections of the configuration)
+    for include in includes.iter().rev() {
+        for connection in include.system_connection.iter().rev() {
+            if let Some(index) = yaml_config
+                .system_connection
+                .iter()
+                .rposition(|existing| existing == connection)
+            {
+                yaml_config.system_connection.remove(index);
+            }
+        }
+    }
+
+    // Return the included parts
+    yaml_includes
+}
+
+// Tests of the include module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test merging and separating an included file
+    #[test]
+    fn merge_and_split() {
+        // Import features for testing
+        use std::env;
+        use std::fs;
+
+        // Write an included file which defines a new scene and one which repeats an item
+        let folder = env::temp_dir().join(format!("minerva_include_test_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let connection =
+            "  - ZmqPrimary:\n      send_path: tcp://*:5555\n      recv_path: tcp://*:5556\n";
+        fs::write(
+            folder.join("area.mnv"),
+            "system_connection:\n".to_string()
+                + connection
+                + "all_scenes:\n  ? id: 300\n  : events:\n      - id: 301\n\
+             event_set:\n  ? id: 300\n    description: Area\n    display: Hidden\n  : []\n  \
+             ? id: 301\n    description: Area Event\n    display: Hidden\n  : []\n",
+        )
+        .unwrap();
+        fs::write(
+            folder.join("repeat.mnv"),
+            "event_set:\n  ? id: 1\n    description: Repeated\n    display: Hidden\n  : []\n",
+        )
+        .unwrap();
+
+        // Load the default configuration (with the same connection as the included file)
+        let config_string = fs::read_to_string("default.mnv").unwrap();
+        let area_string = config_string.replace(
+            "system_connection: []\n",
+            &format!("system_connection:\n{}", connection),
+        ) + "includes:\n  - area.mnv\n";
+        let config: Value = serde_yaml::from_str(&area_string).unwrap();
+        let mut yaml_config: YamlConfig = serde_yaml::from_str(&area_string).unwrap();
+        let original_length = yaml_config.event_set.len();
+        let (general_update, receive) = GeneralUpdate::new();
+        let included = read_includes(&general_update, &config, &folder, "0.8.0").unwrap();
+        let includes = merge_includes(&general_update, &mut yaml_config, included).unwrap();
+
+        // Check the merged configuration
+        assert!(yaml_config
+            .all_scenes
+            .contains_key(&ItemId::new(300).unwrap()));
+        assert_eq!(yaml_config.event_set.len(), original_length + 2);
+        assert_eq!(yaml_config.system_connection.len(), 2);
+        assert_eq!(receive.try_iter().count(), 0);
+
+        // Separate the included file again
+        let yaml_includes = split_includes(&mut yaml_config, &includes);
+        assert_eq!(yaml_includes.len(), 1);
+        assert_eq!(yaml_includes[0].0, PathBuf::from("area.mnv"));
+        assert_eq!(yaml_includes[0].1.all_scenes.len(), 1);
+        assert_eq!(yaml_includes[0].1.event_set.len(), 2);
+        assert_eq!(yaml_includes[0].1.system_connection.len(), 1);
+        assert_eq!(yaml_config.event_set.len(), original_length);
+        assert_eq!(yaml_config.system_connection.len(), 1);
+
+        // Verify that an item defined again is an error
+        let repeat_string = config_string + "includes:\n  - repeat.mnv\n";
+        let config: Value = serde_yaml::from_str(&repeat_string).unwrap();
+        let mut yaml_config: YamlConfig = serde_yaml::from_str(&repeat_string).unwrap();
+        let included = read_includes(&general_update, &config, &folder, "0.8.0").unwrap();
+        fs::remove_dir_all(&folder).unwrap_or(());
+        assert!(merge_includes(&general_update, &mut yaml_config, included).is_err());
+        assert_eq!(receive.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module upgrades configurations written for older versions of the
+//! program to the current layout of the configuration file.
+//!
+//! Each schema lists the first version with a new layout and the function
+//! which converts a configuration from the previous layout. A configuration
+//! is passed through every newer schema in order, so that a configuration
+//! from any older version can be loaded and saved again in the current
+//! layout. Each step reports the changes it made.
+
+// Import standard library features
+use std::cmp::Ordering;
+
+// Import YAML processing library
+extern crate serde_yaml;
+use self::serde_yaml::{Mapping, Value};
+
+/// A structure to define one layout of the configuration file
+///
+struct Schema {
+    version: (u32, u32, u32), // the first version of the program with this layout
+    upgrade: fn(&mut Mapping) -> Vec<String>, // the function to convert a configuration from the previous layout
+}
+
+/// The layouts of the configuration file, in order
+///
+const SCHEMAS: [Schema; 2] = [
+    Schema {
+        version: (0, 7, 0),
+        upgrade: upgrade_scenes_and_actions,
+    },
+    Schema {
+        version: (0, 8, 0),
+        upgrade: upgrade_displays_and_connections,
+    },
+];
+
+/// A structure to hold an upgraded configuration
+///
+pub struct Upgrade {
+    pub version: String,      // the original version of the configuration
+    pub changes: Vec<String>, // a description of each change
+    pub config: Value,        // the configuration in the current layout
+}
+
+/// A function to upgrade the text of a configuration file to the current
+/// layout, one schema at a time.
+///
+/// The function returns None if the configuration is already in the current
+/// layout, or if the version of the configuration cannot be read (the
+/// configuration is then parsed as it is).
+///
+pub fn upgrade(config_string: &str, current_version: &str) -> Option<Upgrade> {
+    // Try to read the configuration and its version
+    let mut config = match serde_yaml::from_str(config_string) {
+        Ok(Value::Mapping(config)) => config,
+        _ => return None,
+    };
+    let version = config.get(&key("version"))?.as_str()?.to_string();
+
+    // Upgrade the configuration and mark it with the current version
+    let changes = upgrade_mapping(&mut config, &version)?;
+    config.insert(key("version"), Value::String(current_version.to_string()));
+    Some(Upgrade {
+        version,
+        changes,
+        config: Value::Mapping(config),
+    })
+}
+
+/// A function to upgrade the text of an included file to the current layout,
+/// one schema at a time. Included files are assumed to be written for the
+/// same version as the configuration, unless they list their own version.
+///
+/// The function returns None if the included file is already in the current
+/// layout, or if the version cannot be read (the file is then parsed as it
+/// is).
+///
+pub fn upgrade_include(
+    include_string: &str,
+    config_version: &str,
+    current_version: &str,
+) -> Option<Upgrade> {
+    // Try to read the included file and its version (if listed)
+    let mut include = match serde_yaml::from_str(include_string) {
+        Ok(Value::Mapping(include)) => include,
+        _ => return None,
+    };
+    let listed = include
+        .get(&key("version"))
+        .and_then(|version| version.as_str());
+    let version = listed.unwrap_or(config_version).to_string();
+    let is_listed = listed.is_some();
+
+    // Upgrade the file and mark it with the current version (if it listed one)
+    let changes = upgrade_mapping(&mut include, &version)?;
+    if is_listed {
+        include.insert(key("version"), Value::String(current_version.to_string()));
+    }
+    Some(Upgrade {
+        version,
+        changes,
+        config: Value::Mapping(include),
+    })
+}
+
+/// An internal function to pass a configuration (or an included file)
+/// through every schema newer than the provided version. Returns None if the
+/// version cannot be read or there were no changes.
+///
+fn upgrade_mapping(config: &mut Mapping, version: &str) -> Option<Vec<String>> {
+    // Upgrade the configuration through every newer schema
+    let original = parse_version(version)?;
+    let mut changes = Vec::new();
+    for schema in SCHEMAS.iter() {
+        if original.cmp(&schema.version) == Ordering::Less {
+            let (major, minor, patch) = schema.version;
+            for change in (schema.upgrade)(config) {
+                changes.push(format!(
+                    "Upgraded To Version {}.{}.{}: {}",
+                    major, minor, patch, change
+                ));
+            }
+        }
+    }
+
+    // Return nothing if there were no changes
+    if changes.is_empty() {
+        return None;
+    }
+    Some(changes)
+}
+
+/// An internal function to read a version string (e.g. 0.6.3)
+///
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    // Read up to three numbers, defaulting to zero
+    let mut numbers = version.trim().split('.').map(|number| number.parse().ok());
+    let major = numbers.next()??;
+    let minor = numbers.next().unwrap_or(Some(0))?;
+    let patch = numbers.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// An internal function to create a mapping key from a field name
+///
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+/// An internal function to upgrade configurations before version 0.7.0.
+/// Scenes were a list of events (now the events field of the scene, beside
+/// the optional key map), and each event detail was a single action (now a
+/// list of actions, with each triggered event a separate queued event).
+///
+fn upgrade_scenes_and_actions(config: &mut Mapping) -> Vec<String> {
+    // Move the events of each scene into the events field
+    let mut changes = Vec::new();
+    let mut scene_count = 0;
+    if let Some(Value::Mapping(scenes)) = config.get_mut(&key("all_scenes")) {
+        for (_, scene) in scenes.iter_mut() {
+            if let Value::Sequence(events) = scene {
+                let mut new_scene = Mapping::new();
+                new_scene.insert(key("events"), Value::Sequence(events.clone()));
+                *scene = Value::Mapping(new_scene);
+                scene_count += 1;
+            }
+        }
+    }
+    if scene_count > 0 {
+        changes.push(format!(
+            "Moved The Events Of {} Scene(s) Into The Events Field.",
+            scene_count
+        ));
+    }
+
+    // Convert each event detail into a list of actions
+    let mut detail_count = 0;
+    let mut trigger_count = 0;
+    if let Some(Value::Mapping(event_set)) = config.get_mut(&key("event_set")) {
+        for (_, detail) in event_set.iter_mut() {
+            // Skip items without an event detail
+            let action = match detail {
+                Value::Mapping(action) => action.clone(),
+                _ => continue,
+            };
+
+            // Expand the triggered events into queued events
+            let mut actions = Vec::new();
+            for (variant, fields) in action.into_iter() {
+                if variant == key("TriggerEvents") {
+                    if let Some(Value::Sequence(events)) = fields.get("events") {
+                        for event in events.iter() {
+                            let mut queue_event = Mapping::new();
+                            queue_event.insert(key("event"), event.clone());
+                            let mut new_action = Mapping::new();
+                            new_action.insert(key("QueueEvent"), Value::Mapping(queue_event));
+                            actions.push(Value::Mapping(new_action));
+                        }
+                    }
+                    trigger_count += 1;
+
+                // Keep any other action as it is
+                } else {
+                    let mut new_action = Mapping::new();
+                    new_action.insert(variant, fields);
+                    actions.push(Value::Mapping(new_action));
+                }
+            }
+            *detail = Value::Sequence(actions);
+            detail_count += 1;
+        }
+    }
+    if detail_count > 0 {
+        changes.push(format!(
+            "Converted {} Event Detail(s) Into Lists Of Actions.",
+            detail_count
+        ));
+    }
+    if trigger_count > 0 {
+        changes.push(format!(
+            "Replaced {} TriggerEvents Action(s) With QueueEvent Actions.",
+            trigger_count
+        ));
+    }
+    changes
+}
+
+/// An internal function to upgrade configurations before version 0.8.0.
+/// The display priority of each item was renamed to position (and the group
+/// of debug items from group_id to group), and DMX connections gained a list
+/// of fades for the all stop.
+///
+fn upgrade_displays_and_connections(config: &mut Mapping) -> Vec<String> {
+    // Rename the fields of each display type (the items are keys, so the event set is rebuilt)
+    let mut changes = Vec::new();
+    let mut priority_count = 0;
+    let mut group_count = 0;
+    if let Some(Value::Mapping(event_set)) = config.get_mut(&key("event_set")) {
+        let old_set = event_set.clone();
+        event_set.clear();
+        for (mut item, detail) in old_set.into_iter() {
+            if let Some(Value::Mapping(display_type)) = item
+                .as_mapping_mut()
+                .and_then(|item| item.get_mut(&key("display")))
+            {
+                for (variant, fields) in display_type.iter_mut() {
+                    if let Value::Mapping(fields) = fields {
+                        // Rename the priority to position
+                        if let Some(priority) = fields.remove(&key("priority")) {
+                            fields.insert(key("position"), priority);
+                            priority_count += 1;
+                        }
+
+                        // Rename the group of debug items
+                        if *variant == key("DisplayDebug") {
+                            if let Some(group) = fields.remove(&key("group_id")) {
+                                fields.insert(key("group"), group);
+                                group_count += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            event_set.insert(item, detail);
+        }
+    }
+    if priority_count > 0 {
+        changes.push(format!(
+            "Renamed The Display Priority Of {} Item(s) To Position.",
+            priority_count
+        ));
+    }
+    if group_count > 0 {
+        changes.push(format!(
+            "Renamed The Group Of {} Debug Item(s) From group_id To group.",
+            group_count
+        ));
+    }
+
+    // Add an empty all stop list to each DMX connection
+    let mut dmx_count = 0;
+    if let Some(Value::Sequence(connections)) = config.get_mut(&key("system_connection")) {
+        for connection in connections.iter_mut() {
+            if let Some(Value::Mapping(fields)) = connection
+                .as_mapping_mut()
+                .and_then(|connection| connection.get_mut(&key("DmxSerial")))
+            {
+                if !fields.contains_key(&key("all_stop_dmx")) {
+                    fields.insert(key("all_stop_dmx"), Value::Sequence(Vec::new()));
+                    dmx_count += 1;
+                }
+            }
+        }
+    }
+    if dmx_count > 0 {
+        changes.push(format!(
+            "Added An Empty All Stop List To {} DMX Connection(s).",
+            dmx_count
+        ));
+    }
+    changes
+}
+
+// Tests of the migration module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test upgrading the example configuration from version 0.6.3
+    #[test]
+    fn upgrade_example() {
+        // Import features for testing
+        use super::super::YamlConfig;
+        use std::fs;
+
+        // Upgrade the example configuration
+        let config_string = fs::read_to_string("examples/example_config.mnv").unwrap();
+        let upgraded = upgrade(&config_string, "0.8.2").expect("Configuration was not upgraded.");
+
+        // Check the changes
+        assert_eq!(upgraded.version, "0.6.3");
+        assert_eq!(
+            upgraded.changes,
+            vec![
+                "Upgraded To Version 0.7.0: Moved The Events Of 2 Scene(s) Into The Events Field.",
+                "Upgraded To Version 0.7.0: Converted 10 Event Detail(s) Into Lists Of Actions.",
+                "Upgraded To Version 0.7.0: Replaced 9 TriggerEvents Action(s) With QueueEvent Actions.",
+                "Upgraded To Version 0.8.0: Renamed The Display Priority Of 6 Item(s) To Position.",
+                "Upgraded To Version 0.8.0: Renamed The Group Of 1 Debug Item(s) From group_id To group.",
+            ]
+        );
+
+        // Check that the configuration now loads
+        let config: YamlConfig = serde_yaml::from_value(upgraded.config).unwrap();
+        assert_eq!(config.version, "0.8.2");
+        assert_eq!(config.event_set.len(), 13);
+
+        // Leave a current configuration alone
+        assert!(upgrade(&config_string.replace("0.6.3", "0.8.0"), "0.8.2").is_none());
+    }
+
+    // Test upgrading an included file with the version of its configuration
+    #[test]
+    fn upgrade_included() {
+        // Write an included file in the layout before version 0.7.0
+        let include_string = "all_scenes:\n  ? id: 300\n  : - id: 301\n";
+
+        // Upgrade the file with the version of the configuration
+        let upgraded =
+            upgrade_include(include_string, "0.6.3", "0.8.2").expect("File was not upgraded.");
+        assert_eq!(upgraded.version, "0.6.3");
+        assert_eq!(upgraded.changes.len(), 1);
+        assert!(upgraded.config.get("version").is_none());
+
+        // Prefer the version listed in the file
+        let listed = format!("version: 0.7.0\n{}", include_string);
+        assert!(upgrade_include(&listed, "0.6.3", "0.8.2").is_none());
+        assert!(upgrade_include(include_string, "0.8.0", "0.8.2").is_none());
+    }
+}
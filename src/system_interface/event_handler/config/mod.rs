@@ -20,31 +20,49 @@
 //! program.
 
 // Reexport the key structures and types
+pub use self::schema::compose_schema;
+pub use self::sequence::SequenceAction;
 pub use self::status::{FullStatus, StatusDescription};
 
 // Define private submodules
+mod graph;
+mod include;
+mod migration;
+mod names;
+mod schema;
+mod sequence;
 mod status;
 
 // Import the relevant structures into the correct namespace
-use self::status::{StatusHandler, StatusMap};
+use self::include::Include;
+use self::names::{NameMap, Names};
+use self::sequence::{SequenceHandler, SequenceMap, SequenceMode};
+use self::status::{StateChange, StatusDetail, StatusHandler, StatusMap};
+use super::super::report::{GameReport, SessionRecord};
 use super::super::system_connection::ConnectionSet;
-use super::super::{ChangeSettings, DisplaySetting, GeneralUpdate, InterfaceUpdate};
+use super::super::web_console::ConsoleSettings;
+use super::super::{
+    ChangeSettings, DisplaySetting, GeneralUpdate, InterfaceUpdate, LogSettings, ReportSettings,
+};
+use super::backup::{ResumeMode, ResumePolicy};
 use super::event::{
     CancelEvent, EventDetail, EventUpdate, GroupedEvent, ModifyStatus, NewScene, QueueEvent,
-    SaveData, SendData,
+    SaveData, SaveReport, SendData,
 };
 use super::item::{Hidden, ItemDescription, ItemId, ItemPair};
+use super::write_atomic;
 
 // Import standard library features
+use std::fs::DirBuilder;
 use std::fs::File;
 use std::io::Read;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // Import the failure crate
 use failure::Error;
@@ -63,6 +81,15 @@ const BACKGROUND_POLLING: u64 = 100; // the polling rate for the background proc
 type KeyMapId = FnvHashMap<u32, ItemId>;
 pub type KeyMap = FnvHashMap<u32, ItemPair>;
 
+/// Define a parsed configuration with its description lookup, event details
+/// and included files
+type ParsedConfig = (
+    YamlConfig,
+    FnvHashMap<ItemId, ItemDescription>,
+    FnvHashMap<ItemId, EventDetail>,
+    Vec<Include>,
+);
+
 /// A structure to define the parameters of a scene
 ///
 #[derive(Clone, Serialize, Deserialize)]
@@ -73,7 +100,7 @@ pub struct Scene {
 
 /// A struct to define the elements of a background process
 ///
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct BackgroundProcess {
     process: PathBuf,       // the location (relative or absolute) of the process to run
     arguments: Vec<String>, // any arguments to pass to the process
@@ -210,12 +237,30 @@ struct YamlConfig {
     version: String,    // a version tag to warn the user of incompatible versions
     identifier: ItemId, // unique identifier for the program instance
     server_location: Option<String>, // the location of the backup server, if specified
+    backup_path: Option<PathBuf>, // the location of the local backup file, if specified
+    resume_mode: Option<ResumeMode>, // how to resume queued events from a backup, if specified
     system_connection: ConnectionSet, // the type of connection(s) to the underlying system
     background_process: Option<BackgroundProcess>, // an option background process to run
     default_scene: Option<ItemId>, // the starting scene for the configuration
     fullscreen: Option<bool>, // whether the interface should begin fullscreen
     all_scenes: FnvHashMap<ItemId, Scene>, // hash map of all availble scenes
     status_map: StatusMap, // hash map of the default game status
+    #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+    sequences: SequenceMap, // hash map of the sequences to watch for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    report: Option<ReportSettings>, // the events of interest for the game report, if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    logging: Option<LogSettings>, // the rotation, retention and location of the log files, if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    headless_resume: Option<ResumePolicy>, // the parts of a backup to resume without a window, if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metrics: Option<String>, // the address to serve the metrics (e.g. 127.0.0.1:9185), if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    web_console: Option<ConsoleSettings>, // the address and token of the web console, if specified
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    includes: Vec<PathBuf>, // the files with more connections, scenes, statuses and items (relative to this file)
+    #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+    names: NameMap, // the numeric id of each symbolic name used in place of an id
     event_set: FnvHashMap<ItemPair, Option<EventDetail>>, // hash map of all the item pairs and event details
 } // Private struct to allow deserialization of the configuration
 
@@ -228,10 +273,20 @@ pub struct Config {
     identifier: ItemId,               // unique identifier for the program instance
     system_connection: ConnectionSet, // the type of connection(s) to the underlying system
     server_location: Option<String>,  // the location of the backup server, if specified
+    backup_path: Option<PathBuf>,     // the location of the local backup file, if specified
+    resume_mode: Option<ResumeMode>,  // how to resume queued events from a backup, if specified
     background_thread: Option<BackgroundThread>, // a copy of the background process info
+    headless_resume: Option<ResumePolicy>, // the parts of a backup to resume without a window
     current_scene: ItemId,            // identifier for the current scene
     all_scenes: FnvHashMap<ItemId, Scene>, // hash map of all availble scenes
     status_handler: StatusHandler,    // status handler for the current game status
+    sequence_handler: SequenceHandler, // sequence handler for sequences of events
+    report: Option<ReportSettings>,   // the events of interest for the game report, if specified
+    logging: Option<LogSettings>,     // the settings of the log files, if specified
+    metrics: Option<String>,          // the address to serve the metrics, if specified
+    web_console: Option<ConsoleSettings>, // the address and token of the web console, if specified
+    includes: Vec<Include>,           // the parts of the configuration loaded from other files
+    names: NameMap,                   // the numeric id of each symbolic name
     lookup: FnvHashMap<ItemId, ItemDescription>, // hash map of all the item descriptions
     events: FnvHashMap<ItemId, EventDetail>, // hash map of all the item details
     general_update: GeneralUpdate,    // line to provide updates to the higher-level system
@@ -263,8 +318,149 @@ impl Config {
     pub fn from_config(
         general_update: GeneralUpdate,
         interface_send: mpsc::Sender<InterfaceUpdate>,
-        mut config_file: &File,
+        config_file: &File,
+        config_folder: &Path,
+    ) -> Result<Config, Error> {
+        Config::compose(
+            general_update,
+            interface_send,
+            config_file,
+            config_folder,
+            None,
+        )
+    }
+
+    /// A function to create a new config from a configuration file to
+    /// replace the provided (previous) configuration. The background process
+    /// of the previous configuration is kept running if its definition has
+    /// not changed. Otherwise, it is stopped before the new one starts.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file and will raise a warning if there is an internal
+    /// consistency problem with the provided configuration. On an error, the
+    /// previous configuration is left unchanged.
+    ///
+    pub fn from_reload(
+        previous: &mut Config,
+        general_update: GeneralUpdate,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+        config_file: &File,
+        config_folder: &Path,
+    ) -> Result<Config, Error> {
+        Config::compose(
+            general_update,
+            interface_send,
+            config_file,
+            config_folder,
+            Some(previous),
+        )
+    }
+
+    /// An internal function to create a new config from a configuration file,
+    /// taking over the background process of the previous configuration (if
+    /// provided and unchanged).
+    ///
+    fn compose(
+        general_update: GeneralUpdate,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+        config_file: &File,
+        config_folder: &Path,
+        previous: Option<&mut Config>,
     ) -> Result<Config, Error> {
+        // Try to read, parse and verify the configuration file (and any included files)
+        let (yaml_config, lookup, events, includes) =
+            Config::parse(&general_update, config_file, config_folder)?;
+        let all_scenes = yaml_config.all_scenes;
+        let status_map = yaml_config.status_map;
+        let sequences = yaml_config.sequences;
+        let report = yaml_config.report;
+
+        // Create the new status handler and sequence handler
+        let status_handler = StatusHandler::new(general_update.clone(), status_map);
+        let sequence_handler = SequenceHandler::new(sequences);
+
+        // Try to load the default scene (already verified)
+        let mut current_scene = ItemId::all_stop(); // an invalid scene id
+        if let Some(scene_id) = yaml_config.default_scene {
+            // Update the current scene id if the scene_id is valid
+            if all_scenes.contains_key(&scene_id) {
+                current_scene = scene_id;
+            }
+        }
+
+        // Keep the previous background process, if it is unchanged
+        let mut background_thread = None;
+        let previous_thread = previous.and_then(|config| config.background_thread.take());
+        if let Some(thread) = previous_thread {
+            if Some(&thread.background_process) == yaml_config.background_process.as_ref() {
+                background_thread = Some(thread);
+
+            // Otherwise, stop the previous background process
+            } else {
+                drop(thread);
+            }
+        }
+
+        // Try to start the background process and monitor it, if specified
+        if background_thread.is_none() {
+            if let Some(background_process) = yaml_config.background_process.clone() {
+                background_thread =
+                    BackgroundThread::new(background_process, general_update.clone());
+            }
+        }
+
+        // Adjust fullscreen, if specified
+        if let Some(fullscreen) = yaml_config.fullscreen {
+            interface_send
+                .send(ChangeSettings {
+                    display_setting: DisplaySetting::FullScreen(fullscreen),
+                })
+                .unwrap_or(());
+        }
+
+        // Return the new configuration
+        Ok(Config {
+            identifier: yaml_config.identifier,
+            system_connection: yaml_config.system_connection,
+            server_location: yaml_config.server_location,
+            backup_path: yaml_config.backup_path,
+            resume_mode: yaml_config.resume_mode,
+            headless_resume: yaml_config.headless_resume,
+            background_thread,
+            current_scene,
+            all_scenes,
+            status_handler,
+            sequence_handler,
+            report,
+            logging: yaml_config.logging,
+            metrics: yaml_config.metrics,
+            web_console: yaml_config.web_console,
+            includes,
+            names: yaml_config.names,
+            lookup,
+            events,
+            general_update,
+        })
+    }
+
+    /// An internal function to read, parse and verify a configuration file
+    /// (merging any included files from the provided configuration folder),
+    /// returning the configuration with its description lookup, event details
+    /// and included files.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file and will raise a warning if there is an internal
+    /// consistency problem with the provided configuration.
+    ///
+    fn parse(
+        general_update: &GeneralUpdate,
+        mut config_file: &File,
+        config_folder: &Path,
+    ) -> Result<ParsedConfig, Error> {
         // Try to read from the configuration file
         let mut config_string = String::new();
         match config_file.read_to_string(&mut config_string) {
@@ -275,8 +471,61 @@ impl Config {
             }
         }
 
-        // Try to parse the configuration file
-        let yaml_config: YamlConfig = match serde_yaml::from_str(config_string.as_str()) {
+        // Upgrade a configuration from an older version, noting each change
+        let version = env!("CARGO_PKG_VERSION");
+        let (mut config, original_version) = match migration::upgrade(&config_string, version) {
+            Some(upgrade) => {
+                update!(warn general_update => "Configuration Upgraded From Version {} To {}. Save The Configuration To Keep The Changes.", upgrade.version, version);
+                for change in upgrade.changes.iter() {
+                    update!(update general_update => "{}", change);
+                }
+                (upgrade.config, Some(upgrade.version))
+            }
+            None => match serde_yaml::from_str(config_string.as_str()) {
+                Ok(config) => (config, None),
+                Err(error) => {
+                    update!(err general_update => "Unable To Parse Configuration File: {}", error);
+                    return Err(format_err!("Unable to parse configuration file: {}", error));
+                }
+            },
+        };
+        let is_upgraded = original_version.is_some();
+
+        // Read any included files (upgrading them from the original version of the configuration)
+        let config_version = original_version
+            .or_else(|| {
+                config
+                    .get("version")
+                    .and_then(|version| version.as_str())
+                    .map(|version| version.to_string())
+            })
+            .unwrap_or_else(|| version.to_string());
+        let mut included =
+            include::read_includes(general_update, &config, config_folder, &config_version)?;
+
+        // Replace any symbolic names in the configuration and the included files
+        let mut documents = vec![&config];
+        documents.extend(included.iter().map(|included_file| &included_file.contents));
+        let mut names = Names::new(&documents);
+        let is_named = names.resolve(&mut config, &config_string, None);
+        for included_file in included.iter_mut() {
+            included_file.is_named = names.resolve(
+                &mut included_file.contents,
+                &included_file.text,
+                Some(&included_file.path),
+            );
+        }
+        if is_named {
+            names.record(&mut config);
+        }
+
+        // Try to parse the configuration file (from the text if possible, to report the location of any error)
+        let parsed = if is_upgraded || is_named {
+            serde_yaml::from_value(config)
+        } else {
+            serde_yaml::from_str(config_string.as_str())
+        };
+        let mut yaml_config: YamlConfig = match parsed {
             Ok(config) => config,
             Err(error) => {
                 update!(err general_update => "Unable To Parse Configuration File: {}", error);
@@ -285,11 +534,13 @@ impl Config {
         };
 
         // Check the version id and warn the user if they differ
-        let version = env!("CARGO_PKG_VERSION");
         if &yaml_config.version != version {
             update!(warn general_update => "Version Of Configuration ({}) Does Not Match Software Version ({})", &yaml_config.version, version);
         }
 
+        // Merge any included files into the configuration
+        let includes = include::merge_includes(general_update, &mut yaml_config, included)?;
+
         // Turn the ItemPairs in to the lookup and event set
         let mut lookup = FnvHashMap::default();
         let mut events = FnvHashMap::default();
@@ -313,53 +564,162 @@ impl Config {
         }
 
         // Verify the configuration is defined correctly
-        let all_scenes = yaml_config.all_scenes;
-        let status_map = yaml_config.status_map;
-        Config::verify_config(&general_update, &all_scenes, &status_map, &lookup, &events);
-
-        // Create the new status handler
-        let status_handler = StatusHandler::new(general_update.clone(), status_map);
+        Config::verify_config(
+            general_update,
+            &yaml_config.all_scenes,
+            &yaml_config.status_map,
+            &yaml_config.sequences,
+            &yaml_config.report,
+            &lookup,
+            &events,
+            names.unknown(),
+        );
 
-        // Try to load the default scene
-        let mut current_scene = ItemId::all_stop(); // an invalid scene id
-        if let Some(scene_id) = yaml_config.default_scene {
-            // Check to see if the scene_id is valid and warn of an error
-            if let Some(..) = all_scenes.get(&scene_id) {
-                // Update the current scene id
-                current_scene = scene_id;
-            } else {
+        // Check to see if the default scene is valid and warn of an error
+        if let Some(ref scene_id) = yaml_config.default_scene {
+            if !yaml_config.all_scenes.contains_key(scene_id) {
                 update!(warn general_update => "Current Scene Is Not Defined.")
             }
         }
 
-        // Try to start the background process and monitor it, if specified
-        let mut background_thread = None;
-        if let Some(background_process) = yaml_config.background_process.clone() {
-            background_thread = BackgroundThread::new(background_process, general_update.clone());
-        }
+        // Return the configuration with the lookup, event set and included files
+        Ok((yaml_config, lookup, events, includes))
+    }
 
-        // Adjust fullscreen, if specified
-        if let Some(fullscreen) = yaml_config.fullscreen {
-            interface_send
-                .send(ChangeSettings {
-                    display_setting: DisplaySetting::FullScreen(fullscreen),
-                })
-                .unwrap_or(());
+    /// A function to check a configuration file without loading it, raising
+    /// the same errors and warnings as loading it would.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file and will raise a warning if there is an internal
+    /// consistency problem with the provided configuration.
+    ///
+    pub fn check(
+        general_update: &GeneralUpdate,
+        config_file: &File,
+        config_folder: &Path,
+    ) -> Result<(), Error> {
+        Config::parse(general_update, config_file, config_folder).map(|_| ())
+    }
+
+    /// A function to compose the event flow graph of a configuration file in
+    /// the Graphviz DOT format, without loading the configuration.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file.
+    ///
+    pub fn compose_graph(
+        general_update: &GeneralUpdate,
+        config_file: &File,
+        config_folder: &Path,
+    ) -> Result<String, Error> {
+        // Try to read the configuration file
+        let (yaml_config, lookup, events, _) =
+            Config::parse(general_update, config_file, config_folder)?;
+
+        // Compose the graph from the configuration
+        Ok(graph::to_dot(
+            &yaml_config.all_scenes,
+            &yaml_config.status_map,
+            &lookup,
+            &events,
+        ))
+    }
+
+    /// A function to compose the game report of a session from the contents
+    /// of a structured game log, without loading the configuration. The
+    /// statuses start from their default states and follow the status
+    /// changes of the session.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file or if the session is not in the game log.
+    ///
+    pub fn compose_report(
+        general_update: &GeneralUpdate,
+        config_file: &File,
+        config_folder: &Path,
+        log: &str,
+        session: Option<&str>,
+    ) -> Result<GameReport, Error> {
+        // Try to read the configuration file
+        let (yaml_config, lookup, _, _) =
+            Config::parse(general_update, config_file, config_folder)?;
+
+        // Try to rebuild the session from the game log
+        let record = match SessionRecord::from_log(log, session) {
+            Some(record) => record,
+            None => {
+                update!(err general_update => "Session Not Found In Game Log.");
+                return Err(format_err!("Session not found in game log."));
+            }
+        };
+
+        // Find the statuses at the end of the session
+        let get_description = |item_id: &ItemId| match lookup.get(item_id) {
+            Some(description) => description.clone(),
+            None => ItemDescription::new("No Description.", Hidden),
+        };
+        let status_handler = StatusHandler::new(general_update.clone(), yaml_config.status_map);
+        let statuses = record.final_statuses(status_handler.get_full_status(get_description));
+
+        // Compose the report at the end of the session
+        let settings = yaml_config.report.unwrap_or_else(ReportSettings::new);
+        Ok(record.compose(&settings, statuses, record.end(), get_description))
+    }
+
+    /// A function to compose a configuration file in the layout of the
+    /// current version, without loading the configuration. Returns the
+    /// composed configuration and each composed included file (with its path
+    /// relative to the configuration).
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to parse the
+    /// configuration file or any of the included files.
+    ///
+    pub fn compose_migration(
+        general_update: &GeneralUpdate,
+        config_file: &File,
+        config_folder: &Path,
+    ) -> Result<(String, Vec<(PathBuf, String)>), Error> {
+        // Try to read the configuration file and update the version
+        let (mut yaml_config, _, _, includes) =
+            Config::parse(general_update, config_file, config_folder)?;
+        let names = yaml_config.names.clone();
+        yaml_config.version = env!("CARGO_PKG_VERSION").to_string();
+
+        // Try to compose the parts from the included files
+        let mut include_strings = Vec::new();
+        for (path, yaml_include) in include::split_includes(&mut yaml_config, &includes) {
+            match names::compose(&yaml_include, &names) {
+                Ok(include_string) => include_strings.push((path, include_string)),
+                Err(error) => {
+                    update!(err general_update => "Unable To Parse Included File {}: {}", path.display(), error);
+                    return Err(format_err!(
+                        "Unable to parse included file {}: {}",
+                        path.display(),
+                        error
+                    ));
+                }
+            }
         }
 
-        // Return the new configuration
-        Ok(Config {
-            identifier: yaml_config.identifier,
-            system_connection: yaml_config.system_connection,
-            server_location: yaml_config.server_location,
-            background_thread,
-            current_scene,
-            all_scenes,
-            status_handler,
-            lookup,
-            events,
-            general_update,
-        })
+        // Try to compose the configuration
+        match names::compose(&yaml_config, &names) {
+            Ok(config_string) => Ok((config_string, include_strings)),
+            Err(error) => {
+                update!(err general_update => "Unable To Parse Current Configuration: {}", error);
+                Err(format_err!(
+                    "Unable to parse current configuration: {}",
+                    error
+                ))
+            }
+        }
     }
 
     /// A method to return the identifier for this program instance.
@@ -379,6 +739,88 @@ impl Config {
         self.server_location.clone()
     }
 
+    /// A method to return the location of the local backup file
+    pub fn backup_path(&self) -> Option<PathBuf> {
+        self.backup_path.clone()
+    }
+
+    /// A method to return the default way to resume queued events from a
+    /// backup (the operator chooses when the backup is resumed). Defaults to
+    /// resuming with the frozen remaining times.
+    ///
+    pub fn resume_mode(&self) -> ResumeMode {
+        self.resume_mode.unwrap_or(ResumeMode::Frozen)
+    }
+
+    /// A method to return the parts of a backup to resume when running
+    /// without a window (there is no operator to choose). Defaults to
+    /// resuming the whole backup.
+    ///
+    pub fn headless_resume(&self) -> ResumePolicy {
+        self.headless_resume.unwrap_or(ResumePolicy {
+            scene: true,
+            statuses: true,
+            events: true,
+        })
+    }
+
+    /// A method to return the events of interest for the game report.
+    /// Defaults to timing every event from the start of the session.
+    ///
+    pub fn report_settings(&self) -> ReportSettings {
+        self.report.clone().unwrap_or_else(ReportSettings::new)
+    }
+
+    /// A method to return the rotation, retention and location of the log
+    /// files, if specified
+    ///
+    pub fn log_settings(&self) -> Option<LogSettings> {
+        self.logging.clone()
+    }
+
+    /// A method to change the rotation, retention and location of the log
+    /// files (saved with the configuration)
+    ///
+    pub fn set_log_settings(&mut self, settings: LogSettings) {
+        self.logging = Some(settings);
+    }
+
+    /// A method to return the address to serve the metrics, if specified
+    ///
+    pub fn metrics_address(&self) -> Option<String> {
+        self.metrics.clone()
+    }
+
+    /// A method to return the address and token of the web console, if
+    /// specified
+    ///
+    pub fn web_console(&self) -> Option<ConsoleSettings> {
+        self.web_console.clone()
+    }
+
+    /// A method to return the files included by this configuration (relative
+    /// to the folder of the configuration)
+    ///
+    pub fn included_files(&self) -> Vec<PathBuf> {
+        self.includes
+            .iter()
+            .map(|include| include.path.clone())
+            .collect()
+    }
+
+    /// A method to check if the provided id is a scene in this configuration
+    ///
+    pub fn is_scene(&self, scene_id: &ItemId) -> bool {
+        self.all_scenes.contains_key(scene_id)
+    }
+
+    /// A method to check if the provided id has an event detail in this
+    /// configuration
+    ///
+    pub fn is_event(&self, event_id: &ItemId) -> bool {
+        self.events.contains_key(event_id)
+    }
+
     /// A method to return the description of a particular item from the lookup.
     ///
     /// # Errors
@@ -416,7 +858,9 @@ impl Config {
     }
 
     /// A method to silently update the status of the system based on a previous
-    /// backup.
+    /// backup. Restored states skip the transition events (they already ran
+    /// when the state was first reached), and derived statuses are recalculated
+    /// from the restored states rather than restored directly.
     ///
     /// # Errors
     ///
@@ -429,10 +873,26 @@ impl Config {
     ///
     pub fn load_backup_status(&mut self, mut status_pairs: Vec<(ItemId, ItemId)>) {
         // For every status in the status pairs, set the current value
+        let mut restored = Vec::new();
         for (status_id, new_state) in status_pairs.drain(..) {
-            self.status_handler.modify_status(&status_id, &new_state);
+            // Skip the derived statuses (recalculated from their inputs below)
+            if self.status_handler.is_derived(&status_id) {
+                continue;
+            }
 
-            // Notify the system of the successful status change
+            // Note only the successful status changes
+            if let Some(new_state) = self.status_handler.restore_status(&status_id, &new_state) {
+                restored.push((status_id, new_state));
+            }
+        }
+
+        // Bring the derived statuses up to date with the restored states
+        for change in self.status_handler.refresh_derived() {
+            restored.push((change.status_id, change.new_state));
+        }
+
+        // Notify the system of each successful status change
+        for (status_id, new_state) in restored {
             let status_pair =
                 ItemPair::from_item(status_id.clone(), self.get_description(&status_id));
             let state_pair =
@@ -637,8 +1097,9 @@ impl Config {
     }
 
     /// A method to modify a status state within the current scene based
-    /// on the provided status id and new state. Return the new state, or
-    /// None if the state was not changed successfully.
+    /// on the provided status id and new state. Return the list of state
+    /// changes (the requested change followed by any changes to derived
+    /// statuses), or None if the state was not changed successfully.
     ///
     /// # Errors
     ///
@@ -650,23 +1111,53 @@ impl Config {
     /// gracefully by notifying of errors on the update line and making no
     /// modifications to the current scene.
     ///
-    pub fn modify_status(&mut self, status_id: &ItemId, new_state: &ItemId) -> Option<ItemId> {
+    pub fn modify_status(
+        &mut self,
+        status_id: &ItemId,
+        new_state: &ItemId,
+    ) -> Option<Vec<StateChange>> {
         // Try to update the underlying status
-        if let Some(new_id) = self.status_handler.modify_status(&status_id, &new_state) {
-            // Notify the system of the successful status change
-            let status_pair =
-                ItemPair::from_item(status_id.clone(), self.get_description(&status_id));
-            let state_pair = ItemPair::from_item(new_id.clone(), self.get_description(&new_id));
-            update!(status &self.general_update => status_pair, state_pair.clone());
+        if let Some(changes) = self.status_handler.modify_status(&status_id, &new_state) {
+            // Notify the system of each successful status change
+            for change in changes.iter() {
+                let status_pair = ItemPair::from_item(
+                    change.status_id.clone(),
+                    self.get_description(&change.status_id),
+                );
+                let state_pair = ItemPair::from_item(
+                    change.new_state.clone(),
+                    self.get_description(&change.new_state),
+                );
+                update!(status &self.general_update => status_pair, state_pair);
+            }
 
             // Indicate success
-            return Some(new_id);
+            return Some(changes);
         }
 
         // Indicate failure
         None
     }
 
+    /// A method to pass a processed event to every sequence in the
+    /// configuration at the provided time. Returns the actions (success and
+    /// failure events to trigger, and timeouts to start or cancel) which
+    /// result.
+    ///
+    pub fn process_sequences(&mut self, event_id: &ItemId, now: Instant) -> Vec<SequenceAction> {
+        self.sequence_handler.process_event(event_id, now)
+    }
+
+    /// A method to clear the progress of every sequence in progress. Returns
+    /// the description of each sequence which was reset and the actions to
+    /// cancel their timeouts.
+    ///
+    pub fn reset_sequences(&mut self) -> (Vec<ItemDescription>, Vec<SequenceAction>) {
+        let (ids, actions) = self.sequence_handler.reset();
+        let descriptions = ids.iter().map(|id| self.get_description(id)).collect();
+        (descriptions, actions)
+    }
+
     /// A method to delete the item description within the current lookup.
     ///
     /// # Errors
@@ -828,7 +1319,9 @@ impl Config {
         }
     }
 
-    /// A method to write the current configuration to a file.
+    /// A method to write the current configuration to a file. The parts of
+    /// the configuration loaded from other files are written back to those
+    /// files, found relative to the provided configuration folder.
     ///
     /// # Errors
     ///
@@ -840,7 +1333,7 @@ impl Config {
     /// gracefully by notifying of errors on the update line and making no
     /// modifications to the file.
     ///
-    pub fn to_config(&self, mut config_file: &File) {
+    pub fn to_config(&self, mut config_file: &File, config_folder: &Path) {
         // Convert the configuration to YamlConfig
         let mut lookup = Vec::new();
         for (item, name) in self.lookup.iter() {
@@ -874,21 +1367,55 @@ impl Config {
         };
 
         // Create a YAML config from the elements
-        let yaml_config = YamlConfig {
+        let mut yaml_config = YamlConfig {
             version: env!("CARGO_PKG_VERSION").to_string(),
             identifier: self.identifier(),
             server_location: self.server_location.clone(),
+            backup_path: self.backup_path.clone(),
+            resume_mode: self.resume_mode,
+            headless_resume: self.headless_resume,
             system_connection: self.system_connection.clone(),
             background_process,
             default_scene: Some(self.current_scene.clone()),
             fullscreen: None, // default to no entry, must be manually changed
             all_scenes: self.all_scenes.clone(),
             status_map: self.status_handler.get_map(),
+            sequences: self.sequence_handler.get_map(),
+            report: self.report.clone(),
+            logging: self.logging.clone(),
+            metrics: self.metrics.clone(),
+            web_console: self.web_console.clone(),
+            includes: self.included_files(),
+            names: self.names.clone(),
             event_set,
         };
 
+        // Write the parts from other files back to those files
+        for (path, yaml_include) in include::split_includes(&mut yaml_config, &self.includes) {
+            // Try to parse the included part
+            let include_string = match names::compose(&yaml_include, &self.names) {
+                Ok(include_string) => include_string,
+                Err(error) => {
+                    update!(err &self.general_update => "Unable To Parse Included File {}: {}", path.display(), error);
+                    continue;
+                }
+            };
+
+            // Try to write it to the file (creating the folder, if necessary)
+            let include_path = config_folder.join(&path);
+            if let Some(folder) = include_path.parent() {
+                DirBuilder::new()
+                    .recursive(true)
+                    .create(folder)
+                    .unwrap_or(());
+            }
+            if let Err(error) = write_atomic(&include_path, &include_string) {
+                update!(err &self.general_update => "Unable To Write Included File {}: {}", path.display(), error);
+            }
+        }
+
         // Try to parse the configuration
-        let config_string = match serde_yaml::to_string(&yaml_config) {
+        let config_string = match names::compose(&yaml_config, &self.names) {
             Ok(config_string) => config_string,
             Err(error) => {
                 update!(err &self.general_update => "Unable To Parse Current Configuration: {}", error);
@@ -903,6 +1430,35 @@ impl Config {
         }
     }
 
+    /// A method to write the event flow graph of the current configuration
+    /// to the provided file in the Graphviz DOT format.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to write to the
+    /// provided file.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line.
+    ///
+    pub fn to_graph(&self, mut graph_file: &File) {
+        // Compose the graph from the current configuration
+        let graph_string = graph::to_dot(
+            &self.all_scenes,
+            &self.status_handler.get_map(),
+            &self.lookup,
+            &self.events,
+        );
+
+        // Try to write the graph to the file
+        match graph_file.write_all(graph_string.as_bytes()) {
+            Ok(_) => (),
+            Err(error) => {
+                update!(err &self.general_update => "Unable To Write Event Graph To File: {}", error)
+            }
+        }
+    }
+
     /// An internal function to verify the configuration.
     ///
     /// Like all EventHandler functions and methods, this method will fail
@@ -918,9 +1474,17 @@ impl Config {
         general_update: &GeneralUpdate,
         all_scenes: &FnvHashMap<ItemId, Scene>,
         status_map: &StatusMap,
+        sequences: &SequenceMap,
+        report: &Option<ReportSettings>,
         lookup: &FnvHashMap<ItemId, ItemDescription>,
         events: &FnvHashMap<ItemId, EventDetail>,
+        unknown_names: &[String],
     ) {
+        // Report any names which are used but never defined
+        for name in unknown_names.iter() {
+            update!(warn general_update => "Unknown Name: {}", name);
+        }
+
         // Verify each scene in the config
         for (id, scene) in all_scenes.iter() {
             if !Config::verify_scene(
@@ -939,6 +1503,134 @@ impl Config {
                 update!(warn general_update => "Scene Not Described In Lookup: {}", id);
             }
         }
+
+        // Verify each status in the config
+        for (id, detail) in status_map.iter() {
+            if !Config::verify_status(general_update, detail, status_map, events) {
+                update!(warn general_update => "Broken Status Definition: {}", id);
+            }
+        }
+
+        // Verify each sequence in the config
+        for (id, sequence) in sequences.iter() {
+            // Verify that every event in the sequence exists
+            let mut test = true;
+            for event in sequence
+                .inputs
+                .iter()
+                .chain(sequence.wrong_inputs.iter())
+                .chain(sequence.success.iter())
+                .chain(sequence.failure.iter())
+            {
+                if !events.contains_key(event) {
+                    update!(warn general_update => "Sequence Contains Invalid Event: {}", event);
+                    test = false;
+                }
+            }
+
+            // Verify that the required number of inputs is possible
+            if let SequenceMode::Unordered { count } = sequence.mode {
+                if (count == 0) | (count > sequence.inputs.len()) {
+                    update!(warn general_update => "Sequence Requires An Invalid Number Of Inputs: {}", count);
+                    test = false;
+                }
+            }
+            if !test {
+                update!(warn general_update => "Broken Sequence Definition: {}", id);
+            }
+        }
+
+        // Verify that every event in the game report exists
+        if let Some(report) = report {
+            for event in report
+                .start
+                .iter()
+                .chain(report.milestones.iter())
+                .chain(report.hints.iter())
+                .chain(report.team.iter())
+                .chain(report.end.iter())
+            {
+                if !events.contains_key(event) {
+                    update!(warn general_update => "Game Report Contains Invalid Event: {}", event);
+                }
+            }
+        }
+    }
+
+    /// An internal function to verify the transition rules and the entry and
+    /// exit events of a particular status, or the conditions of a derived
+    /// status.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying of warnings on the update line. This function
+    /// does not raise any errors.
+    ///
+    fn verify_status(
+        general_update: &GeneralUpdate,
+        detail: &StatusDetail,
+        status_map: &StatusMap,
+        events: &FnvHashMap<ItemId, EventDetail>,
+    ) -> bool {
+        // Verify that every input to a derived status exists
+        let mut test = true;
+        if let &StatusDetail::DerivedState { ref rules, .. } = detail {
+            for (status_id, state) in rules.iter().flat_map(|rule| rule.condition.inputs()) {
+                match status_map.get(&status_id) {
+                    Some(input) => {
+                        if !input.is_allowed(&state) {
+                            update!(warn general_update => "Derived Status Contains Invalid State: {}", state);
+                            test = false;
+                        }
+                    }
+                    None => {
+                        update!(warn general_update => "Derived Status Contains Invalid Status Id: {}", status_id);
+                        test = false;
+                    }
+                }
+            }
+
+            // Verify that every variable is the count of a counted status
+            for status_id in rules.iter().flat_map(|rule| rule.condition.variables()) {
+                match status_map.get(&status_id) {
+                    Some(&StatusDetail::CountedState { .. }) => (),
+                    _ => {
+                        update!(warn general_update => "Derived Status Contains Invalid Variable: {}", status_id);
+                        test = false;
+                    }
+                }
+            }
+        }
+
+        // Only the multistate variant has transitions and state events
+        if let &StatusDetail::MultiState {
+            ref allowed,
+            ref transitions,
+            ref entry_events,
+            ref exit_events,
+            ..
+        } = detail
+        {
+            // Verify that every state in the transitions is allowed
+            if let &Some(ref transitions) = transitions {
+                for (state, next_states) in transitions.iter() {
+                    for id in next_states.iter().chain(Some(state)) {
+                        if !allowed.is_empty() && !allowed.contains(id) {
+                            update!(warn general_update => "Status Transition Contains Invalid State: {}", id);
+                            test = false;
+                        }
+                    }
+                }
+            }
+
+            // Verify that every entry and exit event exists
+            for event in entry_events.values().chain(exit_events.values()) {
+                if !events.contains_key(event) {
+                    update!(warn general_update => "Status Contains Invalid Entry Or Exit Event: {}", event);
+                    test = false;
+                }
+            }
+        }
+        test
     }
 
     /// An internal function to verify a particular scene in the context of config.
@@ -1100,6 +1792,9 @@ impl Config {
                 &SaveData { .. } => (),
                 &SendData { .. } => (),
 
+                // If there is a report to save, assume validity
+                &SaveReport => (),
+
                 // If there is a grouped event, verify the components of the event
                 &GroupedEvent {
                     ref status_id,
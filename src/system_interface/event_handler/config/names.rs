@@ -0,0 +1,314 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module allows symbolic names (e.g. door_1_open) in place of the
+//! numeric ids of a configuration.
+//!
+//! Any item id in the configuration file may be written as a name instead of
+//! a number. Each name is given a numeric id when the configuration is
+//! loaded, and the names are kept with their numbers in the configuration
+//! (under names) so that the numbers stay the same every time the
+//! configuration is loaded and saved. The connected devices only ever see
+//! the numbers. A name is defined by describing it in the event set (or by
+//! listing it under names); any other name is reported with its line.
+
+// Import standard library features
+use std::path::Path;
+
+// Import FNV HashMap and HashSet
+extern crate fnv;
+use self::fnv::{FnvHashMap, FnvHashSet};
+
+// Import the serialization traits
+use serde::Serialize;
+
+// Import YAML processing library
+extern crate serde_yaml;
+use self::serde_yaml::{Mapping, Value};
+
+/// A type to map each symbolic name to its numeric id
+///
+pub type NameMap = FnvHashMap<String, u32>;
+
+/// A structure to replace the symbolic names of a configuration (and its
+/// included files) with their numeric ids
+///
+pub struct Names {
+    names: NameMap,              // the numeric id of each name
+    used: FnvHashSet<u32>,       // the numeric ids already used by the configuration
+    defined: FnvHashSet<String>, // the names which are described in the event set or listed under names
+    unknown: Vec<String>,        // the names which were used but not defined, with their locations
+}
+
+// Implement the name features
+impl Names {
+    /// A function to create the names of a configuration from the provided
+    /// documents (the configuration first, then any included files). The
+    /// existing names are read from the configuration, and the numeric ids
+    /// of every document are noted so that new names never collide with them.
+    ///
+    pub fn new(documents: &[&Value]) -> Names {
+        // Read the existing names from the configuration
+        let mut names = NameMap::default();
+        if let Some(Value::Mapping(mapping)) =
+            documents.first().and_then(|config| config.get("names"))
+        {
+            for (name, id) in mapping.iter() {
+                if let (Some(name), Some(id)) = (name.as_str(), id.as_u64()) {
+                    names.insert(name.to_string(), id as u32);
+                }
+            }
+        }
+
+        // Note the numeric ids and the names described in the event sets
+        let mut used: FnvHashSet<u32> = names.values().cloned().collect();
+        let mut defined: FnvHashSet<String> = names.keys().cloned().collect();
+        for document in documents.iter() {
+            note_ids(document, &mut used);
+            if let Some(Value::Mapping(event_set)) = document.get("event_set") {
+                for (item_pair, _) in event_set.iter() {
+                    if let Some(name) = item_pair.get("id").and_then(|id| id.as_str()) {
+                        defined.insert(name.to_string());
+                    }
+                }
+            }
+        }
+
+        // Return the names
+        Names {
+            names,
+            used,
+            defined,
+            unknown: Vec::new(),
+        }
+    }
+
+    /// A method to replace the names in the provided document with their
+    /// numeric ids, giving each new name the next free id. The text of the
+    /// document is used to find the line of any name which is not defined,
+    /// and the path (if provided) notes the included file. Returns true if
+    /// any names were replaced.
+    ///
+    pub fn resolve(&mut self, document: &mut Value, text: &str, path: Option<&Path>) -> bool {
+        // Replace every name with its numeric id
+        let mut is_named = false;
+        let mut found = Vec::new();
+        let original = document.clone();
+        *document = map_ids(original, &mut |id| {
+            let name = id.as_str()?;
+            is_named = true;
+            if !found.iter().any(|found| found == name) {
+                found.push(name.to_string());
+            }
+            Some(Value::from(self.number(name)))
+        });
+
+        // Note any names which are not defined, with their line
+        for name in found {
+            if !self.defined.contains(&name) {
+                let line = find_line(text, &name);
+                self.unknown.push(match path {
+                    Some(path) => format!("{} (Line {} Of {})", name, line, path.display()),
+                    None => format!("{} (Line {})", name, line),
+                });
+            }
+        }
+        is_named
+    }
+
+    /// A method to add the names (with their numeric ids) to the provided
+    /// configuration, so that the ids stay the same when the configuration
+    /// is loaded again.
+    ///
+    pub fn record(&self, config: &mut Value) {
+        if let Value::Mapping(config) = config {
+            let mut mapping = Mapping::new();
+            for (name, id) in self.names.iter() {
+                mapping.insert(Value::from(name.clone()), Value::from(*id));
+            }
+            config.insert(Value::from("names"), Value::Mapping(mapping));
+        }
+    }
+
+    /// A method to return the names which were used but not defined (with
+    /// their locations)
+    ///
+    pub fn unknown(&self) -> &[String] {
+        &self.unknown
+    }
+
+    /// An internal method to find the numeric id of a name, giving a new
+    /// name the next free id
+    ///
+    fn number(&mut self, name: &str) -> u32 {
+        // Return the existing id, if it exists
+        if let Some(id) = self.names.get(name) {
+            return *id;
+        }
+
+        // Otherwise, use the next free id
+        let mut id = 1;
+        while self.used.contains(&id) {
+            id += 1;
+        }
+        self.used.insert(id);
+        self.names.insert(name.to_string(), id);
+        id
+    }
+}
+
+/// A function to write the numeric ids of the provided document as their
+/// names again (the reverse of resolving the names)
+///
+pub fn restore(document: Value, names: &NameMap) -> Value {
+    // Skip documents when there are no names
+    if names.is_empty() {
+        return document;
+    }
+
+    // Replace every id which has a name
+    let numbers: FnvHashMap<u32, &String> = names.iter().map(|(name, id)| (*id, name)).collect();
+    map_ids(document, &mut |id| {
+        let name = numbers.get(&(id.as_u64()? as u32))?;
+        Some(Value::from(name.as_str()))
+    })
+}
+
+/// A function to compose the provided item as YAML, writing any numeric ids
+/// which have a name as that name
+///
+/// # Errors
+///
+/// This function will raise an error if it is unable to compose the item.
+///
+pub fn compose<T: Serialize>(item: &T, names: &NameMap) -> Result<String, serde_yaml::Error> {
+    // Skip the conversion when there are no names
+    let text = serde_yaml::to_string(item)?;
+    if names.is_empty() {
+        return Ok(text);
+    }
+
+    // Otherwise, read the text back (to_value would change large integers to
+    // floats), restore the names and compose the result
+    let document = serde_yaml::from_str(&text)?;
+    serde_yaml::to_string(&restore(document, names))
+}
+
+/// An internal function to replace the value of every id field in the
+/// provided document (including the ids used as keys) wherever the provided
+/// function returns a new value
+///
+fn map_ids<F>(document: Value, replace: &mut F) -> Value
+where
+    F: FnMut(&Value) -> Option<Value>,
+{
+    match document {
+        // Rebuild each mapping, replacing any id field
+        Value::Mapping(mapping) => {
+            let mut new_mapping = Mapping::new();
+            for (key, value) in mapping.into_iter() {
+                let new_value = match key.as_str() {
+                    Some("id") => replace(&value),
+                    _ => None,
+                };
+                let value = new_value.unwrap_or_else(|| map_ids(value, replace));
+                new_mapping.insert(map_ids(key, replace), value);
+            }
+            Value::Mapping(new_mapping)
+        }
+
+        // Check each element of a sequence
+        Value::Sequence(sequence) => Value::Sequence(
+            sequence
+                .into_iter()
+                .map(|value| map_ids(value, replace))
+                .collect(),
+        ),
+
+        // Leave the other values unchanged
+        value => value,
+    }
+}
+
+/// An internal function to note every numeric id in the provided document
+///
+fn note_ids(document: &Value, used: &mut FnvHashSet<u32>) {
+    match document {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter() {
+                if let (Some("id"), Some(id)) = (key.as_str(), value.as_u64()) {
+                    used.insert(id as u32);
+                }
+                note_ids(key, used);
+                note_ids(value, used);
+            }
+        }
+        Value::Sequence(sequence) => {
+            for value in sequence.iter() {
+                note_ids(value, used);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// An internal function to find the first line of the provided text which
+/// uses the provided name as an id (or zero if it was not found)
+///
+fn find_line(text: &str, name: &str) -> usize {
+    text.lines()
+        .position(|line| {
+            line.splitn(2, "id:").nth(1).map_or(false, |id| {
+                id.trim()
+                    .trim_matches(|quote| quote == '"' || quote == '\'')
+                    == name
+            })
+        })
+        .map_or(0, |index| index + 1)
+}
+
+// Tests of the names module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test replacing and restoring the names of a configuration
+    #[test]
+    fn resolve_and_restore() {
+        // Compose a configuration with names, an existing name and a typo
+        let text = "names:\n  door_open: 5\n\
+                    all_scenes:\n  ? id: intro\n  : events:\n      - id: door_open\n      - id: 1\n\
+                    event_set:\n  ? id: intro\n    description: Introduction\n    display: Hidden\n  \
+                    : - NewScene:\n        new_scene:\n          id: intor\n";
+        let mut config: Value = serde_yaml::from_str(text).unwrap();
+        let original = config.clone();
+
+        // Replace the names and check the new ids (avoiding the ids in use)
+        let mut names = Names::new(&[&config]);
+        assert!(names.resolve(&mut config, text, None));
+        names.record(&mut config);
+        assert_eq!(names.names.get("door_open"), Some(&5));
+        assert_eq!(names.names.get("intro"), Some(&2));
+        assert_eq!(names.names.get("intor"), Some(&3));
+        assert_eq!(names.unknown(), &["intor (Line 14)".to_string()]);
+
+        // Restore the names and compare with the original configuration
+        let restored = restore(config, &names.names);
+        assert_eq!(restored.get("all_scenes"), original.get("all_scenes"));
+        assert_eq!(restored.get("event_set"), original.get("event_set"));
+    }
+}
@@ -0,0 +1,709 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module composes a JSON Schema for the configuration files, so that
+//! an editor (e.g. the YAML extension of VS Code) can suggest and check each
+//! part of a configuration while it is written.
+//!
+//! The schema follows the layout of the configuration file: each enum is
+//! written with the name of its variant as the only key (e.g. NewScene), and
+//! each id is written as a mapping with an id (a number or a name). Ids and
+//! items which are used as keys of a mapping (e.g. `? id: 5` in the event
+//! set) are not checked, as JSON Schema only describes keys which are
+//! strings. The schema accepts both the configuration and the files it
+//! includes.
+
+// Import the serde json library
+extern crate serde_json;
+use self::serde_json::Value;
+
+// Define module constants
+const SCHEMA_VERSION: &str = "http://json-schema.org/draft-07/schema#"; // the version of JSON Schema used
+const DEFINITIONS: &str = "#/definitions/"; // the location of the definitions within the schema
+
+/// A function to compose the JSON Schema of the configuration file
+///
+pub fn compose_schema() -> Value {
+    serde_json::json!({
+        "$schema": SCHEMA_VERSION,
+        "title": "Minerva Configuration",
+        "description": format!("A configuration file for Minerva {} (or a file it includes)", env!("CARGO_PKG_VERSION")),
+        "anyOf": [reference("Configuration"), reference("IncludedFile")],
+        "definitions": {
+            "Configuration": {
+                "type": "object",
+                "properties": {
+                    "version": { "type": "string", "description": "The version of Minerva which wrote the configuration" },
+                    "identifier": reference("ItemId"),
+                    "server_location": optional(serde_json::json!({ "type": "string", "description": "The location of the backup server" })),
+                    "backup_path": optional(serde_json::json!({ "type": "string", "description": "The location of the local backup file" })),
+                    "resume_mode": optional(reference("ResumeMode")),
+                    "headless_resume": optional(reference("ResumePolicy")),
+                    "system_connection": list(reference("ConnectionType")),
+                    "background_process": optional(reference("BackgroundProcess")),
+                    "default_scene": optional(reference("ItemId")),
+                    "fullscreen": optional(serde_json::json!({ "type": "boolean" })),
+                    "all_scenes": map(reference("Scene")),
+                    "status_map": map(reference("StatusDetail")),
+                    "sequences": map(reference("Sequence")),
+                    "report": optional(reference("ReportSettings")),
+                    "logging": optional(reference("LogSettings")),
+                    "metrics": optional(serde_json::json!({ "type": "string", "description": "The address to serve the metrics (e.g. 127.0.0.1:9185)" })),
+                    "web_console": optional(reference("ConsoleSettings")),
+                    "includes": list(serde_json::json!({ "type": "string", "description": "A file with more of the configuration (relative to this file)" })),
+                    "names": map(serde_json::json!({ "type": "integer", "minimum": 0 })),
+                    "event_set": map(optional(list(reference("EventAction")))),
+                },
+                "required": ["version", "identifier", "system_connection", "all_scenes", "status_map", "event_set"],
+                "additionalProperties": false,
+            },
+            "IncludedFile": structure(
+                serde_json::json!({
+                    "version": { "type": "string", "description": "The version of Minerva which wrote the file (defaults to that of the configuration)" },
+                    "system_connection": list(reference("ConnectionType")),
+                    "all_scenes": map(reference("Scene")),
+                    "status_map": map(reference("StatusDetail")),
+                    "event_set": map(optional(list(reference("EventAction")))),
+                }),
+                &[],
+            ),
+            "ItemId": {
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "description": "The number of the item, or its name",
+                        "anyOf": [{ "type": "integer", "minimum": 0 }, { "type": "string" }],
+                    },
+                },
+                "required": ["id"],
+                "additionalProperties": false,
+            },
+            "ItemPair": structure(
+                serde_json::json!({
+                    "id": {
+                        "description": "The number of the item, or its name",
+                        "anyOf": [{ "type": "integer", "minimum": 0 }, { "type": "string" }],
+                    },
+                    "description": { "type": "string" },
+                    "display": reference("DisplayType"),
+                }),
+                &["id", "description", "display"],
+            ),
+            "Duration": structure(
+                serde_json::json!({
+                    "secs": { "type": "integer", "minimum": 0 },
+                    "nanos": { "type": "integer", "minimum": 0, "maximum": 999_999_999 },
+                }),
+                &["secs", "nanos"],
+            ),
+            "Color": {
+                "type": "array",
+                "description": "The red, green and blue values of the color",
+                "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "minItems": 3,
+                "maxItems": 3,
+            },
+            "ConnectionType": { "oneOf": [
+                variant("ComedySerial", structure(
+                    serde_json::json!({
+                        "path": { "type": "string" },
+                        "baud": { "type": "integer", "minimum": 0 },
+                    }),
+                    &["path", "baud"],
+                )),
+                variant("ZmqPrimary", zmq_paths()),
+                variant("ZmqSecondary", zmq_paths()),
+                variant("ZmqTranslate", structure(
+                    serde_json::json!({
+                        "send_path": { "type": "string" },
+                        "recv_path": { "type": "string" },
+                        "event_string": map(serde_json::json!({ "type": "string" })),
+                        "string_event": map(reference("ItemId")),
+                    }),
+                    &["send_path", "recv_path", "event_string", "string_event"],
+                )),
+                variant("DmxSerial", structure(
+                    serde_json::json!({
+                        "path": { "type": "string" },
+                        "all_stop_dmx": list(reference("DmxFade")),
+                        "dmx_map": map(reference("DmxFade")),
+                    }),
+                    &["path", "all_stop_dmx", "dmx_map"],
+                )),
+            ]},
+            "DmxFade": structure(
+                serde_json::json!({
+                    "channel": { "type": "integer", "minimum": 0 },
+                    "value": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    "duration": optional(reference("Duration")),
+                }),
+                &["channel", "value"],
+            ),
+            "ConsoleSettings": structure(
+                serde_json::json!({
+                    "address": { "type": "string", "description": "The address to serve the web console (e.g. 0.0.0.0:8080)" },
+                    "token": { "type": "string", "description": "The token to provide in the address of the console page" },
+                }),
+                &["address", "token"],
+            ),
+            "BackgroundProcess": structure(
+                serde_json::json!({
+                    "process": { "type": "string" },
+                    "arguments": list(serde_json::json!({ "type": "string" })),
+                    "keepalive": { "type": "boolean" },
+                }),
+                &["process", "arguments", "keepalive"],
+            ),
+            "ResumeMode": { "oneOf": [
+                variant("Deadlines", structure(
+                    serde_json::json!({ "skip_expired": { "type": "boolean" } }),
+                    &["skip_expired"],
+                )),
+                { "enum": ["Frozen"] },
+            ]},
+            "ResumePolicy": structure(
+                serde_json::json!({
+                    "scene": { "type": "boolean" },
+                    "statuses": { "type": "boolean" },
+                    "events": { "type": "boolean" },
+                }),
+                &[],
+            ),
+            "Scene": structure(
+                serde_json::json!({
+                    "events": list(reference("ItemId")),
+                    "key_map": optional(map(reference("ItemId"))),
+                }),
+                &["events"],
+            ),
+            "StatusDetail": { "oneOf": [
+                variant("MultiState", structure(
+                    serde_json::json!({
+                        "current": reference("ItemId"),
+                        "allowed": list(reference("ItemId")),
+                        "transitions": optional(map(list(reference("ItemId")))),
+                        "entry_events": map(reference("ItemId")),
+                        "exit_events": map(reference("ItemId")),
+                    }),
+                    &["current", "allowed"],
+                )),
+                variant("CountedState", structure(
+                    serde_json::json!({
+                        "current": reference("ItemId"),
+                        "trigger": reference("ItemId"),
+                        "anti_trigger": reference("ItemId"),
+                        "reset": reference("ItemId"),
+                        "count": { "type": "integer", "minimum": 0 },
+                        "default_count": { "type": "integer", "minimum": 0 },
+                    }),
+                    &["current", "trigger", "anti_trigger", "reset", "count", "default_count"],
+                )),
+                variant("DerivedState", structure(
+                    serde_json::json!({
+                        "current": reference("ItemId"),
+                        "rules": list(reference("DerivedRule")),
+                        "default": reference("ItemId"),
+                    }),
+                    &["current", "rules", "default"],
+                )),
+            ]},
+            "DerivedRule": structure(
+                serde_json::json!({
+                    "condition": reference("StatusCondition"),
+                    "state": reference("ItemId"),
+                }),
+                &["condition", "state"],
+            ),
+            "StatusCondition": { "oneOf": [
+                variant("Is", structure(
+                    serde_json::json!({
+                        "status_id": reference("ItemId"),
+                        "state": reference("ItemId"),
+                    }),
+                    &["status_id", "state"],
+                )),
+                variant("All", list(reference("StatusCondition"))),
+                variant("Any", list(reference("StatusCondition"))),
+                variant("AtLeast", structure(
+                    serde_json::json!({
+                        "count": { "type": "integer", "minimum": 0 },
+                        "conditions": list(reference("StatusCondition")),
+                    }),
+                    &["count", "conditions"],
+                )),
+                variant("Not", reference("StatusCondition")),
+                variant("Compare", structure(
+                    serde_json::json!({
+                        "left": reference("Operand"),
+                        "comparison": reference("Comparison"),
+                        "right": reference("Operand"),
+                    }),
+                    &["left", "comparison", "right"],
+                )),
+            ]},
+            "Operand": { "oneOf": [
+                variant("Value", serde_json::json!({ "type": "integer", "minimum": 0 })),
+                variant("Variable", reference("ItemId")),
+                variant("Count", list(reference("StatusCondition"))),
+            ]},
+            "Comparison": { "enum": ["Equal", "NotEqual", "Less", "LessOrEqual", "Greater", "GreaterOrEqual"] },
+            "Sequence": structure(
+                serde_json::json!({
+                    "inputs": list(reference("ItemId")),
+                    "mode": { "oneOf": [
+                        { "enum": ["Ordered"] },
+                        variant("Unordered", structure(
+                            serde_json::json!({ "count": { "type": "integer", "minimum": 0 } }),
+                            &["count"],
+                        )),
+                    ]},
+                    "wrong_inputs": list(reference("ItemId")),
+                    "reset_on_wrong": { "type": "boolean" },
+                    "timeout": optional(reference("Duration")),
+                    "success": optional(reference("ItemId")),
+                    "failure": optional(reference("ItemId")),
+                }),
+                &["inputs", "mode"],
+            ),
+            "ReportSettings": structure(
+                serde_json::json!({
+                    "start": optional(reference("ItemId")),
+                    "milestones": list(reference("ItemId")),
+                    "hints": list(reference("ItemId")),
+                    "team": optional(reference("ItemId")),
+                    "end": optional(reference("ItemId")),
+                }),
+                &[],
+            ),
+            "LogSettings": structure(
+                serde_json::json!({
+                    "directory": optional(serde_json::json!({ "type": "string" })),
+                    "max_size": { "type": "integer", "minimum": 0, "description": "The size in kB at which a log file is rotated" },
+                    "max_age": { "type": "integer", "minimum": 0, "description": "The age in days after which old log files are removed" },
+                    "max_files": { "type": "integer", "minimum": 0, "description": "The number of old log files to keep" },
+                }),
+                &[],
+            ),
+            "DisplayType": { "oneOf": [
+                variant("DisplayControl", display(None)),
+                variant("DisplayWith", display(Some(("group_id", reference("ItemId"))))),
+                variant("DisplayDebug", display(Some(("group", optional(reference("ItemId")))))),
+                variant("LabelControl", display(None)),
+                variant("LabelHidden", display(None)),
+                { "enum": ["Hidden"] },
+            ]},
+            "EventAction": { "oneOf": [
+                variant("NewScene", structure(
+                    serde_json::json!({ "new_scene": reference("ItemId") }),
+                    &["new_scene"],
+                )),
+                variant("ModifyStatus", structure(
+                    serde_json::json!({
+                        "status_id": reference("ItemId"),
+                        "new_state": reference("ItemId"),
+                    }),
+                    &["status_id", "new_state"],
+                )),
+                variant("QueueEvent", structure(
+                    serde_json::json!({ "event": structure(
+                        serde_json::json!({
+                            "delay": optional(reference("Duration")),
+                            "event_id": reference("ItemId"),
+                        }),
+                        &["event_id"],
+                    )}),
+                    &["event"],
+                )),
+                variant("CancelEvent", structure(
+                    serde_json::json!({ "event": reference("ItemId") }),
+                    &["event"],
+                )),
+                variant("SaveData", structure(
+                    serde_json::json!({ "data": reference("DataType") }),
+                    &["data"],
+                )),
+                variant("SendData", structure(
+                    serde_json::json!({ "data": reference("DataType") }),
+                    &["data"],
+                )),
+                { "enum": ["SaveReport"] },
+                variant("GroupedEvent", structure(
+                    serde_json::json!({
+                        "status_id": reference("ItemId"),
+                        "event_map": map(reference("ItemId")),
+                    }),
+                    &["status_id", "event_map"],
+                )),
+            ]},
+            "DataType": { "oneOf": [
+                variant("TimeUntil", structure(
+                    serde_json::json!({ "event_id": reference("ItemId") }),
+                    &["event_id"],
+                )),
+                variant("TimePassedUntil", structure(
+                    serde_json::json!({
+                        "event_id": reference("ItemId"),
+                        "total_time": reference("Duration"),
+                    }),
+                    &["event_id", "total_time"],
+                )),
+                variant("StaticString", structure(
+                    serde_json::json!({ "string": { "type": "string" } }),
+                    &["string"],
+                )),
+                { "enum": ["UserString"] },
+                variant("BestTimes", structure(
+                    serde_json::json!({ "count": { "type": "integer", "minimum": 0 } }),
+                    &["count"],
+                )),
+            ]},
+        },
+    })
+}
+
+/// A helper function to refer to the provided definition
+///
+fn reference(name: &str) -> Value {
+    serde_json::json!({ "$ref": format!("{}{}", DEFINITIONS, name) })
+}
+
+/// A helper function to allow the provided schema or nothing (i.e. ~)
+///
+fn optional(schema: Value) -> Value {
+    serde_json::json!({ "anyOf": [{ "type": "null" }, schema] })
+}
+
+/// A helper function to describe a list of the provided schema
+///
+fn list(schema: Value) -> Value {
+    serde_json::json!({ "type": "array", "items": schema })
+}
+
+/// A helper function to describe a mapping to the provided schema
+///
+fn map(schema: Value) -> Value {
+    serde_json::json!({ "type": "object", "additionalProperties": schema })
+}
+
+/// A helper function to describe a structure with the provided fields
+///
+fn structure(properties: Value, required: &[&str]) -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// A helper function to describe one variant of an enum (written with the
+/// name of the variant as the only key)
+///
+fn variant(name: &str, schema: Value) -> Value {
+    // Compose the only key
+    let mut properties = serde_json::Map::new();
+    properties.insert(name.to_string(), schema);
+
+    // Return the variant
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": [name],
+        "additionalProperties": false,
+    })
+}
+
+/// A helper function to describe the fields shared by each display type,
+/// along with the provided group field (if any)
+///
+fn display(group: Option<(&str, Value)>) -> Value {
+    // Compose the shared fields
+    let mut properties = serde_json::Map::new();
+    properties.insert(
+        "position".to_string(),
+        optional(serde_json::json!({ "type": "integer", "minimum": 0 })),
+    );
+    properties.insert("color".to_string(), optional(reference("Color")));
+    properties.insert("highlight".to_string(), optional(reference("Color")));
+    properties.insert(
+        "highlight_state".to_string(),
+        optional(serde_json::json!({
+            "type": "array",
+            "description": "The status and the state which highlight the item",
+            "items": reference("ItemId"),
+            "minItems": 2,
+            "maxItems": 2,
+        })),
+    );
+    properties.insert(
+        "spotlight".to_string(),
+        optional(serde_json::json!({ "type": "integer", "minimum": 0 })),
+    );
+
+    // Add the group field and mark it as required, if it is not optional
+    let mut required = Vec::new();
+    if let Some((name, schema)) = group {
+        if schema.get("anyOf").is_none() {
+            required.push(name);
+        }
+        properties.insert(name.to_string(), schema);
+    }
+    structure(Value::Object(properties), &required)
+}
+
+/// A helper function to describe the paths of a ZeroMQ connection
+///
+fn zmq_paths() -> Value {
+    structure(
+        serde_json::json!({
+            "send_path": { "type": "string" },
+            "recv_path": { "type": "string" },
+        }),
+        &["send_path", "recv_path"],
+    )
+}
+
+// Tests of the schema module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Collect every reference within the provided schema
+    fn collect_references(schema: &Value, references: &mut Vec<String>) {
+        match schema {
+            Value::Object(object) => {
+                for (key, value) in object.iter() {
+                    match (key.as_str(), value.as_str()) {
+                        ("$ref", Some(reference)) => references.push(reference.to_string()),
+                        _ => collect_references(value, references),
+                    }
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter() {
+                    collect_references(value, references);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Convert a YAML document to JSON, writing any key which is not a string
+    // or a number as its JSON text (the key of the mapping is the document)
+    fn to_json(value: &serde_yaml::Value) -> Value {
+        match value {
+            serde_yaml::Value::Mapping(mapping) => {
+                let mut object = serde_json::Map::new();
+                for (key, value) in mapping.iter() {
+                    let key = match to_json(key) {
+                        Value::String(key) => key,
+                        Value::Number(key) => key.to_string(),
+                        key => key.to_string(),
+                    };
+                    object.insert(key, to_json(value));
+                }
+                Value::Object(object)
+            }
+            serde_yaml::Value::Sequence(sequence) => {
+                Value::Array(sequence.iter().map(to_json).collect())
+            }
+            value => serde_json::to_value(value).unwrap(),
+        }
+    }
+
+    // Check the provided JSON against the provided part of the schema (the
+    // property names are checked as plain strings, like a standard validator)
+    fn validate(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        // Follow any reference
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches(DEFINITIONS);
+            return validate(root, &root["definitions"][name], value, path);
+        }
+
+        // Check the type and the limits
+        let is_type = |kind: &str| match kind {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_u64() || value.is_i64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => false,
+        };
+        if let Some(kind) = schema["type"].as_str() {
+            if !is_type(kind) {
+                return Err(format!("{}: expected {}, found {}", path, kind, value));
+            }
+        }
+        if let Some(options) = schema["enum"].as_array() {
+            if !options.contains(value) {
+                return Err(format!("{}: unexpected {}", path, value));
+            }
+        }
+        if let (Some(minimum), Some(number)) = (schema["minimum"].as_i64(), value.as_i64()) {
+            if number < minimum {
+                return Err(format!("{}: below the minimum", path));
+            }
+        }
+        if let (Some(maximum), Some(number)) = (schema["maximum"].as_i64(), value.as_i64()) {
+            if number > maximum {
+                return Err(format!("{}: above the maximum", path));
+            }
+        }
+
+        // Check each of the alternatives
+        if let Some(options) = schema["anyOf"].as_array() {
+            let errors: Vec<String> = options
+                .iter()
+                .filter_map(|option| validate(root, option, value, path).err())
+                .collect();
+            if errors.len() == options.len() {
+                return Err(errors.join(" | "));
+            }
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let errors: Vec<String> = options
+                .iter()
+                .filter_map(|option| validate(root, option, value, path).err())
+                .collect();
+            if errors.len() + 1 != options.len() {
+                return Err(format!(
+                    "{}: not exactly one of {}",
+                    path,
+                    errors.join(" | ")
+                ));
+            }
+        }
+
+        // Check the items of a list
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            let length = array.len() as u64;
+            let minimum = schema["minItems"].as_u64().unwrap_or(0);
+            let maximum = schema["maxItems"].as_u64().unwrap_or(u64::MAX);
+            if (length < minimum) || (length > maximum) {
+                return Err(format!("{}: wrong number of items", path));
+            }
+            for (index, item) in array.iter().enumerate() {
+                validate(root, items, item, &format!("{}/{}", path, index))?;
+            }
+        }
+
+        // Check the fields of a mapping
+        if let Some(object) = value.as_object() {
+            for field in schema["required"].as_array().unwrap_or(&Vec::new()) {
+                if !object.contains_key(field.as_str().unwrap()) {
+                    return Err(format!("{}: missing {}", path, field));
+                }
+            }
+            for (key, field) in object.iter() {
+                let field_path = format!("{}/{}", path, key);
+                if let Some(names) = schema.get("propertyNames") {
+                    validate(root, names, &Value::String(key.clone()), &field_path)?;
+                }
+                let additional = schema.get("additionalProperties");
+                match (schema["properties"].get(key), additional) {
+                    (Some(property), _) => validate(root, property, field, &field_path)?,
+                    (None, Some(Value::Bool(false))) => {
+                        return Err(format!("{}: unexpected field", field_path))
+                    }
+                    (None, Some(additional)) => validate(root, additional, field, &field_path)?,
+                    (None, None) => (),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Test that the configurations follow the schema
+    #[test]
+    fn validate_configs() {
+        // Import features for testing
+        use super::super::migration;
+        use std::fs;
+
+        // Validate the default configuration and the upgraded example configuration
+        let schema = super::compose_schema();
+        let example = fs::read_to_string("examples/example_config.mnv").unwrap();
+        let upgraded = migration::upgrade(&example, env!("CARGO_PKG_VERSION")).unwrap();
+        let default: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string("default.mnv").unwrap()).unwrap();
+        for config in [upgraded.config, default].iter() {
+            let config = to_json(config);
+            assert_eq!(validate(&schema, &schema, &config, ""), Ok(()));
+            let parsed = validate(&schema, &reference("Configuration"), &config, "");
+            assert_eq!(parsed, Ok(()));
+        }
+
+        // Validate an included file (without the required sections)
+        let include: serde_yaml::Value = serde_yaml::from_str(
+            "event_set:\n  ? id: 300\n    description: Area\n    display:\n      \
+             DisplayControl:\n        position: 1\n  : []\n",
+        )
+        .unwrap();
+        let include = to_json(&include);
+        assert_eq!(validate(&schema, &schema, &include, ""), Ok(()));
+
+        // Check an item against its definition and reject an unknown display type
+        let item: Value = serde_json::from_str(
+            r#"{"id": 300, "description": "Area", "display": {"DisplayControl": {"position": 1}}}"#,
+        )
+        .unwrap();
+        assert_eq!(validate(&schema, &reference("ItemPair"), &item, ""), Ok(()));
+        let wrong = item.to_string().replace("DisplayControl", "DisplayWrong");
+        let wrong: Value = serde_json::from_str(&wrong).unwrap();
+        assert!(validate(&schema, &reference("ItemPair"), &wrong, "").is_err());
+    }
+
+    // Test that every reference of the schema has a definition
+    #[test]
+    fn compose_schema() {
+        // Compose the schema and collect the references
+        let schema = super::compose_schema();
+        let mut references = Vec::new();
+        collect_references(&schema, &mut references);
+        assert!(!references.is_empty());
+
+        // Check that each reference is defined
+        for reference in references.iter() {
+            let name = reference.trim_start_matches(DEFINITIONS);
+            assert!(schema["definitions"].get(name).is_some(), "{}", reference);
+        }
+
+        // Check a few parts of the layout
+        assert_eq!(
+            schema["definitions"]["Configuration"]["required"][0],
+            "version"
+        );
+        assert_eq!(
+            schema["definitions"]["EventAction"]["oneOf"][0]["required"][0],
+            "NewScene"
+        );
+        assert_eq!(
+            schema["definitions"]["DisplayType"]["oneOf"][1]["properties"]["DisplayWith"]
+                ["required"][0],
+            "group_id"
+        );
+
+        // Check that the keys of the item mappings are not restricted (the
+        // keys of a JSON document are always strings)
+        let event_set = &schema["definitions"]["Configuration"]["properties"]["event_set"];
+        assert!(event_set.get("propertyNames").is_none());
+        let key = r#"{"id":5,"description":"Event"}"#;
+        let event_set_value = serde_json::json!({ key: [] });
+        assert_eq!(validate(&schema, event_set, &event_set_value, ""), Ok(()));
+    }
+}
@@ -0,0 +1,494 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! This module implements the sequence handler which watches the incoming
+//! events for sequences and combinations of inputs (e.g. "events A, B, and C
+//! in that order within ten seconds" or "any four of these six sensors").
+//!
+//! Each sequence consumes copies of the events as they are processed and
+//! fires a success or failure event as the result. The progress of each
+//! sequence is only held locally and is not backed up.
+
+// Import the relevant structures into the correct namespace
+use super::super::item::ItemId;
+use super::super::queue::ComingEvent;
+
+// Import standard library features
+use std::time::{Duration, Instant};
+
+// Import FNV HashMap
+extern crate fnv;
+use self::fnv::FnvHashMap;
+
+/// A type to store a hashmap of sequence ids and sequence definitions
+///
+pub type SequenceMap = FnvHashMap<ItemId, Sequence>; // a hash map of sequence id and sequence pairs
+
+/// An enum to specify how the inputs of a sequence must arrive.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequenceMode {
+    /// A variant for a sequence whose inputs must arrive in the listed order
+    Ordered,
+
+    /// A variant for a sequence which is complete once the provided number
+    /// of different inputs have arrived, in any order
+    Unordered { count: usize },
+}
+
+/// A structure to define a sequence or combination of events to watch for.
+///
+/// A wrong input is any of the wrong inputs or (for an ordered sequence) any
+/// input that arrives out of order. If reset on wrong is set, a wrong input
+/// clears the progress of the sequence and fires the failure event (if any
+/// progress had been made). Otherwise, wrong inputs are ignored.
+///
+/// The timeout is measured from the first matching input. When the timeout
+/// expires, the failure event fires and the progress is cleared.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sequence {
+    pub inputs: Vec<ItemId>, // the events which make up the sequence
+    pub mode: SequenceMode,  // whether the inputs must arrive in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrong_inputs: Vec<ItemId>, // any additional events which count as wrong inputs
+    #[serde(default)]
+    pub reset_on_wrong: bool, // a flag to clear the progress after a wrong input
+    pub timeout: Option<Duration>, // the time allowed to complete the sequence, if limited
+    pub success: Option<ItemId>, // the event to trigger when the sequence is complete
+    pub failure: Option<ItemId>, // the event to trigger when the sequence fails
+}
+
+/// An enum to describe the result of an incoming event on a sequence.
+///
+/// The timeout of a sequence is its own copy of the failure event in the
+/// queue. When the sequence finishes early, only that copy is cancelled, so
+/// that any copies of the failure event queued by other events or sequences
+/// are left in place.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceAction {
+    /// A variant to trigger the provided event immediately
+    Trigger { event: ItemId },
+
+    /// A variant to load the timeout of a sequence into the queue
+    StartTimeout { timeout: ComingEvent },
+
+    /// A variant to cancel the timeout of a sequence from the queue
+    CancelTimeout { timeout: ComingEvent },
+}
+
+// Reexport the sequence action type variants
+pub use self::SequenceAction::{CancelTimeout, StartTimeout, Trigger};
+
+/// An internal structure to track the progress of a sequence
+///
+struct SequenceProgress {
+    matched: Vec<ItemId>,         // the inputs matched so far
+    start_time: Instant,          // the time of the first matched input
+    timeout: Option<ComingEvent>, // the queued timeout of this sequence, if any
+}
+
+/// A structure which holds the sequences of the configuration and tracks the
+/// progress of each one.
+///
+pub struct SequenceHandler {
+    sequences: SequenceMap, // hash map of the sequence definitions
+    progress: FnvHashMap<ItemId, SequenceProgress>, // hash map of the sequences in progress
+}
+
+// Implement key features for the sequence handler
+impl SequenceHandler {
+    /// A function to create and return a new sequence handler.
+    ///
+    pub fn new(sequences: SequenceMap) -> SequenceHandler {
+        SequenceHandler {
+            sequences,
+            progress: FnvHashMap::default(),
+        }
+    }
+
+    /// A method to return a copy of the sequence definitions.
+    ///
+    pub fn get_map(&self) -> SequenceMap {
+        self.sequences.clone()
+    }
+
+    /// A method to clear the progress of every sequence in progress. Returns
+    /// the ids of the sequences which were reset and the actions to cancel
+    /// their timeouts.
+    ///
+    pub fn reset(&mut self) -> (Vec<ItemId>, Vec<SequenceAction>) {
+        // Collect the sequences in progress in a consistent order
+        let mut ids: Vec<ItemId> = self.progress.keys().cloned().collect();
+        ids.sort_unstable();
+
+        // Clear the progress and cancel any timeouts
+        let mut actions = Vec::new();
+        for (_, progress) in self.progress.drain() {
+            if let Some(timeout) = progress.timeout {
+                actions.push(CancelTimeout { timeout });
+            }
+        }
+        (ids, actions)
+    }
+
+    /// A method to process an incoming event against every sequence at the
+    /// provided time (according to the clock of the queue). Returns the
+    /// sequence actions (triggered success and failure events, and started
+    /// or cancelled timeouts) that result from the event.
+    ///
+    /// # Notes
+    ///
+    /// The timeout of a sequence is implemented by queueing the failure event
+    /// after the timeout. When the sequence finishes early, only the copy of
+    /// the failure event queued by the sequence is cancelled.
+    ///
+    pub fn process_event(&mut self, event_id: &ItemId, now: Instant) -> Vec<SequenceAction> {
+        // Check each sequence in a consistent order
+        let mut ids: Vec<ItemId> = self.sequences.keys().cloned().collect();
+        ids.sort_unstable();
+
+        // Collect the resulting actions
+        let mut actions = Vec::new();
+        for id in ids {
+            if let Some(sequence) = self.sequences.get(&id) {
+                // Silently clear any progress which has already timed out
+                if let (Some(progress), Some(timeout)) = (self.progress.get(&id), sequence.timeout)
+                {
+                    if now.duration_since(progress.start_time) >= timeout {
+                        self.progress.remove(&id);
+                    }
+                }
+
+                // Apply the event to the sequence
+                actions.extend(SequenceHandler::apply(
+                    sequence,
+                    &mut self.progress,
+                    id,
+                    event_id,
+                    now,
+                ));
+            }
+        }
+        actions
+    }
+
+    /// An internal function to apply an incoming event to a single sequence
+    ///
+    fn apply(
+        sequence: &Sequence,
+        all_progress: &mut FnvHashMap<ItemId, SequenceProgress>,
+        id: ItemId,
+        event_id: &ItemId,
+        now: Instant,
+    ) -> Vec<SequenceAction> {
+        // Find the number of inputs already matched
+        let matched = match all_progress.get(&id) {
+            Some(progress) => progress.matched.len(),
+            None => 0,
+        };
+
+        // Decide whether the event is the next input or a wrong input
+        let is_input = sequence.inputs.contains(event_id);
+        let (is_next, is_wrong) = match &sequence.mode {
+            // The event must be the next input in the list
+            &SequenceMode::Ordered => {
+                let is_next = sequence.inputs.get(matched) == Some(event_id);
+                (
+                    is_next,
+                    !is_next && (is_input || sequence.wrong_inputs.contains(event_id)),
+                )
+            }
+
+            // The event must be an input which has not already arrived
+            &SequenceMode::Unordered { .. } => {
+                let is_repeat = match all_progress.get(&id) {
+                    Some(progress) => progress.matched.contains(event_id),
+                    None => false,
+                };
+                (
+                    is_input && !is_repeat,
+                    sequence.wrong_inputs.contains(event_id),
+                )
+            }
+        };
+
+        // Handle a wrong input
+        let mut actions = Vec::new();
+        if is_wrong {
+            // Ignore the input if the sequence does not reset
+            if !sequence.reset_on_wrong {
+                return actions;
+            }
+
+            // Clear the progress, cancel the timeout and fire the failure event
+            if let Some(progress) = all_progress.remove(&id) {
+                if let Some(timeout) = progress.timeout {
+                    actions.push(CancelTimeout { timeout });
+                }
+                if let Some(failure) = sequence.failure {
+                    actions.push(Trigger { event: failure });
+                }
+            }
+
+            // Allow a wrong input to begin a new ordered sequence
+            if sequence.inputs.first() != Some(event_id) {
+                return actions;
+            }
+        } else if !is_next {
+            return actions;
+        }
+
+        // Record the matching input, starting the timeout if necessary
+        let progress = all_progress.entry(id).or_insert_with(|| {
+            // Queue the failure event to catch the timeout
+            let mut timeout = None;
+            if let (Some(delay), Some(failure)) = (sequence.timeout, sequence.failure) {
+                let coming = ComingEvent {
+                    start_time: now,
+                    delay,
+                    event_id: failure,
+                };
+                actions.push(StartTimeout { timeout: coming });
+                timeout = Some(coming);
+            }
+            SequenceProgress {
+                matched: Vec::new(),
+                start_time: now,
+                timeout,
+            }
+        });
+        progress.matched.push(*event_id);
+
+        // Check to see if the sequence is complete
+        let required = match &sequence.mode {
+            &SequenceMode::Ordered => sequence.inputs.len(),
+            &SequenceMode::Unordered { count } => count,
+        };
+        if progress.matched.len() >= required {
+            // Clear the progress and cancel the timeout
+            if let Some(timeout) = progress.timeout {
+                actions.push(CancelTimeout { timeout });
+            }
+            all_progress.remove(&id);
+
+            // Fire the success event
+            if let Some(success) = sequence.success {
+                actions.push(Trigger { event: success });
+            }
+        }
+        actions
+    }
+}
+
+// Tests of the sequence module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test an ordered sequence with a timeout and reset on wrong input
+    #[test]
+    fn ordered_sequence() {
+        // Create the ids
+        let sequence_id = ItemId::new(50).unwrap();
+        let a = ItemId::new(1).unwrap();
+        let b = ItemId::new(2).unwrap();
+        let c = ItemId::new(3).unwrap();
+        let wrong = ItemId::new(4).unwrap();
+        let success = ItemId::new(10).unwrap();
+        let failure = ItemId::new(11).unwrap();
+
+        // Create the sequence handler
+        let mut sequences = SequenceMap::default();
+        sequences.insert(
+            sequence_id,
+            Sequence {
+                inputs: vec![a, b, c],
+                mode: SequenceMode::Ordered,
+                wrong_inputs: vec![wrong],
+                reset_on_wrong: true,
+                timeout: Some(Duration::from_secs(10)),
+                success: Some(success),
+                failure: Some(failure),
+            },
+        );
+        let mut handler = SequenceHandler::new(sequences);
+        let start = Instant::now();
+        let timeout = ComingEvent {
+            start_time: start,
+            delay: Duration::from_secs(10),
+            event_id: failure,
+        };
+
+        // Verify that the first input starts the timeout
+        assert_eq!(
+            handler.process_event(&a, start),
+            vec![StartTimeout { timeout }]
+        );
+
+        // Verify that a wrong input cancels the timeout and fires the failure event
+        assert_eq!(
+            handler.process_event(&wrong, start),
+            vec![CancelTimeout { timeout }, Trigger { event: failure }]
+        );
+
+        // Verify that the correct sequence cancels its own timeout and fires the success event
+        let restart = start + Duration::from_secs(1);
+        let timeout = ComingEvent {
+            start_time: restart,
+            ..timeout
+        };
+        assert_eq!(
+            handler.process_event(&a, restart),
+            vec![StartTimeout { timeout }]
+        );
+        assert_eq!(handler.process_event(&b, restart), vec![]);
+        assert_eq!(
+            handler.process_event(&c, start + Duration::from_secs(5)),
+            vec![CancelTimeout { timeout }, Trigger { event: success }]
+        );
+
+        // Verify that the progress is cleared after the timeout
+        handler.process_event(&a, start);
+        handler.process_event(&b, start);
+        let late = start + Duration::from_secs(11);
+        assert_eq!(handler.process_event(&c, late), vec![]);
+
+        // Verify that a reset reports the sequence and cancels its timeout
+        let timeout = ComingEvent {
+            start_time: late,
+            ..timeout
+        };
+        handler.process_event(&a, late);
+        assert_eq!(
+            handler.reset(),
+            (vec![sequence_id], vec![CancelTimeout { timeout }])
+        );
+        assert_eq!(handler.reset(), (vec![], vec![]));
+    }
+
+    // Test that a sequence only cancels its own copy of a shared failure event
+    #[test]
+    fn shared_failure() {
+        // Import libraries for testing
+        use super::super::super::super::Clock;
+        use super::super::super::super::{ClockMode, GeneralUpdate, GeneralUpdateType};
+        use super::super::super::event::EventDelay;
+        use super::super::super::queue::Queue;
+        use std::thread;
+
+        // Create the ids
+        let sequence_id = ItemId::new(50).unwrap();
+        let a = ItemId::new(1).unwrap();
+        let b = ItemId::new(2).unwrap();
+        let failure = ItemId::new(11).unwrap();
+
+        // Create the sequence handler
+        let mut sequences = SequenceMap::default();
+        sequences.insert(
+            sequence_id,
+            Sequence {
+                inputs: vec![a, b],
+                mode: SequenceMode::Ordered,
+                wrong_inputs: Vec::new(),
+                reset_on_wrong: false,
+                timeout: Some(Duration::from_secs(10)),
+                success: None,
+                failure: Some(failure),
+            },
+        );
+        let mut handler = SequenceHandler::new(sequences);
+
+        // Create a queue with a manual clock
+        let (tx, rx) = GeneralUpdate::new();
+        let clock = Clock::new_with_mode(ClockMode::Manual);
+        let queue = Queue::new(tx, clock.clone());
+
+        // Queue the failure event from an ordinary event
+        queue.add_event(EventDelay::new(Some(Duration::from_secs(20)), failure));
+
+        // Complete the sequence, applying each action to the queue
+        for input in [a, b].iter() {
+            for action in handler.process_event(input, clock.now()) {
+                match action {
+                    Trigger { event } => queue.add_event(EventDelay::new(None, event)),
+                    StartTimeout { timeout } => queue.add_coming_event(timeout),
+                    CancelTimeout { timeout } => queue.cancel_event(timeout),
+                }
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        // Verify that the ordinary copy of the failure event is still queued
+        let remaining: Vec<Duration> = queue
+            .get_events()
+            .iter()
+            .filter_map(|event| event.remaining(&clock))
+            .collect();
+        assert_eq!(remaining, vec![Duration::from_secs(20)]);
+
+        // Verify that only the ordinary copy triggers
+        clock.advance(Duration::from_secs(21));
+        thread::sleep(Duration::from_millis(50));
+        let mut triggered = Vec::new();
+        while let Ok(update) = rx.try_recv() {
+            if let GeneralUpdateType::ProcessEvent { event, .. } = update {
+                triggered.push(event);
+            }
+        }
+        assert_eq!(triggered, vec![failure]);
+    }
+
+    // Test an unordered combination of inputs
+    #[test]
+    fn unordered_sequence() {
+        // Create the ids
+        let sequence_id = ItemId::new(50).unwrap();
+        let inputs: Vec<ItemId> = (1..7).map(|id| ItemId::new(id).unwrap()).collect();
+        let success = ItemId::new(10).unwrap();
+
+        // Create the sequence handler for any four of the six inputs
+        let mut sequences = SequenceMap::default();
+        sequences.insert(
+            sequence_id,
+            Sequence {
+                inputs: inputs.clone(),
+                mode: SequenceMode::Unordered { count: 4 },
+                wrong_inputs: Vec::new(),
+                reset_on_wrong: false,
+                timeout: None,
+                success: Some(success),
+                failure: None,
+            },
+        );
+        let mut handler = SequenceHandler::new(sequences);
+        let now = Instant::now();
+
+        // Verify that repeated inputs are not counted
+        assert_eq!(handler.process_event(&inputs[5], now), vec![]);
+        assert_eq!(handler.process_event(&inputs[5], now), vec![]);
+        assert_eq!(handler.process_event(&inputs[2], now), vec![]);
+        assert_eq!(handler.process_event(&inputs[0], now), vec![]);
+
+        // Verify that the fourth different input completes the combination
+        assert_eq!(
+            handler.process_event(&inputs[3], now),
+            vec![Trigger { event: success }]
+        );
+    }
+}
@@ -27,6 +27,9 @@ use super::super::super::GeneralUpdate;
 use super::super::event::EventUpdate;
 use super::super::item::{ItemDescription, ItemId, ItemPair};
 
+// Import the failure features
+use failure::Error;
+
 // Import FNV HashMap
 extern crate fnv;
 use self::fnv::FnvHashMap;
@@ -35,6 +38,24 @@ use self::fnv::FnvHashMap;
 ///
 pub type StatusMap = FnvHashMap<ItemId, StatusDetail>; // a hash map of status id and status detail pairs
 
+/// A type to store the allowed transitions of a status, as a hash map of each
+/// state and the states which may follow it
+///
+pub type TransitionMap = FnvHashMap<ItemId, Vec<ItemId>>;
+
+/// A type to store the events to trigger when a status enters or exits a state
+///
+pub type StateEvents = FnvHashMap<ItemId, ItemId>; // a hash map of state ids and event ids
+
+/// A structure to describe a successful change in the state of a status
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateChange {
+    pub status_id: ItemId,   // the id of the status that changed
+    pub new_state: ItemId,   // the new state of the status
+    pub events: Vec<ItemId>, // the exit and entry events for the transition
+}
+
 /// A type to store a vector of status ids and status descriptions
 ///
 pub type FullStatus = FnvHashMap<ItemPair, StatusDescription>; // a hash map of status id pairs and status description pairs
@@ -64,11 +85,17 @@ impl StatusHandler {
     /// None.
     ///
     pub fn new(update_line: GeneralUpdate, status_map: StatusMap) -> StatusHandler {
-        // Return the new status handler
-        StatusHandler {
+        // Create the new status handler
+        let mut status_handler = StatusHandler {
             status_map,
             update_line,
-        }
+        };
+
+        // Silently bring the derived statuses up to date with their inputs
+        status_handler.update_derived();
+
+        // Return the new status handler
+        status_handler
     }
 
     /// A method to get the current state of the requested status. This
@@ -98,8 +125,12 @@ impl StatusHandler {
     }
 
     /// A method to modify a status state within the current scene based
-    /// on the provided status id and new state. Returns Some(new state id) on
-    /// success and None on failure.
+    /// on the provided status id and new state. Returns a list of the state
+    /// changes on success and None on failure. The first change is always the
+    /// requested change, followed by the changes to any derived statuses.
+    ///
+    /// The events of each change are the exit event of the old state and the
+    /// entry event of the new state (if they are defined), in that order.
     ///
     /// # Errors
     ///
@@ -107,20 +138,41 @@ impl StatusHandler {
     /// the configuration. This usually indicates a problem with the underlying
     /// configuration file.
     ///
+    /// This function will raise a warning if the new state is not allowed or
+    /// if the transition from the current state is not allowed.
+    ///
     /// Like all StatusHandler functions and methods, this method will fail
-    /// gracefully by notifying of errors on the update line and returning false.
+    /// gracefully by notifying of errors on the update line and returning None.
     ///
-    pub fn modify_status(&mut self, status_id: &ItemId, new_state: &ItemId) -> Option<ItemId> {
+    pub fn modify_status(
+        &mut self,
+        status_id: &ItemId,
+        new_state: &ItemId,
+    ) -> Option<Vec<StateChange>> {
         // Try to get a mutable reference to the status detail
         if let Some(status_detail) = self.status_map.get_mut(status_id) {
+            // Save the current state for the transition events
+            let old_state = status_detail.current();
+
             // Try to update the status detail
             match status_detail.update(new_state.clone()) {
-                // If the update was successful, return the new state
-                Some(new_id) => Some(new_id),
+                // If the update was successful, return the changes
+                Ok(new_id) => {
+                    // Compose the requested change
+                    let mut changes = vec![StateChange {
+                        status_id: status_id.clone(),
+                        new_state: new_id,
+                        events: status_detail.transition_events(&old_state, &new_id),
+                    }];
+
+                    // Add any changes to the derived statuses
+                    changes.append(&mut self.update_derived());
+                    Some(changes)
+                }
 
                 // If the update failed, warn the system
-                None => {
-                    update!(warn &self.update_line => "Selected State Was Not Valid: {}", new_state);
+                Err(error) => {
+                    update!(warn &self.update_line => "Status {} Was Not Changed: {}", status_id, error);
                     None
                 }
             }
@@ -132,6 +184,100 @@ impl StatusHandler {
         }
     }
 
+    /// A method to restore a status state (e.g. from a backup) without
+    /// checking the transition rules or triggering any transition events.
+    /// Returns Some(new state id) on success and None on failure.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise a warning if the provided id was not found in
+    /// the configuration or if the state is not allowed.
+    ///
+    /// Like all StatusHandler functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line and returning None.
+    ///
+    pub fn restore_status(&mut self, status_id: &ItemId, new_state: &ItemId) -> Option<ItemId> {
+        // Try to get a mutable reference to the status detail
+        if let Some(status_detail) = self.status_map.get_mut(status_id) {
+            // Try to restore the state
+            match status_detail.restore(new_state.clone()) {
+                Ok(new_id) => Some(new_id),
+                Err(error) => {
+                    update!(warn &self.update_line => "Status {} Was Not Restored: {}", status_id, error);
+                    None
+                }
+            }
+
+        // Warn the system that this is not a valid id
+        } else {
+            update!(warn &self.update_line => "Status ID Not Found In Config: {}", status_id);
+            None
+        }
+    }
+
+    /// A method to check if the provided status is derived from other
+    /// statuses. Returns false if the status does not exist.
+    ///
+    pub fn is_derived(&self, status_id: &ItemId) -> bool {
+        self.status_map
+            .get(status_id)
+            .map_or(false, |detail| detail.is_derived())
+    }
+
+    /// A method to bring the derived statuses up to date with their inputs
+    /// (e.g. after statuses were restored). Returns the list of derived
+    /// statuses that changed state.
+    ///
+    pub fn refresh_derived(&mut self) -> Vec<StateChange> {
+        self.update_derived()
+    }
+
+    /// An internal method to recalculate the state of every derived status.
+    /// Returns the list of derived statuses that changed state.
+    ///
+    /// Derived statuses may depend on other derived statuses, so the
+    /// calculation repeats until no more statuses change. A warning is raised
+    /// if the derived statuses do not settle (i.e. they depend on each other
+    /// in a loop).
+    ///
+    fn update_derived(&mut self) -> Vec<StateChange> {
+        // Repeat the calculation at most once for every status
+        let mut changes = Vec::new();
+        for _ in 0..=self.status_map.len() {
+            // Calculate the new state of each derived status
+            let mut new_states = Vec::new();
+            for (status_id, detail) in self.status_map.iter() {
+                if let Some(new_state) = detail.derive(&self.status_map) {
+                    if new_state != detail.current() {
+                        new_states.push((status_id.clone(), new_state));
+                    }
+                }
+            }
+
+            // Stop when there are no more changes
+            if new_states.is_empty() {
+                return changes;
+            }
+
+            // Update each derived status
+            for (status_id, new_state) in new_states {
+                if let Some(detail) = self.status_map.get_mut(&status_id) {
+                    if let Ok(new_id) = detail.restore(new_state) {
+                        changes.push(StateChange {
+                            status_id,
+                            new_state: new_id,
+                            events: Vec::new(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Warn that the derived statuses did not settle
+        update!(warn &self.update_line => "Derived Statuses Depend On Each Other In A Loop.");
+        changes
+    }
+
     /// A method to return a copy of the status map inside the status handler.
     ///
     /// # Errors
@@ -210,6 +356,8 @@ impl StatusHandler {
                     StatusDescription {
                         current: current_pair,
                         allowed: allowed_pairs,
+                        transitions: detail.transitions(),
+                        is_derived: detail.is_derived(),
                     }
                 }
 
@@ -232,9 +380,20 @@ impl StatusHandler {
 pub enum StatusDetail {
     /// The MultState variant
     ///
+    /// Transitions between states may be restricted by listing the states
+    /// which may follow each state. If no transitions are specified, any
+    /// allowed state may follow any other. Each state may also trigger an
+    /// event when the status enters or exits that state.
+    ///
     MultiState {
         current: ItemId,      // the current state
         allowed: Vec<ItemId>, // the allowed states
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transitions: Option<TransitionMap>, // the allowed transitions, if restricted
+        #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+        entry_events: StateEvents, // the events to trigger when entering a state
+        #[serde(default, skip_serializing_if = "FnvHashMap::is_empty")]
+        exit_events: StateEvents, // the events to trigger when exiting a state
     },
 
     /// The CountedState variant
@@ -247,10 +406,243 @@ pub enum StatusDetail {
         count: u32,           // the current count of the status
         default_count: u32,   // the starting value of the status count
     },
+
+    /// The DerivedState variant
+    ///
+    /// The current state is calculated from the states and variables of other
+    /// statuses and cannot be changed directly. The state of the first rule
+    /// whose condition is true becomes the current state, or the default
+    /// state if none are.
+    ///
+    DerivedState {
+        current: ItemId,         // the current state
+        rules: Vec<DerivedRule>, // the rules to calculate the state, in order
+        default: ItemId,         // the state when none of the rules apply
+    },
+}
+
+/// A structure to define one rule of a derived status.
+///
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct DerivedRule {
+    pub condition: StatusCondition, // the condition for this rule to apply
+    pub state: ItemId,              // the state of the derived status when it applies
+}
+
+/// An enum to define a condition over the states and variables of other
+/// statuses.
+///
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum StatusCondition {
+    /// A condition which is true when the status is in the state
+    Is { status_id: ItemId, state: ItemId },
+
+    /// A condition which is true when every one of the conditions is true
+    All(Vec<StatusCondition>),
+
+    /// A condition which is true when any of the conditions is true
+    Any(Vec<StatusCondition>),
+
+    /// A condition which is true when at least the count of the conditions
+    /// are true (e.g. a majority of sensors)
+    AtLeast {
+        count: usize,
+        conditions: Vec<StatusCondition>,
+    },
+
+    /// A condition which is true when the condition is false
+    Not(Box<StatusCondition>),
+
+    /// A condition which compares two values (e.g. the count of a counted
+    /// status with a number)
+    Compare {
+        left: Operand,
+        comparison: Comparison,
+        right: Operand,
+    },
+}
+
+/// An enum to define a value to compare within a status condition.
+///
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub enum Operand {
+    /// A fixed number
+    Value(u32),
+
+    /// The variable of a status (the current count of a counted status)
+    Variable(ItemId),
+
+    /// The number of the conditions which are true
+    Count(Vec<StatusCondition>),
+}
+
+// Implement key features of the operand
+impl Operand {
+    /// A method to calculate the value from the provided statuses. Returns
+    /// None if the status of a variable is missing or is not counted.
+    ///
+    fn value(&self, status_map: &StatusMap) -> Option<u64> {
+        match self {
+            &Operand::Value(ref value) => Some(*value as u64),
+            &Operand::Variable(ref status_id) => match status_map.get(status_id) {
+                Some(&CountedState { ref count, .. }) => Some(*count as u64),
+                _ => None,
+            },
+            &Operand::Count(ref conditions) => Some(
+                conditions
+                    .iter()
+                    .filter(|condition| condition.is_true(status_map))
+                    .count() as u64,
+            ),
+        }
+    }
+
+    /// A method to list every status and state that the value depends on.
+    ///
+    fn inputs(&self) -> Vec<(ItemId, ItemId)> {
+        match self {
+            &Operand::Count(ref conditions) => conditions
+                .iter()
+                .flat_map(|condition| condition.inputs())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// A method to list every status whose variable the value depends on.
+    ///
+    fn variables(&self) -> Vec<ItemId> {
+        match self {
+            &Operand::Value(_) => Vec::new(),
+            &Operand::Variable(ref status_id) => vec![status_id.clone()],
+            &Operand::Count(ref conditions) => conditions
+                .iter()
+                .flat_map(|condition| condition.variables())
+                .collect(),
+        }
+    }
+}
+
+/// An enum to define the comparison between two values.
+///
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+// Implement key features of the comparison
+impl Comparison {
+    /// A method to check if the comparison is true for the provided values
+    ///
+    fn is_true(self, left: u64, right: u64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+// Implement key features of the status condition
+impl StatusCondition {
+    /// A method to check if the condition is true for the provided statuses.
+    /// A condition on a missing status (or a variable of a status which is
+    /// not counted) is always false.
+    ///
+    pub fn is_true(&self, status_map: &StatusMap) -> bool {
+        match self {
+            &StatusCondition::Is {
+                ref status_id,
+                ref state,
+            } => status_map
+                .get(status_id)
+                .map_or(false, |detail| detail.current() == *state),
+            &StatusCondition::All(ref conditions) => conditions
+                .iter()
+                .all(|condition| condition.is_true(status_map)),
+            &StatusCondition::Any(ref conditions) => conditions
+                .iter()
+                .any(|condition| condition.is_true(status_map)),
+            &StatusCondition::AtLeast {
+                ref count,
+                ref conditions,
+            } => {
+                conditions
+                    .iter()
+                    .filter(|condition| condition.is_true(status_map))
+                    .count()
+                    >= *count
+            }
+            &StatusCondition::Not(ref condition) => !condition.is_true(status_map),
+            &StatusCondition::Compare {
+                ref left,
+                ref comparison,
+                ref right,
+            } => match (left.value(status_map), right.value(status_map)) {
+                (Some(left), Some(right)) => comparison.is_true(left, right),
+                _ => false,
+            },
+        }
+    }
+
+    /// A method to list every status and state that the condition depends on.
+    ///
+    pub fn inputs(&self) -> Vec<(ItemId, ItemId)> {
+        match self {
+            &StatusCondition::Is {
+                ref status_id,
+                ref state,
+            } => vec![(status_id.clone(), state.clone())],
+            &StatusCondition::All(ref conditions)
+            | &StatusCondition::Any(ref conditions)
+            | &StatusCondition::AtLeast { ref conditions, .. } => conditions
+                .iter()
+                .flat_map(|condition| condition.inputs())
+                .collect(),
+            &StatusCondition::Not(ref condition) => condition.inputs(),
+            &StatusCondition::Compare {
+                ref left,
+                ref right,
+                ..
+            } => left.inputs().into_iter().chain(right.inputs()).collect(),
+        }
+    }
+
+    /// A method to list every status whose variable the condition depends on.
+    ///
+    pub fn variables(&self) -> Vec<ItemId> {
+        match self {
+            &StatusCondition::Is { .. } => Vec::new(),
+            &StatusCondition::All(ref conditions)
+            | &StatusCondition::Any(ref conditions)
+            | &StatusCondition::AtLeast { ref conditions, .. } => conditions
+                .iter()
+                .flat_map(|condition| condition.variables())
+                .collect(),
+            &StatusCondition::Not(ref condition) => condition.variables(),
+            &StatusCondition::Compare {
+                ref left,
+                ref right,
+                ..
+            } => left
+                .variables()
+                .into_iter()
+                .chain(right.variables())
+                .collect(),
+        }
+    }
 }
 
 // Reexport the status detail variants
-use self::StatusDetail::{CountedState, MultiState};
+use self::StatusDetail::{CountedState, DerivedState, MultiState};
 
 // Implement key features for Status Detail
 impl StatusDetail {
@@ -260,6 +652,41 @@ impl StatusDetail {
         match self {
             &MultiState { ref current, .. } => current.clone(),
             &CountedState { ref current, .. } => current.clone(),
+            &DerivedState { ref current, .. } => current.clone(),
+        }
+    }
+
+    /// A method to check if the status is derived from other statuses (and
+    /// therefore cannot be changed directly).
+    ///
+    pub fn is_derived(&self) -> bool {
+        match self {
+            &DerivedState { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// A method to calculate the state of a derived status from the provided
+    /// statuses. Returns None if the status is not derived.
+    ///
+    pub fn derive(&self, status_map: &StatusMap) -> Option<ItemId> {
+        match self {
+            &DerivedState {
+                ref rules,
+                ref default,
+                ..
+            } => {
+                // Use the state of the first rule that applies
+                for rule in rules.iter() {
+                    if rule.condition.is_true(status_map) {
+                        return Some(rule.state.clone());
+                    }
+                }
+
+                // Otherwise use the default state
+                Some(default.clone())
+            }
+            _ => None,
         }
     }
 
@@ -281,6 +708,20 @@ impl StatusDetail {
                 allowed.push(reset.clone());
                 allowed
             }
+            &DerivedState {
+                ref rules,
+                ref default,
+                ..
+            } => {
+                // Collect the state of each rule and the default state
+                let mut allowed = Vec::new();
+                for state in rules.iter().map(|rule| &rule.state).chain(Some(default)) {
+                    if !allowed.contains(state) {
+                        allowed.push(state.clone());
+                    }
+                }
+                allowed
+            }
         }
     }
 
@@ -305,28 +746,89 @@ impl StatusDetail {
                 // Check if the new state is valid
                 (*new_state == *trigger) | (*new_state == *anti_trigger) | (*new_state == *reset)
             }
+
+            // The derivedstate variant
+            &DerivedState { .. } => self.allowed().contains(new_state),
         }
     }
 
+    /// A method to return the allowed transitions of the status, if they are
+    /// restricted.
+    ///
+    pub fn transitions(&self) -> Option<TransitionMap> {
+        match self {
+            &MultiState {
+                ref transitions, ..
+            } => transitions.clone(),
+            _ => None,
+        }
+    }
+
+    /// A method to return the events to trigger when the status moves from
+    /// the old state to the new state: the exit event of the old state and
+    /// then the entry event of the new state. Remaining in the same state does
+    /// not trigger either event.
+    ///
+    pub fn transition_events(&self, old_state: &ItemId, new_state: &ItemId) -> Vec<ItemId> {
+        // Return nothing if the state did not change
+        let mut events = Vec::new();
+        if old_state == new_state {
+            return events;
+        }
+
+        // Collect the exit and entry events
+        if let &MultiState {
+            ref entry_events,
+            ref exit_events,
+            ..
+        } = self
+        {
+            if let Some(event) = exit_events.get(old_state) {
+                events.push(event.clone());
+            }
+            if let Some(event) = entry_events.get(new_state) {
+                events.push(event.clone());
+            }
+        }
+        events
+    }
+
     /// A method to update the state of the status, first checking for
-    /// that the new state is valid. If the operation was successful, the
-    /// method returns the new state, otherwise None.
+    /// that the new state is valid and that the transition from the current
+    /// state is allowed. If the operation was successful, the method returns
+    /// the new state.
     ///
-    pub fn update(&mut self, new_state: ItemId) -> Option<ItemId> {
+    /// # Errors
+    ///
+    /// This method will return an error describing the problem if the new
+    /// state is not allowed or the transition is not allowed.
+    ///
+    pub fn update(&mut self, new_state: ItemId) -> Result<ItemId, Error> {
         match self {
             // The multistate variant
             &mut MultiState {
                 ref mut current,
                 ref allowed,
+                ref transitions,
                 ..
             } => {
                 // Check that the new state is valid
-                if allowed.is_empty() | allowed.contains(&new_state) {
-                    // Update the state
-                    *current = new_state;
-                    return Some(new_state);
+                if !(allowed.is_empty() | allowed.contains(&new_state)) {
+                    return Err(format_err!("State {} Is Not Allowed.", new_state));
+                }
+
+                // Check that the transition is allowed
+                if !is_legal_transition(transitions, current, &new_state) {
+                    return Err(format_err!(
+                        "Transition From {} To {} Is Not Allowed.",
+                        current,
+                        new_state
+                    ));
                 }
-                None // indicate failure
+
+                // Update the state
+                *current = new_state;
+                Ok(new_state)
             }
 
             // The countedstate variant
@@ -345,7 +847,7 @@ impl StatusDetail {
                     *current = *anti_trigger; // reset the current state
 
                     // Return the current state
-                    Some(current.clone())
+                    Ok(current.clone())
 
                 // Increment the count when the anti-trigger is provided
                 } else if new_state == *anti_trigger {
@@ -353,7 +855,7 @@ impl StatusDetail {
                     *current = *anti_trigger; // reset the current state
 
                     // Return the current state
-                    Some(current.clone())
+                    Ok(current.clone())
 
                 // Decrement the count when the trigger is provided
                 } else if new_state == *trigger {
@@ -368,17 +870,90 @@ impl StatusDetail {
                     }
 
                     // Return the current state
-                    Some(current.clone())
+                    Ok(current.clone())
 
                 // Otherwise report failure
                 } else {
-                    None
+                    Err(format_err!("State {} Is Not Allowed.", new_state))
+                }
+            }
+
+            // The derivedstate variant cannot be changed directly
+            &mut DerivedState { .. } => Err(format_err!(
+                "Status Is Derived From Other Statuses And Cannot Be Changed."
+            )),
+        }
+    }
+
+    /// A method to restore the state of the status without checking the
+    /// transition rules (used when reloading a backup). If the operation was
+    /// successful, the method returns the new state.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the new state is not allowed.
+    ///
+    pub fn restore(&mut self, new_state: ItemId) -> Result<ItemId, Error> {
+        match self {
+            // Set the multistate variant directly
+            &mut MultiState {
+                ref mut current,
+                ref allowed,
+                ..
+            } => {
+                // Check that the new state is valid
+                if !(allowed.is_empty() | allowed.contains(&new_state)) {
+                    return Err(format_err!("State {} Is Not Allowed.", new_state));
+                }
+
+                // Update the state
+                *current = new_state;
+                Ok(new_state)
+            }
+
+            // Update the countedstate variant normally
+            &mut CountedState { .. } => self.update(new_state),
+
+            // Set the derivedstate variant directly
+            &mut DerivedState {
+                ref mut current,
+                ref rules,
+                ref default,
+            } => {
+                // Check that the new state is valid
+                if (new_state != *default) & !rules.iter().any(|rule| rule.state == new_state) {
+                    return Err(format_err!("State {} Is Not Allowed.", new_state));
                 }
+
+                // Update the state
+                *current = new_state;
+                Ok(new_state)
             }
         }
     }
 }
 
+/// A helper function to check if the transition between two states is allowed
+/// by the provided transition rules. Remaining in the same state is always
+/// allowed, as are all transitions if there are no rules.
+///
+fn is_legal_transition(
+    transitions: &Option<TransitionMap>,
+    old_state: &ItemId,
+    new_state: &ItemId,
+) -> bool {
+    // Check the transition rules, if they exist
+    match transitions {
+        &Some(ref transitions) => {
+            (old_state == new_state)
+                | transitions
+                    .get(old_state)
+                    .map_or(false, |next_states| next_states.contains(new_state))
+        }
+        &None => true,
+    }
+}
+
 /// A struct which allows a limited number of possible states. This version
 /// uses fully described itempairs for use with the user interface. If the
 /// allowed state vector is empty, any state will be allowed.
@@ -389,6 +964,26 @@ impl StatusDetail {
 pub struct StatusDescription {
     pub current: ItemPair,
     pub allowed: Vec<ItemPair>,
+    pub transitions: Option<TransitionMap>, // the allowed transitions, if restricted
+    pub is_derived: bool, // a flag to indicate the status cannot be changed directly
+}
+
+// Implement key features of the status description
+impl StatusDescription {
+    /// A method to return the allowed states which may follow the current
+    /// state (including the current state itself). A derived status lists all
+    /// of its states, though none may be selected.
+    ///
+    #[allow(dead_code)]
+    pub fn available(&self) -> Vec<ItemPair> {
+        // Filter the allowed states by the transition rules
+        let current = self.current.get_id();
+        self.allowed
+            .iter()
+            .filter(|state| is_legal_transition(&self.transitions, &current, &state.get_id()))
+            .cloned()
+            .collect()
+    }
 }
 
 // Tests of the status module
@@ -402,4 +997,253 @@ mod tests {
         // FIXME: Implement this
         unimplemented!();
     }
+
+    // Test the transition rules and events of a multistate status
+    #[test]
+    fn transition_rules() {
+        // Import features for testing
+        use super::super::super::item::Hidden;
+
+        // Create a status which must pass from idle to armed to triggered
+        let idle = ItemId::new_unchecked(21);
+        let armed = ItemId::new_unchecked(22);
+        let triggered = ItemId::new_unchecked(23);
+        let mut transitions = TransitionMap::default();
+        transitions.insert(idle, vec![armed]);
+        transitions.insert(armed, vec![idle, triggered]);
+        let mut entry_events = StateEvents::default();
+        entry_events.insert(armed, ItemId::new_unchecked(30));
+        let mut exit_events = StateEvents::default();
+        exit_events.insert(armed, ItemId::new_unchecked(31));
+        let mut detail = MultiState {
+            current: idle,
+            allowed: vec![idle, armed, triggered],
+            transitions: Some(transitions),
+            entry_events,
+            exit_events,
+        };
+
+        // Verify that an illegal transition is rejected
+        assert!(detail.update(triggered).is_err());
+        assert_eq!(detail.current(), idle);
+
+        // Verify that a legal transition succeeds with the entry event
+        assert_eq!(detail.update(armed).unwrap(), armed);
+        assert_eq!(
+            detail.transition_events(&idle, &armed),
+            vec![ItemId::new_unchecked(30)]
+        );
+
+        // Verify that leaving the state triggers the exit event
+        assert_eq!(detail.update(triggered).unwrap(), triggered);
+        assert_eq!(
+            detail.transition_events(&armed, &triggered),
+            vec![ItemId::new_unchecked(31)]
+        );
+
+        // Verify that remaining in the same state is allowed without events
+        assert_eq!(detail.update(triggered).unwrap(), triggered);
+        assert!(detail.transition_events(&triggered, &triggered).is_empty());
+
+        // Verify that a restore ignores the transition rules
+        assert_eq!(detail.restore(idle).unwrap(), idle);
+
+        // Verify that the description only offers the legal transitions
+        let description = StatusDescription {
+            current: ItemPair::from_item(idle, ItemDescription::new("Idle", Hidden)),
+            allowed: vec![
+                ItemPair::from_item(idle, ItemDescription::new("Idle", Hidden)),
+                ItemPair::from_item(armed, ItemDescription::new("Armed", Hidden)),
+                ItemPair::from_item(triggered, ItemDescription::new("Triggered", Hidden)),
+            ],
+            transitions: detail.transitions(),
+            is_derived: false,
+        };
+        let available: Vec<ItemId> = description
+            .available()
+            .iter()
+            .map(|state| state.get_id())
+            .collect();
+        assert_eq!(available, vec![idle, armed]);
+    }
+
+    // Test a derived status which follows two other statuses
+    #[test]
+    fn derived_status() {
+        // Import features for testing
+        use super::super::super::super::GeneralUpdate;
+
+        // Create two locks with open and closed states
+        let (lock_one, lock_two) = (ItemId::new_unchecked(10), ItemId::new_unchecked(20));
+        let (open, closed) = (ItemId::new_unchecked(11), ItemId::new_unchecked(12));
+        let mut status_map = StatusMap::default();
+        for lock in vec![lock_one, lock_two] {
+            status_map.insert(
+                lock,
+                MultiState {
+                    current: closed,
+                    allowed: vec![open, closed],
+                    transitions: None,
+                    entry_events: StateEvents::default(),
+                    exit_events: StateEvents::default(),
+                },
+            );
+        }
+
+        // Create a derived status which is open when both locks are open
+        let all_open = ItemId::new_unchecked(30);
+        let (yes, no) = (ItemId::new_unchecked(31), ItemId::new_unchecked(32));
+        status_map.insert(
+            all_open,
+            DerivedState {
+                current: yes, // corrected when the handler is created
+                rules: vec![DerivedRule {
+                    condition: StatusCondition::All(vec![
+                        StatusCondition::Is {
+                            status_id: lock_one,
+                            state: open,
+                        },
+                        StatusCondition::Is {
+                            status_id: lock_two,
+                            state: open,
+                        },
+                    ]),
+                    state: yes,
+                }],
+                default: no,
+            },
+        );
+        let (general_update, _receive) = GeneralUpdate::new();
+        let mut handler = StatusHandler::new(general_update, status_map);
+        assert_eq!(handler.get_state(&all_open), Some(no));
+
+        // Open the first lock and verify the derived status does not change
+        let changes = handler.modify_status(&lock_one, &open).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(handler.get_state(&all_open), Some(no));
+
+        // Open the second lock and verify the derived status changes
+        let changes = handler.modify_status(&lock_two, &open).unwrap();
+        assert_eq!(
+            changes[1],
+            StateChange {
+                status_id: all_open,
+                new_state: yes,
+                events: Vec::new(),
+            }
+        );
+        assert_eq!(handler.get_state(&all_open), Some(yes));
+
+        // Verify that the derived status cannot be changed directly
+        assert_eq!(handler.modify_status(&all_open, &no), None);
+
+        // Verify that a majority condition counts the matching conditions
+        let majority = StatusCondition::AtLeast {
+            count: 2,
+            conditions: vec![
+                StatusCondition::Is {
+                    status_id: lock_one,
+                    state: open,
+                },
+                StatusCondition::Is {
+                    status_id: lock_two,
+                    state: closed,
+                },
+                StatusCondition::Not(Box::new(StatusCondition::Is {
+                    status_id: all_open,
+                    state: no,
+                })),
+            ],
+        };
+        assert!(majority.is_true(&handler.get_map()));
+    }
+
+    // Test the comparisons of variables and counts in a derived status
+    #[test]
+    fn compare_derived_status() {
+        // Create a counter of the hints given and a lock
+        let (hints, lock) = (ItemId::new_unchecked(10), ItemId::new_unchecked(20));
+        let (done, counting, reset) = (
+            ItemId::new_unchecked(11),
+            ItemId::new_unchecked(12),
+            ItemId::new_unchecked(13),
+        );
+        let (open, closed) = (ItemId::new_unchecked(21), ItemId::new_unchecked(22));
+        let mut status_map = StatusMap::default();
+        status_map.insert(
+            hints,
+            CountedState {
+                current: counting,
+                trigger: done,
+                anti_trigger: counting,
+                reset,
+                count: 0,
+                default_count: 0,
+            },
+        );
+        status_map.insert(
+            lock,
+            MultiState {
+                current: closed,
+                allowed: vec![open, closed],
+                transitions: None,
+                entry_events: StateEvents::default(),
+                exit_events: StateEvents::default(),
+            },
+        );
+
+        // Create a derived status which is a bonus when the lock is open with fewer than two hints
+        let bonus = ItemId::new_unchecked(30);
+        let (yes, no) = (ItemId::new_unchecked(31), ItemId::new_unchecked(32));
+        let condition = StatusCondition::All(vec![
+            StatusCondition::Compare {
+                left: Operand::Variable(hints),
+                comparison: Comparison::Less,
+                right: Operand::Value(2),
+            },
+            StatusCondition::Compare {
+                left: Operand::Count(vec![StatusCondition::Is {
+                    status_id: lock,
+                    state: open,
+                }]),
+                comparison: Comparison::Equal,
+                right: Operand::Value(1),
+            },
+        ]);
+        assert_eq!(condition.inputs(), vec![(lock, open)]);
+        assert_eq!(condition.variables(), vec![hints]);
+        status_map.insert(
+            bonus,
+            DerivedState {
+                current: no,
+                rules: vec![DerivedRule {
+                    condition,
+                    state: yes,
+                }],
+                default: no,
+            },
+        );
+        let (general_update, _receive) = GeneralUpdate::new();
+        let mut handler = StatusHandler::new(general_update, status_map);
+        assert_eq!(handler.get_state(&bonus), Some(no));
+
+        // Open the lock and verify the derived status changes
+        handler.modify_status(&lock, &open).unwrap();
+        assert_eq!(handler.get_state(&bonus), Some(yes));
+
+        // Count two hints and verify the derived status changes back
+        handler.modify_status(&hints, &counting).unwrap();
+        assert_eq!(handler.get_state(&bonus), Some(yes));
+        let changes = handler.modify_status(&hints, &counting).unwrap();
+        assert_eq!(changes[1].new_state, no);
+        assert_eq!(handler.get_state(&bonus), Some(no));
+
+        // Verify that a variable of a status which is not counted is always false
+        let wrong = StatusCondition::Compare {
+            left: Operand::Variable(lock),
+            comparison: Comparison::GreaterOrEqual,
+            right: Operand::Value(0),
+        };
+        assert!(!wrong.is_true(&handler.get_map()));
+    }
 }
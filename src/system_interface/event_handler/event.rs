@@ -95,6 +95,13 @@ pub enum DataType {
 
     /// A variant for a string collected from the user
     UserString,
+
+    /// A variant for the provided number of the fastest completion times
+    /// from the session results. The number of times is sent first, followed
+    /// by each time in seconds.
+    BestTimes {
+        count: u32, // the maximum number of times
+    },
 }
 
 /// An enum with various action options for each event.
@@ -127,6 +134,10 @@ pub enum EventAction {
     /// when broadcast to the system
     SendData { data: DataType },
 
+    /// A variant which saves the game report of the current session next to
+    /// the game log.
+    SaveReport,
+
     /// A variant which indicates a grouped event. This event changes its
     /// event detail based on the state of the corresponding status.
     GroupedEvent {
@@ -141,7 +152,7 @@ pub type EventDetail = Vec<EventAction>;
 
 // Reexport the event action type variants
 pub use self::EventAction::{
-    CancelEvent, GroupedEvent, ModifyStatus, NewScene, QueueEvent, SaveData, SendData,
+    CancelEvent, GroupedEvent, ModifyStatus, NewScene, QueueEvent, SaveData, SaveReport, SendData,
 };
 
 /// An enum for updating the rest of the system on changes to the scene and
@@ -150,8 +161,8 @@ pub use self::EventAction::{
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum EventUpdate {
     /// A variant that notifies the rest of the system to broadcast this
-    /// event with optional data.
-    Broadcast(ItemPair, Option<u32>),
+    /// event once with any data (each piece of data is sent separately).
+    Broadcast(ItemPair, Vec<u32>),
 
     /// A variant that notifies the rest of the system of a currently playing
     /// event.
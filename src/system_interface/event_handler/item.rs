@@ -455,6 +455,7 @@ impl ItemPair {
     /// the all stop item id. This is useful when either (or both) of these
     /// cases are possible and desired.
     ///
+    #[allow(dead_code)]
     pub fn new_unchecked(id: u32, description: &str, display: DisplayType) -> ItemPair {
         ItemPair {
             id,
@@ -515,6 +516,7 @@ impl ItemPair {
     /// This method compares the description as well as the id of the item pairs
     /// (as contrasted with the == operator which will just compare the ids).
     ///
+    #[allow(dead_code)]
     pub fn truly_equal(&self, other_id: &ItemPair) -> bool {
         self == other_id
             && self.description == other_id.description
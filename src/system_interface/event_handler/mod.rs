@@ -22,7 +22,8 @@
 //! of the program.
 
 // Reexport the key structures and types
-pub use self::config::{FullStatus, KeyMap, StatusDescription};
+pub use self::backup::{BackupSummary, ResumeMode};
+pub use self::config::{compose_schema, FullStatus, KeyMap, StatusDescription};
 pub use self::queue::ComingEvent;
 
 // Define public submodules
@@ -36,23 +37,29 @@ mod config;
 mod queue;
 
 // Import the relevant structures into the correct namespace
-use self::backup::BackupHandler;
-use self::config::Config;
+use self::backup::{BackupData, BackupHandler, ResumedEvent};
+use self::config::{Config, SequenceAction};
 use self::event::{
     CancelEvent, DataType, EventAction, EventDelay, EventDetail, EventUpdate, GroupedEvent,
-    ModifyStatus, NewScene, QueueEvent, SaveData, SendData, UpcomingEvent,
+    ModifyStatus, NewScene, QueueEvent, SaveData, SaveReport, SendData, UpcomingEvent,
 };
 use self::item::{ItemDescription, ItemId, ItemPair};
 use self::queue::Queue;
+use super::log_file::read_rotated;
+use super::statistics::{SessionResult, Statistics, StatisticsSummary};
 use super::system_connection::ConnectionSet;
-use super::{GeneralUpdate, InterfaceUpdate};
+use super::web_console::ConsoleSettings;
+use super::{
+    Clock, ConfigTask, GeneralUpdate, InterfaceUpdate, LogSettings, ReportSettings, SystemUpdate,
+};
 
 // Import standard library modules
-use std::fs::File;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 // Import the failure features
 use failure::Error;
@@ -64,8 +71,12 @@ use failure::Error;
 pub struct EventHandler {
     general_update: GeneralUpdate, // sending line for event updates and timed events
     queue: Queue,                  // current event queue
+    clock: Clock,                  // the clock for timing all events
     config: Config,                // current configuration
     backup: BackupHandler,         // current backup server
+    mirrored: Option<BackupData>,  // the last backup mirrored from the primary, if on standby
+    lingering: Option<BackupData>, // lingering backup data waiting for the operator to resume or discard it
+    statistics: Statistics,        // the results of past sessions
 }
 
 // Implement the event handler functions
@@ -77,7 +88,8 @@ impl EventHandler {
     /// module including errors, warnings, and normal system updates (like
     /// currently playing events). The main program is expected to parse these
     /// updates appropriately. See the event::EventUpdate enum for more detail
-    /// on the possible update types.
+    /// on the possible update types. All events are timed according to the
+    /// provided clock.
     ///
     /// # Errors
     ///
@@ -95,10 +107,68 @@ impl EventHandler {
         config_path: PathBuf,
         general_update: GeneralUpdate,
         interface_send: mpsc::Sender<InterfaceUpdate>,
+        clock: Clock,
+        log_failure: bool,
+    ) -> Result<EventHandler, Error> {
+        EventHandler::compose(
+            config_path,
+            general_update,
+            interface_send,
+            clock,
+            log_failure,
+            true,
+        )
+    }
+
+    /// A function to create a new event handler which never connects to the
+    /// backup of the configuration (neither the Redis server nor the backup
+    /// file), for running a configuration without disturbing a live game.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the provided configuration
+    /// filename was impossible to find, impossible to parse, or raised a fatal
+    /// error, like the new function.
+    ///
+    #[cfg(test)]
+    pub fn new_without_backup(
+        config_path: PathBuf,
+        general_update: GeneralUpdate,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+        clock: Clock,
+    ) -> Result<EventHandler, Error> {
+        EventHandler::compose(
+            config_path,
+            general_update,
+            interface_send,
+            clock,
+            true,
+            false,
+        )
+    }
+
+    /// A method to check whether the event queue is idle (no event is waiting
+    /// to be loaded or due to trigger). Once the queue is idle, every event it
+    /// triggered has already been sent on the general update line.
+    ///
+    #[cfg(test)]
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_idle()
+    }
+
+    /// An internal function to create a new event handler, connecting to the
+    /// backup of the configuration only if the backup flag is set.
+    ///
+    fn compose(
+        config_path: PathBuf,
+        general_update: GeneralUpdate,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+        clock: Clock,
         log_failure: bool,
+        is_backed_up: bool,
     ) -> Result<EventHandler, Error> {
         // Attempt to open the configuration file
-        let config_file = match File::open(config_path) {
+        let config_file = match File::open(&config_path) {
             Ok(file) => file,
             Err(_) => {
                 // Only log failure if the flag is set
@@ -110,57 +180,530 @@ impl EventHandler {
         };
 
         // Attempt to process the configuration file
-        let mut config = Config::from_config(general_update.clone(), interface_send, &config_file)?;
+        let config = Config::from_config(
+            general_update.clone(),
+            interface_send,
+            &config_file,
+            config_folder(&config_path),
+        )?;
 
-        // Attempt to create the backup handler
+        // Attempt to create the backup handler (without a connection, if not backed up)
+        let (server_location, backup_path) = if is_backed_up {
+            (config.server_location(), config.backup_path())
+        } else {
+            (None, None)
+        };
         let backup = BackupHandler::new(
             general_update.clone(),
             config.identifier(),
-            config.server_location(),
+            server_location,
+            backup_path,
         )?;
 
         // Create an empty event queue
-        let queue = Queue::new(general_update.clone());
+        let queue = Queue::new(general_update.clone(), clock.clone());
 
-        // Check for existing data from the backup handler
-        if let Some((current_scene, status_pairs, queued_events)) =
-            backup.reload_backup(config.get_status_ids())
+        // Load the results of past sessions, stored next to the configuration
+        let statistics = Statistics::new(
+            general_update.clone(),
+            config_path.with_extension("results.yaml"),
+        );
+
+        // Create the new EventHandler with the new queue
+        let mut event_handler = EventHandler {
+            general_update: general_update.clone(),
+            queue,
+            clock,
+            config,
+            backup,
+            mirrored: None,
+            lingering: None,
+            statistics,
+        };
+
+        // If another instance already holds the backup as the primary, start on standby
+        if !event_handler.backup.claim_primary() {
+            // Notify that this instance is on standby
+            update!(warn &general_update => "Detected A Running Primary Instance. Starting On Standby ...");
+            event_handler.backup.start_standby(general_update);
+
+        // Otherwise, become the primary
+        } else {
+            // Hold any existing backup data until the operator decides what to resume
+            let status_ids = event_handler.config.get_status_ids();
+            event_handler.lingering = event_handler.backup.reload_backup(status_ids);
+            if event_handler.lingering.is_some() {
+                update!(err &general_update => "Detected Lingering Backup Data. Waiting For The Operator (See Lingering Backup In The Menu) ...");
+                event_handler.backup.set_held(true);
+
+            // If there was no existing data, start normally
+            } else {
+                event_handler.resume_backup(None, ResumeMode::Frozen);
+            }
+        }
+
+        // Return the completed EventHandler
+        Ok(event_handler)
+    }
+
+    /// A method to reload the configuration from the provided file while
+    /// keeping the live state. The current scene, the status states and the
+    /// queued events are kept wherever their ids still exist in the new
+    /// configuration, and the queue and backup are left running.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if the new configuration could not be
+    /// opened or parsed, and will raise a warning for every part of the state
+    /// which could not be kept.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line and leaving the
+    /// current configuration in place.
+    ///
+    pub fn reload_config(
+        &mut self,
+        config_path: &Path,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+    ) -> Result<(), Error> {
+        // Attempt to open the configuration file
+        let config_file = match File::open(config_path) {
+            Ok(file) => file,
+            Err(_) => {
+                update!(err &self.general_update => "Unable To Open Configuration File.");
+                return Err(format_err!("Unable to open configuration file."));
+            }
+        };
+
+        // Attempt to process the new configuration
+        let mut config = Config::from_reload(
+            &mut self.config,
+            self.general_update.clone(),
+            interface_send,
+            &config_file,
+            config_folder(config_path),
+        )?;
+
+        // Keep the current scene, if it still exists
+        let current_scene = self.config.get_current_scene();
+        if config.is_scene(&current_scene.get_id()) {
+            config.choose_scene(current_scene.get_id()).unwrap_or(());
+        } else {
+            update!(warn &self.general_update => "Scene {} No Longer Exists. Changed To {}.", current_scene.description, config.get_current_scene().description);
+        }
+
+        // Keep the status states, if the status still exists
+        let status_ids = config.get_status_ids();
+        let mut status_pairs = Vec::new();
+        for status_id in self.config.get_status_ids() {
+            if !status_ids.contains(&status_id) {
+                update!(warn &self.general_update => "Status {} No Longer Exists. Its State Was Dropped.", self.config.get_description(&status_id).description);
+            } else if let Some(state) = self.config.get_state(&status_id) {
+                status_pairs.push((status_id, state));
+            }
+        }
+        config.load_backup_status(status_pairs);
+
+        // Keep the queued events, if the event still exists
+        let mut dropped = Vec::new();
+        for event in self.queue.get_events() {
+            let event_id = event.id();
+            if !config.is_event(&event_id) && !dropped.contains(&event_id) {
+                update!(warn &self.general_update => "Event {} No Longer Exists. Removed From The Queue.", self.config.get_description(&event_id).description);
+                self.queue.cancel_all(event_id);
+                dropped.push(event_id);
+            }
+        }
+
+        // Reset any sequences in progress and cancel their timeouts
+        let (reset, actions) = self.config.reset_sequences();
+        for description in reset {
+            update!(warn &self.general_update => "Sequence {} Was In Progress And Has Been Reset.", description.description);
+        }
+        for action in actions {
+            if let SequenceAction::CancelTimeout { timeout } = action {
+                self.queue.cancel_event(timeout);
+            }
+        }
+
+        // Warn that any new backup settings take effect only once the configuration is loaded again
+        if (config.identifier() != self.config.identifier())
+            | (config.server_location() != self.config.server_location())
+            | (config.backup_path() != self.config.backup_path())
         {
-            // Notify that existing data was found
-            update!(err &general_update => "Detected Lingering Backup Data. Reloading ...");
+            update!(warn &self.general_update => "Backup Settings Changed. Choose The Configuration Again To Apply Them.");
+        }
+
+        // Replace the configuration and back up the kept state
+        self.config = config;
+        let current_scene = self.config.get_current_scene().get_id();
+        self.backup.backup_current_scene(&current_scene);
+        for status_id in self.config.get_status_ids() {
+            if let Some(state) = self.config.get_state(&status_id) {
+                self.backup.backup_status(&status_id, &state);
+            }
+        }
+        update!(update &self.general_update => "Configuration Reloaded.");
+
+        // Trigger a redraw of the window
+        self.general_update.send_redraw();
+        Ok(())
+    }
+
+    /// A method to return the files included by the current configuration
+    /// (relative to the folder of the configuration).
+    ///
+    pub fn included_files(&self) -> Vec<PathBuf> {
+        self.config.included_files()
+    }
+
+    /// A method to check if this instance is on standby (i.e. mirroring a
+    /// primary instance).
+    ///
+    pub fn is_standby(&self) -> bool {
+        self.backup.is_standby()
+    }
+
+    /// A method to mirror the backup of the primary instance while on
+    /// standby. Returns the queued events of the primary (to display) if the
+    /// backup has changed since the last mirror.
+    ///
+    pub fn mirror_primary(&mut self) -> Option<Vec<UpcomingEvent>> {
+        // Only mirror while on standby
+        if !self.is_standby() {
+            return None;
+        }
+
+        // Check for new data from the primary
+        let backup_data = self.backup.reload_backup(self.config.get_status_ids());
+        if backup_data == self.mirrored {
+            return None;
+        }
+        self.mirrored = backup_data.clone();
+
+        // Silently copy the current scene and status states
+        let (current_scene, status_pairs, queued_events) = backup_data?;
+        self.config.choose_scene(current_scene).unwrap_or(());
+        self.config.load_backup_status(status_pairs);
+
+        // Repackage the queued events with their remaining time until the deadline
+        let now = SystemTime::now();
+        let start_time = self.clock.now();
+        let mut upcoming_events = Vec::new();
+        let resume_mode = self.takeover_mode();
+        for event in queued_events {
+            if let ResumedEvent::Delay(delay) = event.resume(resume_mode, now) {
+                upcoming_events.push(UpcomingEvent {
+                    start_time,
+                    delay,
+                    event: ItemPair::from_item(
+                        event.event_id,
+                        self.get_description(&event.event_id),
+                    ),
+                });
+            }
+        }
+        Some(upcoming_events)
+    }
+
+    /// A method to take over from the primary instance after it stopped
+    /// responding. The backup is always resumed with the deadlines of the
+    /// events (the frozen remaining times would be out of date).
+    ///
+    pub fn take_over(&mut self) {
+        // Only take over while on standby
+        if !self.is_standby() {
+            return;
+        }
+
+        // Become the primary and resume from the backup
+        self.backup.take_over();
+        self.mirrored = None;
+        let resume_mode = self.takeover_mode();
+        let backup_data = self.backup.reload_backup(self.config.get_status_ids());
+        self.resume_backup(backup_data, resume_mode);
+    }
+
+    /// An internal method to return how the queued events are resumed when
+    /// taking over from the primary instance. The configured resume mode is
+    /// used, except that frozen times are replaced with the deadlines.
+    ///
+    fn takeover_mode(&self) -> ResumeMode {
+        match self.config.resume_mode() {
+            ResumeMode::Frozen => ResumeMode::Deadlines {
+                skip_expired: false,
+            },
+            resume_mode => resume_mode,
+        }
+    }
+
+    /// A method to summarize the lingering backup data (if any) which is
+    /// waiting for the operator to resume or discard it.
+    ///
+    pub fn backup_summary(&self) -> Option<BackupSummary> {
+        // Unpack the lingering backup data
+        let (current_scene, status_pairs, queued_events) = self.lingering.as_ref()?;
+
+        // Collect the statuses which differ from the current (default) states
+        let mut statuses = Vec::new();
+        for (status_id, state) in status_pairs.iter() {
+            if self.config.get_state(status_id) != Some(*state) {
+                statuses.push((
+                    ItemPair::from_item(*status_id, self.get_description(status_id)),
+                    ItemPair::from_item(*state, self.get_description(state)),
+                ));
+            }
+        }
+
+        // Collect the queued events with the time remaining in either case
+        let now = SystemTime::now();
+        let deadlines = ResumeMode::Deadlines {
+            skip_expired: false,
+        };
+        let mut events = Vec::new();
+        for event in queued_events.iter() {
+            let remaining = match event.resume(deadlines, now) {
+                ResumedEvent::Delay(delay) => Some(delay),
+                _ => None,
+            };
+            events.push((
+                ItemPair::from_item(event.event_id, self.get_description(&event.event_id)),
+                event.remaining,
+                remaining,
+            ));
+        }
+
+        // Return the summary (with the configured resume mode and policy as the defaults)
+        Some(BackupSummary {
+            age: self.backup.backup_age(),
+            scene: ItemPair::from_item(*current_scene, self.get_description(current_scene)),
+            statuses,
+            events,
+            resume_mode: self.config.resume_mode(),
+            headless_resume: self.config.headless_resume(),
+        })
+    }
+
+    /// A method to resume the selected parts of the lingering backup data
+    /// (the current scene, the status states, and the queued events) with
+    /// the resume mode chosen by the operator. Any part which is not selected
+    /// is discarded. If nothing is selected, the reset event of the current
+    /// scene is triggered instead. The backup is held unchanged until this
+    /// method is called.
+    ///
+    /// # Errors
+    ///
+    /// This method will fail silently if there is no lingering backup data.
+    ///
+    pub fn resume_lingering(
+        &mut self,
+        scene: bool,
+        statuses: bool,
+        events: bool,
+        resume_mode: ResumeMode,
+    ) {
+        // Take the lingering backup data, if it exists
+        let (current_scene, status_pairs, queued_events) = match self.lingering.take() {
+            Some(backup_data) => backup_data,
+            None => return,
+        };
+
+        // Release the hold on the backup
+        self.backup.set_held(false);
+
+        // Discard the parts of the backup which were not selected
+        if !statuses {
+            self.backup
+                .discard_statuses(status_pairs.iter().map(|&(id, _)| id).collect());
+        }
+        if !events {
+            self.backup.discard_events();
+        }
+
+        // Resume the selected parts, or start fresh if nothing was selected
+        let backup_data = if scene || statuses || events {
+            Some((
+                if scene {
+                    current_scene
+                } else {
+                    self.config.get_current_scene().get_id()
+                },
+                if statuses { status_pairs } else { Vec::new() },
+                if events { queued_events } else { Vec::new() },
+            ))
+        } else {
+            update!(update &self.general_update => "Discarded Lingering Backup Data.");
+            None
+        };
+        self.resume_backup(backup_data, resume_mode);
+
+        // Back up the status states (which may have changed while the backup was held)
+        for status_id in self.config.get_status_ids() {
+            if let Some(state) = self.config.get_state(&status_id) {
+                self.backup.backup_status(&status_id, &state);
+            }
+        }
+    }
+
+    /// An internal method to resume from the provided backup data, using the
+    /// provided resume mode for the queued events. If there is no backup
+    /// data, the reset event for the current scene is triggered instead.
+    ///
+    fn resume_backup(&mut self, backup_data: Option<BackupData>, resume_mode: ResumeMode) {
+        // Check for existing data from the backup handler
+        if let Some((current_scene, status_pairs, queued_events)) = backup_data {
+            // Notify that the data is being reloaded
+            update!(err &self.general_update => "Reloading Backup Data ...");
 
             // Change the current scene silently (i.e. do not trigger the reset event)
-            config.choose_scene(current_scene).unwrap_or(());
+            self.config.choose_scene(current_scene).unwrap_or(());
 
             // Update the current status states based on the backup
-            config.load_backup_status(status_pairs);
+            self.config.load_backup_status(status_pairs);
 
-            // Update the queue with the found events
+            // Update the queue with the found events, according to the resume mode
+            let now = SystemTime::now();
             for event in queued_events {
-                queue.add_event(EventDelay::new(Some(event.remaining), event.event_id));
+                match event.resume(resume_mode, now) {
+                    // Queue the event with the resumed delay
+                    ResumedEvent::Delay(remaining) => {
+                        self.queue
+                            .add_event(EventDelay::new(Some(remaining), event.event_id));
+                    }
+
+                    // Trigger any event that expired during the outage
+                    ResumedEvent::Expired => {
+                        update!(warn &self.general_update => "Event Expired During Outage, Triggering Now: {}", self.config.get_description(&event.event_id));
+                        self.queue.add_event(EventDelay::new(None, event.event_id));
+                    }
+
+                    // Report any event that was skipped
+                    ResumedEvent::Skipped => {
+                        update!(warn &self.general_update => "Event Expired During Outage, Skipped: {}", self.config.get_description(&event.event_id));
+                    }
+                }
             }
 
             // Wait 10 nanoseconds for the queued events to process
             thread::sleep(Duration::new(0, 20));
 
             // Trigger a redraw of the window and timeline
-            general_update.send_redraw();
+            self.general_update.send_redraw();
 
         // If there was no existing data in the backup, trigger the scene reset event
         } else {
-            queue.add_event(EventDelay::new(None, config.get_current_scene().get_id()));
+            self.queue.add_event(EventDelay::new(
+                None,
+                self.config.get_current_scene().get_id(),
+            ));
         }
 
         // Load the current scene into the backup (to detect any crash after this point)
-        backup.backup_current_scene(&config.get_current_scene().get_id());
+        self.backup
+            .backup_current_scene(&self.config.get_current_scene().get_id());
+    }
 
-        // Return the completed EventHandler with a new queue
-        Ok(EventHandler {
-            general_update: general_update,
-            queue,
-            config,
-            backup,
-        })
+    /// A method to save a named checkpoint of the current scene, the current
+    /// status states and the events in the queue. A checkpoint with the same
+    /// name is replaced.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if there is no backup connected or if
+    /// the checkpoint could not be saved.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line.
+    ///
+    pub fn save_checkpoint(&self, name: &str) {
+        // Compile the current status states
+        let mut statuses = Vec::new();
+        for status_id in self.config.get_status_ids() {
+            if let Some(state) = self.config.get_state(&status_id) {
+                statuses.push((status_id, state));
+            }
+        }
+
+        // Save the checkpoint to the backup
+        self.backup.save_checkpoint(
+            name,
+            &self.config.get_current_scene().get_id(),
+            statuses,
+            self.queue.get_events(),
+            &self.clock,
+        );
+    }
+
+    /// A method to list the names of the saved checkpoints.
+    ///
+    pub fn list_checkpoints(&self) -> Vec<String> {
+        self.backup.list_checkpoints()
+    }
+
+    /// A method to delete the named checkpoint from the backup.
+    ///
+    pub fn delete_checkpoint(&self, name: &str) {
+        self.backup.delete_checkpoint(name);
+    }
+
+    /// A method to restore the named checkpoint: the current scene and status
+    /// states are replaced silently (i.e. without triggering the reset event
+    /// or any state events) and the queue is replaced with the events of the
+    /// checkpoint. If rebroadcast is set, the state event of every status is
+    /// then triggered so that any connected devices resynchronize.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if the checkpoint could not be found,
+    /// or if this instance is on standby.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line and leaving the
+    /// current state unmodified.
+    ///
+    pub fn restore_checkpoint(&mut self, name: &str, rebroadcast: bool) {
+        // Never restore a checkpoint while on standby
+        if self.is_standby() {
+            update!(warn &self.general_update => "Unable To Restore Checkpoint While On Standby.");
+            return;
+        }
+
+        // Try to load the checkpoint
+        let checkpoint = match self.backup.load_checkpoint(name) {
+            Some(checkpoint) => checkpoint,
+            None => return,
+        };
+        update!(update &self.general_update => "Restoring Checkpoint: {} ...", name);
+
+        // Clear the existing events in the queue
+        self.queue.clear();
+
+        // Change the current scene silently and back it up
+        if self.config.choose_scene(checkpoint.scene).is_ok() {
+            self.backup.backup_current_scene(&checkpoint.scene);
+        }
+
+        // Restore the status states silently and back them up
+        self.config.load_backup_status(checkpoint.statuses.clone());
+        for (status_id, state) in checkpoint.statuses.iter() {
+            self.backup.backup_status(status_id, state);
+        }
+
+        // Requeue the events with the remaining time at the checkpoint
+        for event in checkpoint.events {
+            self.queue
+                .add_event(EventDelay::new(Some(event.remaining), event.event_id));
+        }
+
+        // If requested, trigger the state event of every status
+        if rebroadcast {
+            for (_, state) in checkpoint.statuses {
+                self.queue.add_event(EventDelay::new(None, state));
+            }
+        }
+
+        // Trigger a redraw of the window and timeline
+        self.general_update.send_redraw();
     }
 
     /// A method to return the configured system connection type.
@@ -185,6 +728,35 @@ impl EventHandler {
         self.queue.clear();
     }
 
+    /// A method to notify the timed queue that the mode of the clock has
+    /// changed (so that it can recalculate the time until the next event).
+    ///
+    pub fn refresh_clock(&self) {
+        self.queue.wake();
+    }
+
+    /// A method to start or end a simulation. While simulating, nothing is
+    /// written to the backup. Once the simulation ends, the current scene,
+    /// the status states and the queued events are backed up again.
+    ///
+    pub fn set_simulated(&mut self, is_simulated: bool) {
+        // Pause (or resume) the backup
+        self.backup.set_paused(is_simulated);
+
+        // Once the simulation ends, bring the backup up to date
+        if !is_simulated {
+            self.backup
+                .backup_current_scene(&self.config.get_current_scene().get_id());
+            for status_id in self.config.get_status_ids() {
+                if let Some(state) = self.config.get_state(&status_id) {
+                    self.backup.backup_status(&status_id, &state);
+                }
+            }
+            self.backup
+                .backup_events(self.queue.get_events(), &self.clock);
+        }
+    }
+
     /// A method to repackage a list of coming events as upcoming events.
     ///
     /// # Notes
@@ -195,7 +767,7 @@ impl EventHandler {
     ///
     pub fn repackage_events(&self, mut events: Vec<ComingEvent>) -> Vec<UpcomingEvent> {
         // Backup the coming events
-        self.backup.backup_events(events.clone());
+        self.backup.backup_events(events.clone(), &self.clock);
 
         // Repackage the list as upcoming events
         let mut upcoming_events = Vec::new();
@@ -228,6 +800,54 @@ impl EventHandler {
         self.config.try_event(event_id, false) // do not check the scene
     }
 
+    /// A method to return the events of interest for the game report of
+    /// the current configuration.
+    ///
+    pub fn report_settings(&self) -> ReportSettings {
+        self.config.report_settings()
+    }
+
+    /// A method to return the log settings of the configuration, if
+    /// specified
+    ///
+    pub fn log_settings(&self) -> Option<LogSettings> {
+        self.config.log_settings()
+    }
+
+    /// A method to change the log settings of the configuration
+    ///
+    pub fn set_log_settings(&mut self, settings: LogSettings) {
+        self.config.set_log_settings(settings);
+    }
+
+    /// A method to return the address to serve the metrics, if specified
+    ///
+    pub fn metrics_address(&self) -> Option<String> {
+        self.config.metrics_address()
+    }
+
+    /// A method to return the address and token of the web console, if
+    /// specified
+    ///
+    pub fn web_console(&self) -> Option<ConsoleSettings> {
+        self.config.web_console()
+    }
+
+    /// A method to record the result of a session in the session results
+    ///
+    pub fn record_result(&mut self, result: SessionResult) {
+        self.statistics.add_result(result);
+    }
+
+    /// A method to summarize the session results, with the provided number
+    /// of leaderboard entries
+    ///
+    pub fn get_statistics(&self, count: usize) -> StatisticsSummary {
+        let config = &self.config;
+        self.statistics
+            .summary(count, |item_id| config.get_description(item_id))
+    }
+
     /// A method to return a copy of the description of the provided id.
     ///
     /// # Errors
@@ -339,18 +959,30 @@ impl EventHandler {
     /// a status in the configuration. This usually indicates that the provided
     /// id was incorrect or that the configuration file is incorrect.
     ///
+    /// This method will raise a warning if the status does not allow a
+    /// transition from its current state to the new state.
+    ///
     /// Like all EventHandler functions and methods, this method will fail
     /// gracefully by notifying of errors on the update line and leaving the
     /// current configuration unmodified.
     ///
     pub fn modify_status(&mut self, status_id: &ItemId, new_state: &ItemId) {
-        // Try to modify the underlying status
-        if let Some(new_id) = self.config.modify_status(status_id, new_state) {
-            // Backup the status change
-            self.backup.backup_status(status_id, &new_id);
+        // Try to modify the underlying status (and any derived statuses)
+        if let Some(changes) = self.config.modify_status(status_id, new_state) {
+            for change in changes {
+                // Backup the status change
+                self.backup
+                    .backup_status(&change.status_id, &change.new_state);
+
+                // Run the exit and entry events for the transition (no backup necessary)
+                for event_id in change.events {
+                    self.queue.add_event(EventDelay::new(None, event_id));
+                }
 
-            // Run the change event for the new state (no backup necessary)
-            self.queue.add_event(EventDelay::new(None, new_id));
+                // Run the change event for the new state (no backup necessary)
+                self.queue
+                    .add_event(EventDelay::new(None, change.new_state));
+            }
         }
     }
 
@@ -478,7 +1110,7 @@ impl EventHandler {
     ///
     pub fn save_config(&self, config_path: PathBuf) {
         // Attempt to open the new configuration file
-        let config_file = match File::create(config_path) {
+        let config_file = match File::create(&config_path) {
             Ok(file) => file,
             Err(_) => {
                 update!(err &self.general_update => "Unable To Open Configuration File.");
@@ -486,8 +1118,155 @@ impl EventHandler {
             }
         };
 
-        // Save the configuration to the provided file
-        self.config.to_config(&config_file);
+        // Save the configuration to the provided file (and any included files beside it)
+        self.config
+            .to_config(&config_file, config_folder(&config_path));
+    }
+
+    /// A method to export the event flow graph of the current configuration
+    /// to the provided file.
+    ///
+    /// # Errors
+    ///
+    /// This method will fail silently if it was unable to create the desired
+    /// file. This usually indicates that there is an underlying file system
+    /// error.
+    ///
+    /// Like all EventHandler functions and methods, this method will fail
+    /// gracefully by notifying the user.
+    ///
+    pub fn save_graph(&self, graph_path: PathBuf) {
+        // Attempt to open the new graph file
+        let graph_file = match File::create(graph_path) {
+            Ok(file) => file,
+            Err(_) => {
+                update!(err &self.general_update => "Unable To Open Event Graph File.");
+                return;
+            }
+        };
+
+        // Save the event graph to the provided file
+        self.config.to_graph(&graph_file);
+    }
+
+    /// A function to perform a task on a configuration file without loading
+    /// the configuration: to check it, to export its event graph, to rewrite
+    /// it in the layout of the current version, or to compose the game report
+    /// of a session in a game log.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to open or parse
+    /// the configuration file or to create the output file, and will raise a
+    /// warning if there is an internal consistency problem with the
+    /// configuration.
+    ///
+    /// Like all EventHandler functions and methods, this function will fail
+    /// gracefully by notifying of errors on the update line.
+    ///
+    pub fn process_config(
+        general_update: &GeneralUpdate,
+        config_path: PathBuf,
+        task: ConfigTask,
+    ) -> Result<(), Error> {
+        // Attempt to open the configuration file
+        let config_file = match File::open(&config_path) {
+            Ok(file) => file,
+            Err(_) => {
+                update!(err general_update => "Unable To Open Configuration File.");
+                return Err(format_err!("Unable to open configuration file."));
+            }
+        };
+
+        // Complete the requested task (noting whether to keep a copy of each replaced file)
+        let (outputs, is_migration) = match task {
+            // Check the configuration
+            ConfigTask::Validate => {
+                return Config::check(general_update, &config_file, config_folder(&config_path))
+            }
+
+            // Compose the event graph
+            ConfigTask::Graph(graph_path) => (
+                vec![(
+                    graph_path,
+                    Config::compose_graph(
+                        general_update,
+                        &config_file,
+                        config_folder(&config_path),
+                    )?,
+                )],
+                false,
+            ),
+
+            // Compose the game report (as CSV and HTML) from the game log and its rotated copies
+            ConfigTask::Report {
+                log_path,
+                session,
+                report_path,
+            } => {
+                let report = Config::compose_report(
+                    general_update,
+                    &config_file,
+                    config_folder(&config_path),
+                    &read_rotated(&log_path),
+                    session.as_deref(),
+                )?;
+                (
+                    vec![
+                        (report_path.with_extension("csv"), report.to_csv()),
+                        (report_path.with_extension("html"), report.to_html()),
+                    ],
+                    false,
+                )
+            }
+
+            // Compose the configuration and included files at the current version
+            ConfigTask::Migrate(new_path) => {
+                let (contents, includes) = Config::compose_migration(
+                    general_update,
+                    &config_file,
+                    config_folder(&config_path),
+                )?;
+
+                // Write the included files beside the new configuration (so their relative paths still work)
+                let new_folder = config_folder(&new_path).to_path_buf();
+                let mut outputs = vec![(new_path, contents)];
+                for (path, include_contents) in includes {
+                    outputs.push((new_folder.join(path), include_contents));
+                }
+                (outputs, true)
+            }
+        };
+
+        // Write each of the output files
+        for (output_path, contents) in outputs {
+            // Keep a copy of any file that the migration will replace
+            if is_migration && output_path.exists() {
+                let mut backup_path = output_path.clone().into_os_string();
+                backup_path.push(".bak");
+                if let Err(error) = fs::copy(&output_path, &backup_path) {
+                    update!(err general_update => "Unable To Keep A Copy Of {}: {}", output_path.display(), error);
+                    return Err(format_err!(
+                        "Unable to keep a copy of {}: {}",
+                        output_path.display(),
+                        error
+                    ));
+                }
+            }
+
+            // Try to write the result to the output file (creating the folder, if necessary)
+            if let Some(folder) = output_path.parent() {
+                fs::DirBuilder::new()
+                    .recursive(true)
+                    .create(folder)
+                    .unwrap_or(());
+            }
+            if let Err(error) = write_atomic(&output_path, &contents) {
+                update!(err general_update => "Unable To Write To Output File: {}", error);
+                return Err(format_err!("Unable to write to output file: {}", error));
+            }
+        }
+        Ok(())
     }
 
     /// A method to process a new event in the event handler. If the event was
@@ -503,6 +1282,12 @@ impl EventHandler {
     /// gracefully by notifying of errors on the update line.
     ///
     pub fn process_event(&mut self, event_id: &ItemId, checkscene: bool, broadcast: bool) -> bool {
+        // Do not process events while on standby
+        if self.is_standby() {
+            update!(warn &self.general_update => "Event Not Processed. This Instance Is On Standby: {}", event_id);
+            return false;
+        }
+
         // Try to retrieve the event details and unpack the event
         let event_detail = match self.config.try_event(event_id, checkscene) {
             // Process a valid event
@@ -515,49 +1300,32 @@ impl EventHandler {
         // Compose the item into an item pair
         let pair = ItemPair::from_item(event_id.clone(), self.get_description(&event_id));
 
-        // Unpack and process each action of the event
-        let mut was_broadcast = false;
+        // Unpack and process each action of the event, collecting any data
+        let mut data = Vec::new();
+        let mut is_solicited = false;
         for action in event_detail {
             // Switch based on the result of unpacking the action
             match self.unpack_action(action) {
                 // No additional action required
                 UnpackResult::None => (),
 
-                // Send data to the system
-                UnpackResult::Data(mut data) => {
-                    // Save that the event has been broadcast
-                    was_broadcast = true;
-
-                    // If we should broadcast the event
-                    if broadcast {
-                        // Broadcast the event and each piece of data
-                        for number in data.drain(..) {
-                            update!(broadcast &self.general_update => pair.clone(), Some(number));
-                        }
-
-                    // Otherwise just update the system about the event
-                    } else {
-                        update!(now &self.general_update => pair.clone());
-                    }
-                }
+                // Collect the data to send to the system
+                UnpackResult::Data(mut new_data) => data.append(&mut new_data),
 
-                // Solicit a string from the user
+                // Solicit a string from the user (the string is broadcast later)
                 UnpackResult::String => {
-                    // Save that the event was broadcast
-                    was_broadcast = true;
-
-                    // Solicit a string
+                    is_solicited = true;
                     self.general_update.send_get_user_string(pair.clone());
                 }
             }
         }
 
-        // Broadcast the event (if it hasn't been broadcast yet)
-        if !was_broadcast {
+        // Broadcast the event once with all the data (unless only a string was solicited)
+        if !data.is_empty() || !is_solicited {
             // If we should broadcast the event
             if broadcast {
                 // Send it to the system
-                update!(broadcast &self.general_update => pair.clone(), None);
+                update!(broadcast &self.general_update => pair.clone(), data);
 
             // Otherwise just update the system about the event
             } else {
@@ -565,6 +1333,21 @@ impl EventHandler {
             }
         }
 
+        // Pass the event to the sequences and queue any success or failure events
+        let now = self.clock.now();
+        for action in self.config.process_sequences(event_id, now) {
+            match action {
+                // Trigger the success or failure event
+                SequenceAction::Trigger { event } => {
+                    self.queue.add_event(EventDelay::new(None, event));
+                }
+
+                // Queue or cancel the timeout of the sequence
+                SequenceAction::StartTimeout { timeout } => self.queue.add_coming_event(timeout),
+                SequenceAction::CancelTimeout { timeout } => self.queue.cancel_event(timeout),
+            }
+        }
+
         // Indicate success
         true
     }
@@ -655,6 +1438,22 @@ impl EventHandler {
                         // Error that this is not yet implemented
                         update!(err &self.general_update => "Saving a User String is not yet implemented.");
                     }
+
+                    // Collect the fastest completion times
+                    DataType::BestTimes { count } => {
+                        // Convert each time to minutes and seconds
+                        let times: Vec<String> = self
+                            .statistics
+                            .best_times(count as usize)
+                            .iter()
+                            .map(|time| {
+                                format!("{}:{:02}", time.as_secs() / 60, time.as_secs() % 60)
+                            })
+                            .collect();
+
+                        // Save the times to the game log
+                        update!(save &self.general_update => format!("Best Times {}", times.join(", ")));
+                    }
                 }
             }
 
@@ -735,9 +1534,24 @@ impl EventHandler {
 
                     // Solicit a string from the user
                     DataType::UserString => return UnpackResult::String,
+
+                    // Collect the fastest completion times
+                    DataType::BestTimes { count } => {
+                        // Send the number of times, followed by each time in seconds
+                        let times = self.statistics.best_times(count as usize);
+                        let mut data = vec![times.len() as u32];
+                        data.extend(times.iter().map(|time| time.as_secs() as u32));
+                        return UnpackResult::Data(data);
+                    }
                 }
             }
 
+            // If there is a report to save, pass the request to the system interface
+            SaveReport => {
+                self.general_update
+                    .send_system(SystemUpdate::SaveReport { filepath: None });
+            }
+
             // If there is a grouped event, trigger the corresponding event
             GroupedEvent {
                 status_id,
@@ -753,7 +1567,9 @@ impl EventHandler {
                             .add_event(EventDelay::new(None, event_id.clone())),
 
                         // Otherwise warn the system the event was not found
-                        None => update!(warn &self.general_update => "Unable To Find State In Grouped Event: {}", state),
+                        None => {
+                            update!(warn &self.general_update => "Unable To Find State In Grouped Event: {}", state)
+                        }
                     }
                 }
             }
@@ -764,6 +1580,28 @@ impl EventHandler {
     }
 }
 
+/// A helper function to find the folder of a configuration file, where any
+/// included files are found
+///
+fn config_folder(config_path: &Path) -> &Path {
+    config_path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// A helper function to atomically replace the contents of a file, so that
+/// a failed write never leaves the file truncated
+///
+fn write_atomic(path: &Path, contents: &str) -> Result<(), Error> {
+    // Write the contents to a temporary file and flush it to the disk
+    let temporary = path.with_extension("tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+
+    // Replace the file with the temporary file
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
 /// A helper enum to return the different results of unpacking an event detail
 ///
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -784,6 +1622,80 @@ enum UnpackResult {
 mod tests {
     use super::*;
 
+    // Test reloading a configuration while keeping the live state
+    #[test]
+    fn reload_config() {
+        // Import features for testing
+        use super::super::{ClockMode, GeneralUpdateType};
+        use std::env;
+        use std::fs;
+
+        // Write a copy of the default configuration and load it
+        let folder = env::temp_dir().join(format!("minerva_reload_test_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let config_path = folder.join("config.mnv");
+        let config_string = fs::read_to_string("default.mnv").unwrap();
+        fs::write(&config_path, &config_string).unwrap();
+        let (general_update, receive) = GeneralUpdate::new();
+        let (interface_send, _interface_receive) = mpsc::channel();
+        let mut event_handler = EventHandler::new(
+            config_path.clone(),
+            general_update,
+            interface_send.clone(),
+            Clock::new_with_mode(ClockMode::Manual),
+            true,
+        )
+        .unwrap();
+
+        // Change the scene and a status and queue two events
+        let id = |id| ItemId::new(id).unwrap();
+        event_handler.config.choose_scene(id(200)).unwrap();
+        event_handler
+            .config
+            .load_backup_status(vec![(id(20), id(22))]);
+        event_handler.add_event(EventDelay::new(Some(Duration::from_secs(60)), id(6)));
+        event_handler.add_event(EventDelay::new(Some(Duration::from_secs(60)), id(3)));
+
+        // Remove a status and one of the queued events from the configuration
+        let remove = |string: &str, start: &str, end: &str| {
+            let start = string.find(start).unwrap();
+            let end = start + string[start..].find(end).unwrap();
+            format!("{}{}", &string[..start], &string[end..])
+        };
+        let config_string = remove(&config_string, "  ? id: 30\n", "# Item Lookup");
+        let config_string = remove(&config_string, "  ? id: 3\n", "  ? id: 4\n");
+        fs::write(&config_path, config_string).unwrap();
+        receive.try_iter().count();
+
+        // Reload the configuration and check the state which was kept
+        event_handler
+            .reload_config(&config_path, interface_send)
+            .unwrap();
+        fs::remove_dir_all(&folder).unwrap_or(());
+        assert_eq!(event_handler.get_current_scene().get_id(), id(200));
+        assert_eq!(event_handler.config.get_state(&id(20)), Some(id(22)));
+        assert_eq!(event_handler.config.get_status_ids(), vec![id(20)]);
+        let queued: Vec<ItemId> = event_handler
+            .queue
+            .get_events()
+            .iter()
+            .map(|event| event.id())
+            .collect();
+        assert_eq!(queued, vec![id(6)]);
+
+        // Check that the dropped status and event were reported
+        let dropped = receive
+            .try_iter()
+            .filter(|update| match update {
+                GeneralUpdateType::Update(EventUpdate::Warning(message, ..)) => {
+                    message.contains("No Longer Exists")
+                }
+                _ => false,
+            })
+            .count();
+        assert_eq!(dropped, 2);
+    }
+
     // FIXME Repair these tests
     // Simple test of running the queue module
     /*#[test]
@@ -21,11 +21,12 @@
 //! that events with a longer delay always arrive later than earlier events.
 
 // Import the relevant structures into the correct namespace
-use super::super::{EventUpdate, GeneralUpdate};
+use super::super::{Clock, EventUpdate, GeneralUpdate};
 use super::event::EventDelay;
 use super::item::ItemId;
 
 // Import other standard library features
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
@@ -41,11 +42,11 @@ pub struct ComingEvent {
 // Implement the Coming Event features
 impl ComingEvent {
     /// A function to return a new ComingEvent by consuming Duration and
-    /// ItemId.
+    /// ItemId. The start time of the event is the current time of the clock.
     ///
-    pub fn new(delay: Duration, event_id: ItemId) -> ComingEvent {
+    pub fn new(delay: Duration, event_id: ItemId, clock: &Clock) -> ComingEvent {
         ComingEvent {
-            start_time: Instant::now(),
+            start_time: clock.now(),
             delay,
             event_id,
         }
@@ -58,10 +59,11 @@ impl ComingEvent {
     }
 
     /// A method to calculate the amount of time remaining before the event
-    /// triggers. Returns None if the event should already have occured.
+    /// triggers, according to the provided clock. Returns None if the event
+    /// should already have occured.
     ///
-    pub fn remaining(&self) -> Option<Duration> {
-        self.delay.checked_sub(self.start_time.elapsed())
+    pub fn remaining(&self, clock: &Clock) -> Option<Duration> {
+        clock.remaining(&self.start_time, &self.delay)
     }
 
     /// A method to compare the start time and event id of two coming events.
@@ -81,6 +83,7 @@ impl ComingEvent {
 ///
 struct ComingEvents {
     list: Vec<ComingEvent>,        // a vector to hold the coming events
+    clock: Clock,                  // the clock for calculating the remaining time of each event
     general_update: GeneralUpdate, // the general update line for passing current events back to the rest of the system
 }
 
@@ -88,9 +91,10 @@ struct ComingEvents {
 impl ComingEvents {
     /// A function to create a new, empty ComingEvents structure.
     ///
-    fn new(general_update: GeneralUpdate) -> ComingEvents {
+    fn new(general_update: GeneralUpdate, clock: Clock) -> ComingEvents {
         ComingEvents {
             list: Vec::new(),
+            clock,
             general_update,
         }
     }
@@ -112,12 +116,12 @@ impl ComingEvents {
     ///
     fn load_event(&mut self, event: ComingEvent) {
         // Calculate the remaining time before the event triggers
-        if let Some(event_remaining) = event.remaining() {
+        if let Some(event_remaining) = event.remaining(&self.clock) {
             // Find the correct spot in the queue
             let mut index = 0;
             for coming in self.list.iter() {
                 // Calculate the remaining time for this particular coming event
-                if let Some(coming_remaining) = coming.remaining() {
+                if let Some(coming_remaining) = coming.remaining(&self.clock) {
                     // If event delay is larger than coming event, put new event in front
                     if event_remaining > coming_remaining {
                         break;
@@ -194,7 +198,7 @@ impl ComingEvents {
             // If the event ids match
             if coming.event_id == *event_id {
                 // Return the corresponding remaining duration
-                return coming.remaining();
+                return coming.remaining(&self.clock);
             }
         }
 
@@ -211,21 +215,25 @@ impl ComingEvents {
     /// return None.
     ///
     fn withdraw(&mut self, new_event: ComingEvent) -> Option<ComingEvent> {
-        // Look for and remove the requested event (based on the drain_filter code)
-        let mut index = 0;
-        while index != self.list.len() {
-            // If the event was found, remove it, and return the provided event
-            if self.list[index].compare_with(&new_event) {
-                // Remove the old event and update the flag
-                self.list.remove(index);
-                self.send_current();
-
-                // Return the new event
-                return Some(new_event);
-            }
+        // Prefer an identical event (two events may share an id and start time)
+        let found = match self.list.iter().position(|event| *event == new_event) {
+            Some(index) => Some(index),
+
+            // Otherwise, look for the event by id and start time
+            None => self
+                .list
+                .iter()
+                .position(|event| event.compare_with(&new_event)),
+        };
+
+        // If the event was found, remove it, and return the provided event
+        if let Some(index) = found {
+            // Remove the old event and update the flag
+            self.list.remove(index);
+            self.send_current();
 
-            // Otherwise, keep looking
-            index += 1;
+            // Return the new event
+            return Some(new_event);
         }
 
         // If the event wasn't found, return None
@@ -267,8 +275,10 @@ impl ComingEvents {
 ///
 pub struct Queue {
     queue_load: mpsc::Sender<ComingEvent>, // the queue loading line that sends additional items to the daemon
+    clock: Clock,                          // the clock for timing the events in the queue
     general_update: GeneralUpdate, // the general update line for passing current events back to the rest of the system
     coming_events: Arc<Mutex<ComingEvents>>, // the data queue to be modified by the background process and system handler process
+    loading: Arc<AtomicUsize>, // the number of events sent to the background process but not yet loaded
 }
 
 // Implement the Queue methods
@@ -277,28 +287,43 @@ impl Queue {
     ///
     /// This function returns a new queue which will send all triggered events
     /// back up the reply_line. The new implementation of the queue launches a
-    /// background thread to monitor updates.
+    /// background thread to monitor updates. All events in the queue are timed
+    /// according to the provided clock.
     ///
-    pub fn new(general_update: GeneralUpdate) -> Queue {
+    pub fn new(general_update: GeneralUpdate, clock: Clock) -> Queue {
         // Create a new channel pair to send updates to the background queue
         let (queue_load, queue_receive) = mpsc::channel();
 
         // Create the new queue data
-        let coming_events = Arc::new(Mutex::new(ComingEvents::new(general_update.clone())));
+        let coming_events = Arc::new(Mutex::new(ComingEvents::new(
+            general_update.clone(),
+            clock.clone(),
+        )));
         let coming_clone = coming_events.clone();
+        let loading = Arc::new(AtomicUsize::new(0));
+        let loading_clone = loading.clone();
 
         // Launch the background process with the queue data
         let general_clone = general_update.clone();
+        let clock_clone = clock.clone();
         thread::spawn(move || {
             // Run the queue background process indefinitely
-            Queue::run_loop(general_clone, queue_receive, coming_clone);
+            Queue::run_loop(
+                general_clone,
+                clock_clone,
+                queue_receive,
+                coming_clone,
+                loading_clone,
+            );
         });
 
         // Return the Queue
         Queue {
             queue_load,
+            clock,
             general_update,
             coming_events,
+            loading,
         }
     }
 
@@ -307,8 +332,10 @@ impl Queue {
     ///
     fn run_loop(
         general_update: GeneralUpdate,
+        clock: Clock,
         queue_receive: mpsc::Receiver<ComingEvent>,
         coming_events: Arc<Mutex<ComingEvents>>,
+        loading: Arc<AtomicUsize>,
     ) {
         // Run the background process indefinitely
         loop {
@@ -321,7 +348,7 @@ impl Queue {
                     match queue_receive.recv() {
                         // Process an upcoming event
                         Ok(event) => {
-                            coming_events.lock().unwrap().load_event(event);
+                            Queue::load_received(&coming_events, &loading, event);
                         }
 
                         // Terminate the process if there was an error
@@ -332,33 +359,28 @@ impl Queue {
                 // Otherwise, wait for this event or a new event on the line
                 Some(event) => {
                     // Look to see how much time is remaining on the newest event
-                    match event.remaining() {
+                    match event.remaining(&clock) {
                         // If there is no time remaining, launch the event
                         None => {
                             // Remove the last event from the list and send it if it matches what we expected. Otherwise, do nothing.
-                            if let Some(event_now) = coming_events.lock().unwrap().pop_if(&event) {
-                                general_update.send_event(event_now.id(), true, true);
+                            // The lock is held until the event is sent, so the queue is never seen as idle in between.
+                            let mut events = coming_events.lock().unwrap();
+                            if let Some(event_now) = events.pop_if(&event) {
+                                general_update.send_queued_event(event_now.id(), true, true);
                             }
                         }
 
                         // If there is some time remaining, wait for a message to arrive or the time to pass
                         Some(delay) => {
                             // Wait for a new message or the time to elapse
-                            match queue_receive.recv_timeout(delay) {
+                            match clock.recv_timeout(&queue_receive, delay) {
                                 // Process an upcoming event
                                 Ok(new_event) => {
-                                    coming_events.lock().unwrap().load_event(new_event);
+                                    Queue::load_received(&coming_events, &loading, new_event);
                                 }
 
-                                // Catch the timeout of the receiver
-                                Err(mpsc::RecvTimeoutError::Timeout) => {
-                                    // Remove the last event from the list and send it if it matches what we expected. Otherwise, do nothing.
-                                    if let Some(event_now) =
-                                        coming_events.lock().unwrap().pop_if(&event)
-                                    {
-                                        general_update.send_event(event_now.id(), true, true);
-                                    }
-                                }
+                                // Catch the timeout of the receiver and recheck the time remaining (the clock may not have run for the full delay)
+                                Err(mpsc::RecvTimeoutError::Timeout) => (),
 
                                 // Terminate the process if there was an error
                                 _ => break,
@@ -370,6 +392,31 @@ impl Queue {
         }
     }
 
+    /// An internal function to load an event received by the background
+    /// process. The event is no longer counted as loading once it is in the
+    /// list of coming events.
+    ///
+    fn load_received(
+        coming_events: &Arc<Mutex<ComingEvents>>,
+        loading: &AtomicUsize,
+        event: ComingEvent,
+    ) {
+        // Load the event and update the count while holding the lock
+        let mut events = coming_events.lock().unwrap();
+        events.load_event(event);
+        loading.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// An internal method to send a coming event to the background process.
+    ///
+    fn load(&self, event: ComingEvent) {
+        // Count the event before sending it, and uncount it if it was not sent
+        self.loading.fetch_add(1, Ordering::SeqCst);
+        if self.queue_load.send(event).is_err() {
+            self.loading.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
     /// A method to add a new event to the queue.
     ///
     /// This function adds the new event to the existing queue. This event may
@@ -381,15 +428,25 @@ impl Queue {
             // Load delayed events into the queue
             Some(delay) => {
                 // Create a coming event and send it to the queue
-                let coming = ComingEvent::new(delay, event.id());
-                self.queue_load.send(coming).unwrap_or(());
+                let coming = ComingEvent::new(delay, event.id(), &self.clock);
+                self.load(coming);
             }
 
             // Immediately return any events that have no delay
-            None => self.general_update.send_event(event.id(), true, true),
+            None => self
+                .general_update
+                .send_queued_event(event.id(), true, true),
         }
     }
 
+    /// A method to add a specific coming event to the queue. Unlike events
+    /// added with add_event, the caller keeps a copy of the coming event and
+    /// can later cancel exactly this event with cancel_event.
+    ///
+    pub fn add_coming_event(&self, event: ComingEvent) {
+        self.load(event);
+    }
+
     /// A method to check the remaining time until an event is triggered. If
     /// multiple events with the same id are in the queue, the remaining time
     /// until the earliest event (the one with the shortest delay) is provided.
@@ -444,7 +501,7 @@ impl Queue {
                 // Try to withdraw the existing event from the queue
                 if let Some(event) = events.withdraw(new_event) {
                     // If successful, send the new event to the queue. This also triggers the queue to notice the change.
-                    self.queue_load.send(event).unwrap_or(());
+                    self.load(event);
                 }
             }
 
@@ -477,13 +534,11 @@ impl Queue {
                     // Add time to all the events
                     for event in events.list.iter() {
                         // Load the new event into the Queue
-                        self.queue_load
-                            .send(ComingEvent {
-                                start_time: event.start_time.clone(),
-                                delay: event.delay + adjustment,
-                                event_id: event.id(),
-                            })
-                            .unwrap_or(());
+                        self.load(ComingEvent {
+                            start_time: event.start_time.clone(),
+                            delay: event.delay + adjustment,
+                            event_id: event.id(),
+                        });
                     }
 
                 // Otherwise, try to subtract time from the events
@@ -491,7 +546,7 @@ impl Queue {
                     // Try to subtract time from all the events
                     for event in events.list.iter() {
                         // Ignore events that have already happened
-                        let remaining = match event.remaining() {
+                        let remaining = match event.remaining(&self.clock) {
                             Some(time) => time,
                             None => continue,
                         };
@@ -504,13 +559,11 @@ impl Queue {
                                 // Calculate the new duration (should always succeed)
                                 if let Some(delay) = event.delay.checked_sub(adjustment) {
                                     // Load the new event into the Queue
-                                    self.queue_load
-                                        .send(ComingEvent {
-                                            start_time: event.start_time.clone(),
-                                            delay,
-                                            event_id: event.id(),
-                                        })
-                                        .unwrap_or(());
+                                    self.load(ComingEvent {
+                                        start_time: event.start_time.clone(),
+                                        delay,
+                                        event_id: event.id(),
+                                    });
                                 }
                             }
                         }
@@ -528,6 +581,20 @@ impl Queue {
         }
     }
 
+    /// A method to have the queue recalculate the time until the next event.
+    /// This should be called whenever the mode of the clock changes.
+    ///
+    /// # Note
+    ///
+    /// While unlikely, this function must wait for the background process to
+    /// release the lock on the queue. If the background process hangs, this
+    /// function may hang as well.
+    ///
+    pub fn wake(&self) {
+        // Reload every event into the queue without any adjustment
+        self.adjust_all(Duration::from_secs(0), false);
+    }
+
     /// A method to cancel a specific upcoming event.
     ///
     /// # Errors
@@ -584,6 +651,53 @@ impl Queue {
         }
     }
 
+    /// A method to return a copy of the events currently in the queue.
+    ///
+    /// # Note
+    ///
+    /// While unlikely, this function must wait for the background process to
+    /// release the lock on the queue. If the background process hangs, this
+    /// function may hang as well.
+    ///
+    pub fn get_events(&self) -> Vec<ComingEvent> {
+        // Open the coming events
+        match self.coming_events.lock() {
+            Ok(events) => events.list.clone(),
+
+            // Raise an error if the queue has failed
+            _ => {
+                update!(err &self.general_update => "Internal Failure Of The Event Queue.");
+                Vec::new()
+            }
+        }
+    }
+
+    /// A method to check whether the queue is idle: every event sent to the
+    /// background process has been loaded and no event is due to trigger.
+    /// Once the queue is idle, all of its updates have already been sent.
+    ///
+    #[cfg(test)]
+    pub fn is_idle(&self) -> bool {
+        // Open the coming events (the background process sends its updates while holding the lock)
+        match self.coming_events.lock() {
+            Ok(events) => {
+                // Check for events still loading
+                if self.loading.load(Ordering::SeqCst) != 0 {
+                    return false;
+                }
+
+                // Check for an event already due
+                match events.last() {
+                    Some(event) => event.remaining(&self.clock).is_some(),
+                    None => true,
+                }
+            }
+
+            // Consider a failed queue to be idle
+            _ => true,
+        }
+    }
+
     /// A method to clear any events in the queue.
     ///
     /// # Note
@@ -622,7 +736,7 @@ mod tests {
         let (tx, rx) = GeneralUpdate::new();
 
         // Create a new message queue
-        let queue = Queue::new(tx);
+        let queue = Queue::new(tx, Clock::new());
 
         // Load some events into the queue
         queue.add_event(EventDelay::new(
@@ -658,4 +772,57 @@ mod tests {
         // Print and check the messages received (wait at most half a second)
         test_vec!(=rx, test);
     }
+
+    // Test the queue with a manual clock
+    #[test]
+    fn manual_clock_queue() {
+        // Import libraries for testing
+        use super::super::super::ClockMode;
+        use super::super::super::GeneralUpdateType;
+
+        // Create a channel for receiving messages from the queue
+        let (tx, rx) = GeneralUpdate::new();
+
+        // Create a new message queue with a manual clock
+        let clock = Clock::new_with_mode(ClockMode::Manual);
+        let queue = Queue::new(tx, clock.clone());
+
+        // Load some events an hour into the future
+        queue.add_event(EventDelay::new(
+            Some(Duration::from_secs(600)),
+            ItemId::new(20).unwrap(),
+        ));
+        queue.add_event(EventDelay::new(
+            Some(Duration::from_secs(3600)),
+            ItemId::new(60).unwrap(),
+        ));
+
+        // Create a helper to collect the triggered events
+        let triggered = || {
+            // Give the queue time to respond
+            thread::sleep(Duration::from_millis(50));
+
+            // Collect only the processed events
+            let mut events = Vec::new();
+            while let Ok(update) = rx.try_recv() {
+                if let GeneralUpdateType::ProcessEvent { event, .. } = update {
+                    events.push(event);
+                }
+            }
+            events
+        };
+
+        // Verify that nothing triggers until the clock advances
+        assert_eq!(triggered(), Vec::new());
+        assert_eq!(
+            queue.event_remaining(&ItemId::new(20).unwrap()),
+            Some(Duration::from_secs(600))
+        );
+
+        // Advance the clock past each event in turn
+        clock.advance(Duration::from_secs(601));
+        assert_eq!(triggered(), vec![ItemId::new(20).unwrap()]);
+        clock.advance(Duration::from_secs(3000));
+        assert_eq!(triggered(), vec![ItemId::new(60).unwrap()]);
+    }
 }
@@ -0,0 +1,384 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to write the log files of the program and to keep the log
+//! folder from growing without bound.
+//!
+//! Every log file is opened in append mode, so an existing log is extended
+//! rather than replaced. When a log file grows past the size limit, it is
+//! renamed with a numbered suffix (e.g. debug_log.1.txt, with the oldest
+//! having the largest number) and a new file is started in its place.
+//!
+//! The game logs of previous launches (and their reports) are removed from
+//! the log folder once they are older than the age limit or once there are
+//! more sessions than the retention policy allows.
+
+// Import standard library features
+use std::cmp::Reverse;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Import the failure features
+use failure::Error;
+
+// Define module constants
+const DEFAULT_MAX_SIZE: u64 = 1024; // the default size limit of a log file in kB
+const DEFAULT_MAX_AGE: u64 = 30; // the default age limit of old log files in days
+const DEFAULT_MAX_FILES: usize = 20; // the default number of old log files to keep
+const SECONDS_PER_DAY: u64 = 86_400; // the number of seconds in one day
+
+/// A structure to hold the retention policy and location of the log files
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<PathBuf>, // the folder for the log files, if not the default
+    #[serde(default = "default_max_size")]
+    pub max_size: u64, // the size in kB at which a log file is rotated
+    #[serde(default = "default_max_age")]
+    pub max_age: u64, // the age in days after which old log files are removed
+    #[serde(default = "default_max_files")]
+    pub max_files: usize, // the number of old log files (or game sessions) to keep
+}
+
+// Implement key features of the log settings
+impl LogSettings {
+    /// A function to create the default log settings
+    ///
+    pub fn new() -> LogSettings {
+        LogSettings {
+            directory: None,
+            max_size: DEFAULT_MAX_SIZE,
+            max_age: DEFAULT_MAX_AGE,
+            max_files: DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+// Helper functions to provide the defaults of missing log settings
+fn default_max_size() -> u64 {
+    DEFAULT_MAX_SIZE
+}
+fn default_max_age() -> u64 {
+    DEFAULT_MAX_AGE
+}
+fn default_max_files() -> usize {
+    DEFAULT_MAX_FILES
+}
+
+/// A helper function to compose the location of the numbered copy of the
+/// provided log file (e.g. debug_log.2.txt)
+///
+pub fn rotated_path(path: &Path, number: usize) -> PathBuf {
+    // Insert the number before the extension, if there is one
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!(
+            "{}.{}.{}",
+            stem,
+            number,
+            extension.to_string_lossy()
+        )),
+        None => path.with_file_name(format!("{}.{}", stem, number)),
+    }
+}
+
+/// A helper function to read a log file after its rotated copies (oldest
+/// first). Any file which cannot be read is skipped.
+///
+pub fn read_rotated(path: &Path) -> String {
+    // Count the rotated copies
+    let mut count = 0;
+    while rotated_path(path, count + 1).exists() {
+        count += 1;
+    }
+
+    // Read the copies, oldest first, and then the file itself
+    let mut contents = String::new();
+    for number in (1..=count).rev() {
+        contents += &fs::read_to_string(rotated_path(path, number)).unwrap_or_default();
+    }
+    contents + &fs::read_to_string(path).unwrap_or_default()
+}
+
+/// A structure to append lines to a log file, rotating the file when it
+/// grows past the size limit
+///
+pub struct LogFile {
+    path: PathBuf,    // the location of the log file
+    file: File,       // the open log file
+    size: u64,        // the current size of the log file in bytes
+    max_size: u64,    // the size in bytes at which the file is rotated
+    max_files: usize, // the number of rotated copies to keep
+}
+
+// Implement key features of the log file
+impl LogFile {
+    /// A function to open the log file at the provided location for
+    /// appending, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if it is unable to open or create
+    /// the file.
+    ///
+    pub fn open(path: PathBuf, settings: &LogSettings) -> Result<LogFile, Error> {
+        // Open the file and note its current size
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        // Return the new log file
+        Ok(LogFile {
+            path,
+            file,
+            size,
+            max_size: settings.max_size * 1024,
+            max_files: settings.max_files,
+        })
+    }
+
+    /// A method to return the location of the log file
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A method to change the size limit and the number of rotated copies
+    /// of the log file. The new limits apply from the next line.
+    ///
+    pub fn set_limits(&mut self, settings: &LogSettings) {
+        self.max_size = settings.max_size * 1024;
+        self.max_files = settings.max_files;
+    }
+
+    /// A method to append the provided text to the log file, rotating the
+    /// file first if the text would make it too large.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to rotate or write to
+    /// the file.
+    ///
+    pub fn write(&mut self, text: &str) -> Result<(), Error> {
+        // Rotate the file if it would grow too large (never rotate an empty file)
+        let length = text.len() as u64;
+        if self.size > 0 && self.size + length > self.max_size {
+            self.rotate()?;
+        }
+
+        // Write the text to the file
+        self.file.write_all(text.as_bytes())?;
+        self.size += length;
+        Ok(())
+    }
+
+    /// An internal method to rename the log file and its rotated copies
+    /// (removing the oldest copy beyond the limit) and start a new file
+    ///
+    fn rotate(&mut self) -> Result<(), Error> {
+        // Remove the oldest copy, if it exists
+        let oldest = rotated_path(&self.path, self.max_files.max(1));
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+
+        // Shift each copy to the next number, oldest first
+        for number in (1..self.max_files.max(1)).rev() {
+            let copy = rotated_path(&self.path, number);
+            if copy.exists() {
+                fs::rename(&copy, rotated_path(&self.path, number + 1))?;
+            }
+        }
+
+        // Move the current file and start a new one
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// A helper function to remove the old log files in the provided folder
+/// whose names begin with the provided prefix. The files are grouped by
+/// session (e.g. game_log_X.txt, its rotated copy game_log_X.1.txt,
+/// game_log_X.jsonl and game_log_X_report.csv are one session) and a
+/// session is removed if its newest file is older
+/// than the age limit or if it is beyond the number of sessions to keep.
+/// Sessions which contain one of the protected files (i.e. the logs in use)
+/// are never removed.
+///
+/// # Errors
+///
+/// This function will raise an error if it is unable to read the folder or
+/// remove one of the files.
+///
+pub fn prune(
+    directory: &Path,
+    prefix: &str,
+    settings: &LogSettings,
+    protected: &[&Path],
+    now: SystemTime,
+) -> Result<usize, Error> {
+    // Group the matching files by session
+    let mut sessions: Vec<(String, SystemTime, Vec<PathBuf>)> = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        // Ignore anything which is not a matching file
+        let path = entry?.path();
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        if !path.is_file() || !name.starts_with(prefix) {
+            continue;
+        }
+
+        // Identify the session by the file name without the extension or report suffix
+        let mut stem = match name.rfind('.') {
+            Some(index) => &name[..index],
+            None => name.as_str(),
+        };
+
+        // Remove the number of a rotated copy (e.g. game_log_X.1)
+        if let Some(index) = stem.rfind('.') {
+            let number = &stem[index + 1..];
+            if !number.is_empty() && number.chars().all(|digit| digit.is_ascii_digit()) {
+                stem = &stem[..index];
+            }
+        }
+        let key = stem.trim_end_matches("_report").to_string();
+
+        // Add the file to its session, noting the newest modification time
+        let modified = fs::metadata(&path)?
+            .modified()
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        match sessions.iter_mut().find(|(existing, ..)| *existing == key) {
+            Some((_, newest, files)) => {
+                if modified > *newest {
+                    *newest = modified;
+                }
+                files.push(path);
+            }
+            None => sessions.push((key, modified, vec![path])),
+        }
+    }
+
+    // Set aside the sessions in use and sort the rest, newest first
+    sessions.retain(|(_, _, files)| !files.iter().any(|file| protected.contains(&file.as_path())));
+    sessions.sort_by_key(|(_, newest, _)| Reverse(*newest));
+
+    // Remove the sessions beyond the limit or older than the age limit
+    let max_age = Duration::from_secs(settings.max_age * SECONDS_PER_DAY);
+    let mut removed = 0;
+    for (index, (_, newest, files)) in sessions.iter().enumerate() {
+        let is_old = now
+            .duration_since(*newest)
+            .map(|age| age > max_age)
+            .unwrap_or(false);
+        if index >= settings.max_files || is_old {
+            for file in files.iter() {
+                fs::remove_file(file)?;
+            }
+            removed += 1;
+        }
+    }
+
+    // Return the number of sessions removed
+    Ok(removed)
+}
+
+// Tests of the log file module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the rotation of a log file and the removal of old sessions
+    #[test]
+    fn rotate_and_prune() {
+        // Create a new temporary folder
+        let folder = std::env::temp_dir().join(format!("minerva_rotate_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+
+        // Open a log file which rotates after one kB and keeps two copies
+        let settings = LogSettings {
+            directory: None,
+            max_size: 1,
+            max_age: 1,
+            max_files: 2,
+        };
+        let path = folder.join("debug_log.txt");
+        let mut log = LogFile::open(path.clone(), &settings).unwrap();
+
+        // Write four lines of 600 bytes (each after the first rotates the file)
+        for number in 0..4 {
+            log.write(&format!("{}{}\n", number, "x".repeat(598)))
+                .unwrap();
+        }
+
+        // Verify that the newest line is in the file and the oldest copy was removed
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap_or_default();
+        assert!(read(path.clone()).starts_with('3'));
+        assert!(read(rotated_path(&path, 1)).starts_with('2'));
+        assert!(read(rotated_path(&path, 2)).starts_with('1'));
+        assert!(!rotated_path(&path, 3).exists());
+
+        // Verify that reopening the file appends to it
+        drop(log);
+        let mut log = LogFile::open(path.clone(), &settings).unwrap();
+        log.write("4\n").unwrap();
+        assert!(read(path.clone()).ends_with("\n4\n"));
+
+        // Create three game log sessions (one with a report)
+        for name in [
+            "game_log_1.txt",
+            "game_log_1_report.csv",
+            "game_log_2.txt",
+            "game_log_2.jsonl",
+            "game_log_3.txt",
+            "game_log_3.1.txt",
+        ]
+        .iter()
+        {
+            fs::write(folder.join(name), "").unwrap();
+        }
+
+        // Verify that only the newest sessions are kept
+        let now = SystemTime::now();
+        let protected = folder.join("game_log_3.txt");
+        assert_eq!(
+            prune(&folder, "game_log_", &settings, &[&protected], now).unwrap(),
+            0
+        );
+        let later = now + Duration::from_secs(2 * SECONDS_PER_DAY);
+        assert_eq!(
+            prune(&folder, "game_log_", &settings, &[&protected], later).unwrap(),
+            2
+        );
+        assert!(!folder.join("game_log_1_report.csv").exists());
+        assert!(!folder.join("game_log_2.jsonl").exists());
+        assert!(protected.exists());
+        assert!(folder.join("game_log_3.1.txt").exists());
+        fs::remove_dir_all(&folder).unwrap_or(());
+    }
+}
@@ -20,17 +20,40 @@
 //! Current event updates are sent to the provided broadcast line. Error updates
 //! are logged to the provided log file. Other updates are converted to a human
 //! readable format and returned to higher-level modules.
+//!
+//! Alongside the human readable game log, every update is also recorded in a
+//! structured game log (one JSON object per line, in a file with the same
+//! name and the jsonl extension). The structured log marks the start and the
+//! end of each session so that it can be processed by other programs.
+//!
+//! The game report is composed from the structured game log, which also
+//! records the strings provided by the user. While a simulation is running,
+//! every record in the game logs is tagged as simulated (and left out of the
+//! game report).
+//!
+//! Every notification of the session is kept in a notification history which
+//! can be filtered by severity, event and text. Errors stay pinned until the
+//! operator acknowledges them, and each acknowledgement is recorded in the
+//! game log.
+//!
+//! Every log file is appended to and rotated according to the log settings,
+//! and the error log marks the start and the end of each session. The game
+//! logs of old sessions are removed from the log folder according to the
+//! retention policy of the log settings.
 
 // Import the relevant structures into the correct namespace
 use super::event_handler::event::EventUpdate;
+use super::log_file::{self, LogFile, LogSettings};
+use super::report::{GameReport, SessionRecord};
 use super::{GeneralUpdate, InterfaceUpdate, ItemPair, UpdateStatus};
 
 // Import standard library modules
 use std::fmt;
-use std::fs::File;
+use std::fs::{DirBuilder, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::SystemTime;
 
 // Import the failure features
 use failure::Error as FailureError;
@@ -38,6 +61,42 @@ use failure::Error as FailureError;
 // Import the eternal time library
 extern crate time;
 
+// Import the JSON processing library
+extern crate serde_json;
+
+// Define module constants
+const GAME_LOG_PREFIX: &str = "game_log_"; // the beginning of every default game log name
+const HISTORY_LIMIT: usize = 1000; // the maximum number of notifications kept in the history
+const SIMULATED_TAG: &str = "[Simulated] "; // the tag of game log lines written during a simulation
+
+/// An internal enum to hold the data of a record in the structured game log
+///
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RecordData {
+    Number(u32),       // numeric data, such as broadcast data
+    Numbers(Vec<u32>), // several pieces of numeric data, such as a broadcast string
+    Text(String),      // text data, such as a message or saved data
+}
+
+/// An internal structure to hold one record of the structured game log.
+/// Every field is always present (as null if unknown) so that each line has
+/// the same layout.
+///
+#[derive(Serialize)]
+struct GameRecord {
+    time: String,                // the local time of the record (RFC 3339)
+    session: String,             // the start time of the session, to identify it
+    kind: &'static str,          // the kind of record (e.g. current, status, session_start)
+    event_id: Option<u32>,       // the id of the event or status, if relevant
+    description: Option<String>, // the description of the event or status, if relevant
+    data: Option<RecordData>,    // the data, message, or new state of the record
+    cause: Option<u32>,          // the id of the event which caused an error or warning, if known
+    scene_id: Option<u32>,       // the id of the current scene, if known
+    scene: Option<String>,       // the description of the current scene, if known
+    simulated: bool,             // a flag to indicate the record was made during a simulation
+}
+
 /// An enum to contain system notifications in different types.
 ///
 /// This notification type mirrors the event update type, but is only allowed
@@ -74,6 +133,30 @@ pub use self::Notification::{Current, Error, Update, Warning};
 
 // Implement key features for the Notification type
 impl Notification {
+    /// A function to return a copy of the message inside the notification,
+    /// regardless of variant.
+    ///
+    pub fn message(&self) -> String {
+        match self {
+            // For every variant type, return a copy of the message
+            Error { message, .. }
+            | Warning { message, .. }
+            | Current { message, .. }
+            | Update { message, .. } => message.clone(),
+        }
+    }
+
+    /// A function to return the severity of the notification
+    ///
+    pub fn severity(&self) -> Severity {
+        match self {
+            Error { .. } => Severity::Error,
+            Warning { .. } => Severity::Warning,
+            Current { .. } => Severity::Event,
+            Update { .. } => Severity::Update,
+        }
+    }
+
     /// A function to return a copy of the time inside the notification,
     /// regardless of variant.
     ///
@@ -135,12 +218,87 @@ impl fmt::Display for Notification {
     }
 }
 
+/// An enum to describe the severity of a notification, to filter the
+/// notification history
+///
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Severity {
+    Error,   // an error notification
+    Warning, // a warning notification
+    Event,   // a current event notification
+    Update,  // any other notification
+}
+
+/// A structure to hold one notification in the notification history
+///
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HistoryEntry {
+    pub index: usize,               // the position of the notification in the session
+    pub notification: Notification, // the notification itself
+    pub event: Option<ItemPair>,    // the event or status related to the notification, if any
+    pub acknowledged: bool,         // a flag to indicate the operator acknowledged an error
+}
+
+/// A structure to filter the notification history. Empty text matches every
+/// notification.
+///
+#[allow(dead_code)]
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HistoryFilter {
+    pub severity: Option<Severity>, // the severity to show, or None for all
+    pub event: String,              // the id or part of the description of the related event
+    pub text: String,               // part of the message to search for
+}
+
+// Implement key features of the history filter
+impl HistoryFilter {
+    /// A method to check if the provided history entry matches the filter.
+    /// The event and text comparisons ignore case.
+    ///
+    #[allow(dead_code)]
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        // Check the severity
+        if let Some(severity) = self.severity {
+            if entry.notification.severity() != severity {
+                return false;
+            }
+        }
+
+        // Check the related event by id or description
+        let event = self.event.trim().to_lowercase();
+        if !event.is_empty() {
+            match entry.event {
+                Some(ref pair) => {
+                    if pair.id().to_string() != event
+                        && !pair.description.to_lowercase().contains(&event)
+                    {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+
+        // Check the message
+        let text = self.text.trim().to_lowercase();
+        text.is_empty() || entry.notification.message().to_lowercase().contains(&text)
+    }
+}
+
 /// A structure to handle all logging and update processing for the program.
 ///
 pub struct Logger {
-    game_log: Option<File>,                        // game log file for the program
-    error_log: Option<File>,                       // error log file for the program
+    game_log: Option<LogFile>,            // game log file for the program
+    game_record: Option<LogFile>,         // structured game log file for the program
+    session: String,                      // the start time of the current session
+    report_path: Option<PathBuf>,         // the default location of the game report, if available
+    error_log: Option<LogFile>,           // error log file for the program
+    log_folder: Option<PathBuf>,          // the folder for new game logs, if available
+    settings: LogSettings,                // the rotation and retention policy for the log files
     old_notifications: Vec<Notification>, // internal list of notifications less than 1 minute old
+    history: Vec<HistoryEntry>,           // the notifications of the session, oldest first
+    history_count: usize,                 // the number of notifications added to the history
+    is_simulated: bool,                   // a flag to tag the records made during a simulation
     general_update: GeneralUpdate,        // broadcast channel for current events
     interface_send: mpsc::Sender<InterfaceUpdate>, // an update line for passing updates to the user interface
 }
@@ -165,89 +323,165 @@ impl Logger {
         general_update: GeneralUpdate,
         interface_send: mpsc::Sender<InterfaceUpdate>,
     ) -> Result<Logger, FailureError> {
-        // Attempt to open the game log file
-        let game_log = match log_path {
-            // If a file was specified, try to load it
-            Some(mut filepath) => {
-                // Use the current time for each instance
-                let time = time::now();
-                filepath.push(
-                    format!(
-                        "game_log_{:04}-{:02}-{:02}_{:02}-{:02}.txt",
-                        time.tm_year + 1900,
-                        time.tm_mon + 1,
-                        time.tm_mday,
-                        time.tm_hour,
-                        time.tm_min
-                    )
-                    .as_str(),
-                );
-
-                // Create the new file instance
-                match File::create(filepath.to_str().unwrap_or("")) {
-                    Ok(file) => Some(file),
-                    Err(_) => return Err(format_err!("Unable to create game log file.")),
-                }
+        // Attempt to open a new game log in the log folder
+        let settings = LogSettings::new();
+        let (game_log, game_record, report_path) = match log_path {
+            // If a folder was specified, try to create the logs
+            Some(ref folder) => {
+                let (game_log, game_record) =
+                    Logger::open_game_log(&Logger::game_log_path(folder), &settings)
+                        .map_err(|_| format_err!("Unable to create game log file."))?;
+                let report_path = Logger::report_path(game_log.path());
+                (Some(game_log), Some(game_record), Some(report_path))
             }
 
-            // If a file was not specified, run without a log file
-            None => None,
+            // If a folder was not specified, run without a log file
+            None => (None, None, None),
         };
 
-        // Attempt to open the error log file
+        // Attempt to open (or create) the error log file
         let error_log = match error_path {
-            // If a file was specified, try to open it first
-            Some(filepath) => match File::open(filepath.to_str().unwrap_or("")) {
+            // If a file was specified, open it for appending
+            Some(filepath) => match LogFile::open(filepath, &settings) {
                 Ok(file) => Some(file),
-
-                // If the file does not exist
-                Err(_) => {
-                    // Try to create the filepath instead
-                    match File::create(filepath.to_str().unwrap_or("")) {
-                        Ok(file) => Some(file),
-                        Err(_) => {
-                            return Err(format_err!("Unable to create error log file."));
-                        }
-                    }
-                }
+                Err(_) => return Err(format_err!("Unable to create error log file.")),
             },
 
             // If a file was not specified, run without a log file
             None => None,
         };
 
-        // Return the new logger
-        Ok(Logger {
+        // Create the new logger and mark the start of the session
+        let mut logger = Logger {
             game_log,
+            game_record,
+            session: time::now().rfc3339().to_string(),
+            report_path,
             error_log,
+            log_folder: log_path,
+            settings,
             old_notifications: Vec::new(),
+            history: Vec::new(),
+            history_count: 0,
+            is_simulated: false,
             general_update,
             interface_send,
-        })
+        };
+        logger.record("session_start", None, None, None, &None);
+        logger.mark_error_log("Session Started");
+
+        // Return the new logger (old logs are removed once the log settings are known)
+        Ok(logger)
     }
 
     /// A method to set the game log file for the logger.
     ///
-    /// This function takes a log file name to log saved data.
+    /// This function takes a log file name to log saved data. If the file
+    /// already exists, the new session is appended to it.
     ///
     pub fn set_game_log(&mut self, log_path: PathBuf) {
-        // Attempt to open the log file
-        self.game_log = match File::create(&log_path.to_str().unwrap_or("")) {
-            Ok(file) => Some(file),
-            Err(_) => None,
+        // Close the session in the old structured log
+        self.record("session_end", None, None, None, &None);
+
+        // Attempt to open the log file and the matching structured log
+        match Logger::open_game_log(&log_path, &self.settings) {
+            Ok((game_log, game_record)) => {
+                self.game_log = Some(game_log);
+                self.game_record = Some(game_record);
+            }
+            Err(error) => {
+                self.game_log = None;
+                self.game_record = None;
+                update!(err &self.general_update => "Unable To Open Game Log: {}", error);
+            }
         }
+
+        // Start a new session
+        self.session = time::now().rfc3339().to_string();
+        self.report_path = Some(Logger::report_path(&log_path));
+        self.record("session_start", None, None, None, &None);
     }
 
     /// A method to set the error log file for the logger.
     ///
-    /// This function takes a log file name to log program-wide errors.
+    /// This function takes a log file name to log program-wide errors. If
+    /// the file already exists, the new errors are appended to it.
     ///
     pub fn set_error_log(&mut self, log_path: PathBuf) {
-        // Attempt to open the log file
-        self.error_log = match File::create(&log_path.to_str().unwrap_or("")) {
+        // Close the session in the old log
+        self.mark_error_log("Session Moved To A New Error Log");
+
+        // Attempt to open the log file and mark the start of the session
+        self.error_log = match LogFile::open(log_path, &self.settings) {
             Ok(file) => Some(file),
-            Err(_) => None,
+            Err(error) => {
+                update!(err &self.general_update => "Unable To Open Error Log: {}", error);
+                None
+            }
+        };
+        self.mark_error_log("Session Started");
+    }
+
+    /// A method to return a copy of the current log settings, with the
+    /// current log folder.
+    ///
+    pub fn settings(&self) -> LogSettings {
+        let mut settings = self.settings.clone();
+        settings.directory = settings.directory.or_else(|| self.log_folder.clone());
+        settings
+    }
+
+    /// A method to change the log settings. If a new log folder is provided,
+    /// a new game log is started in that folder and the error log moves
+    /// there (keeping its file name). Old logs are then removed according to
+    /// the new retention policy.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to create the new
+    /// log folder. Like all system interface functions and methods, this
+    /// method will fail gracefully by notifying of errors on the update line
+    /// and keeping the previous folder.
+    ///
+    pub fn set_settings(&mut self, settings: LogSettings) {
+        // Apply the new limits to the open logs
+        for log in [
+            &mut self.game_log,
+            &mut self.game_record,
+            &mut self.error_log,
+        ]
+        .iter_mut()
+        {
+            if let Some(file) = log.as_mut() {
+                file.set_limits(&settings);
+            }
+        }
+        self.settings = settings.clone();
+
+        // If a different log folder was provided
+        if let Some(folder) = settings.directory {
+            if Some(&folder) != self.log_folder.as_ref() {
+                // Make sure the new folder exists
+                if let Err(error) = DirBuilder::new().recursive(true).create(&folder) {
+                    update!(err &self.general_update => "Unable To Create Log Folder {}: {}", folder.display(), error);
+                    return;
+                }
+
+                // Move the logs to the new folder
+                self.set_game_log(Logger::game_log_path(&folder));
+                let error_name = self
+                    .error_log
+                    .as_ref()
+                    .and_then(|file| file.path().file_name().map(|name| name.to_os_string()));
+                if let Some(name) = error_name {
+                    self.set_error_log(folder.join(name));
+                }
+                self.log_folder = Some(folder);
+            }
         }
+
+        // Remove any old logs with the new policy
+        self.prune();
     }
 
     /// A method to process an update from the system interface and return a
@@ -261,23 +495,396 @@ impl Logger {
     /// from this method are the newest notifications as well as notifications
     /// from the last minute of operation.
     ///
-    pub fn update(&mut self, update: EventUpdate) -> Vec<Notification> {
-        // Unpack the new update into a notification
-        let mut notifications = vec![self.unpack_update(update)];
+    /// Each update is also recorded in the structured game log along with
+    /// the provided current scene.
+    ///
+    pub fn update(&mut self, update: EventUpdate, scene: Option<ItemPair>) -> Vec<Notification> {
+        // Record the update in the structured game log
+        self.record_update(&update, &scene);
+
+        // Note the related event and unpack the new update into a notification
+        let event = match &update {
+            EventUpdate::Broadcast(event, _)
+            | EventUpdate::Current(event)
+            | EventUpdate::Status(event, _) => Some(event.clone()),
+            EventUpdate::Error(_, event) | EventUpdate::Warning(_, event) => event.clone(),
+            _ => None,
+        };
+        let notification = self.unpack_update(update);
+
+        // Add the notification to the history
+        self.add_history(notification.clone(), event);
+
+        // Add the notification to the recent notifications and return them
+        self.old_notifications.insert(0, notification);
+        self.recent_notifications()
+    }
+
+    /// A method to return the notifications from the last minute of
+    /// operation, the most recent first
+    ///
+    pub fn recent_notifications(&mut self) -> Vec<Notification> {
+        // Keep the notifications younger than one minute
+        let now = time::now();
+        self.old_notifications
+            .retain(|old_note| (now - old_note.time()) < time::Duration::minutes(1));
+        self.old_notifications.clone()
+    }
+
+    /// A method to return the errors which have not been acknowledged, the
+    /// most recent first
+    ///
+    pub fn pinned_notifications(&self) -> Vec<HistoryEntry> {
+        self.history
+            .iter()
+            .rev()
+            .filter(|entry| entry.notification.severity() == Severity::Error && !entry.acknowledged)
+            .cloned()
+            .collect()
+    }
+
+    /// A method to return the notification history of the session, the
+    /// most recent first
+    ///
+    pub fn notification_history(&self) -> Vec<HistoryEntry> {
+        self.history.iter().rev().cloned().collect()
+    }
+
+    /// A method to acknowledge the error with the provided index (or every
+    /// error if None) and record the acknowledgement in the game log
+    ///
+    pub fn acknowledge(&mut self, index: Option<usize>) {
+        // Mark each matching error which has not been acknowledged
+        let mut acknowledged = Vec::new();
+        for entry in self.history.iter_mut() {
+            if entry.notification.severity() == Severity::Error
+                && !entry.acknowledged
+                && (index.is_none() || index == Some(entry.index))
+            {
+                entry.acknowledged = true;
+                acknowledged.push(entry.notification.message());
+            }
+        }
+
+        // Record each acknowledgement in the game log and the structured log
+        let now = time::now();
+        let tag = if self.is_simulated { SIMULATED_TAG } else { "" };
+        for message in acknowledged.drain(..) {
+            if let Some(ref mut file) = self.game_log {
+                file.write(&format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02} — {}Acknowledged Error: {}\n",
+                    now.tm_year + 1900,
+                    now.tm_mon + 1,
+                    now.tm_mday,
+                    now.tm_hour,
+                    now.tm_min,
+                    tag,
+                    &message
+                ))
+                .unwrap_or(());
+            }
+            self.record(
+                "acknowledge",
+                None,
+                Some(RecordData::Text(message)),
+                None,
+                &None,
+            );
+        }
+    }
+
+    /// A method to start or end a simulation. The start and the end are
+    /// marked in the structured game log, and every record in between is
+    /// tagged as simulated.
+    ///
+    pub fn set_simulated(&mut self, is_simulated: bool) {
+        // Ignore a repeated change
+        if self.is_simulated == is_simulated {
+            return;
+        }
+
+        // Mark the end of the simulation before clearing the flag
+        if !is_simulated {
+            self.record("simulation_end", None, None, None, &None);
+        }
+
+        // Change the flag and mark the start of the simulation
+        self.is_simulated = is_simulated;
+        if is_simulated {
+            self.record("simulation_start", None, None, None, &None);
+        }
+    }
+
+    /// A method to rebuild the record of the current session from the
+    /// structured game log (and its rotated copies), to compose the game
+    /// report. Returns None if there is no active game log.
+    ///
+    pub fn session_record(&self) -> Option<SessionRecord> {
+        let path = self.game_record.as_ref()?.path();
+        SessionRecord::from_log(&log_file::read_rotated(path), Some(&self.session))
+    }
+
+    /// A method to record a string provided by the user in response to the
+    /// prompt of the provided event in the structured game log
+    ///
+    pub fn add_string(&mut self, event: ItemPair, string: String) {
+        self.record(
+            "string",
+            Some(&event),
+            Some(RecordData::Text(string)),
+            None,
+            &None,
+        );
+    }
+
+    /// A method to save the provided game report as a CSV file and an HTML
+    /// file. If no filepath is provided, the report is saved next to the game
+    /// log. The extension of the filepath is replaced for each file.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to write either file.
+    /// Like all system interface functions and methods, this method will fail
+    /// gracefully by notifying of errors on the update line.
+    ///
+    pub fn save_report(&self, report: &GameReport, filepath: Option<PathBuf>) {
+        // Use the provided filepath or default to the game log folder
+        let filepath = match filepath.or_else(|| self.report_path.clone()) {
+            Some(filepath) => filepath,
+            None => {
+                update!(warn &self.general_update => "Report Not Saved. No Active Game Log.");
+                return;
+            }
+        };
+
+        // Try to write each version of the report
+        for (extension, contents) in [("csv", report.to_csv()), ("html", report.to_html())].iter() {
+            let path = filepath.with_extension(extension);
+            if let Err(error) =
+                File::create(&path).and_then(|mut file| file.write_all(contents.as_bytes()))
+            {
+                update!(err &self.general_update => "Unable To Write Game Report To {}: {}", path.display(), error);
+                return;
+            }
+        }
+
+        // Notify the user of the location
+        update!(update &self.general_update => "Game Report Saved To {}.", filepath.display());
+    }
+
+    /// An internal function to compose the default location of the game
+    /// report from the location of the game log.
+    ///
+    fn report_path(log_path: &Path) -> PathBuf {
+        let stem = log_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        log_path.with_file_name(format!("{}_report", stem))
+    }
+
+    /// An internal method to add a notification to the history. If the
+    /// history is full, the oldest notification which is not a pinned error
+    /// is removed.
+    ///
+    fn add_history(&mut self, notification: Notification, event: Option<ItemPair>) {
+        // Make room in the history, if necessary
+        if self.history.len() >= HISTORY_LIMIT {
+            if let Some(position) = self.history.iter().position(|entry| {
+                entry.notification.severity() != Severity::Error || entry.acknowledged
+            }) {
+                self.history.remove(position);
+            }
+        }
+
+        // Add the new entry
+        self.history.push(HistoryEntry {
+            index: self.history_count,
+            notification,
+            event,
+            acknowledged: false,
+        });
+        self.history_count += 1;
+    }
+
+    /// An internal function to compose the location of a new game log in
+    /// the provided folder, named for the current time.
+    ///
+    fn game_log_path(folder: &Path) -> PathBuf {
+        let time = time::now();
+        folder.join(format!(
+            "{}{:04}-{:02}-{:02}_{:02}-{:02}.txt",
+            GAME_LOG_PREFIX,
+            time.tm_year + 1900,
+            time.tm_mon + 1,
+            time.tm_mday,
+            time.tm_hour,
+            time.tm_min
+        ))
+    }
+
+    /// An internal function to open the game log at the provided location
+    /// and the matching structured log.
+    ///
+    fn open_game_log(
+        log_path: &Path,
+        settings: &LogSettings,
+    ) -> Result<(LogFile, LogFile), FailureError> {
+        let game_log = LogFile::open(log_path.to_path_buf(), settings)?;
+        let game_record = LogFile::open(log_path.with_extension("jsonl"), settings)?;
+        Ok((game_log, game_record))
+    }
+
+    /// An internal method to write a session marker with the current time
+    /// to the error log. Errors writing to the log are ignored.
+    ///
+    fn mark_error_log(&mut self, marker: &str) {
+        if let Some(ref mut file) = self.error_log {
+            let now = time::now();
+            file.write(&format!(
+                "==== {:04}-{:02}-{:02} {:02}:{:02} — {} ====\n",
+                now.tm_year + 1900,
+                now.tm_mon + 1,
+                now.tm_mday,
+                now.tm_hour,
+                now.tm_min,
+                marker
+            ))
+            .unwrap_or(());
+        }
+    }
+
+    /// A method to remove the old game logs (and their rotated copies) from
+    /// the log folder, according to the retention policy. This should be
+    /// called once the log settings of the configuration are known. The
+    /// rotated copies of the error log are limited when the log rotates.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise a warning if it is unable to remove the old
+    /// logs. Like all system interface functions and methods, this method
+    /// will fail gracefully by notifying of errors on the update line.
+    ///
+    pub fn prune(&mut self) {
+        // Compose the protected logs
+        let folder = match self.log_folder {
+            Some(ref folder) => folder.clone(),
+            None => return,
+        };
+        let protected: Vec<PathBuf> = [&self.game_log, &self.game_record, &self.error_log]
+            .iter()
+            .filter_map(|log| log.as_ref().map(|file| file.path().to_path_buf()))
+            .collect();
+        let protected: Vec<&Path> = protected.iter().map(|path| path.as_path()).collect();
+
+        // Remove the old game logs
+        if let Err(error) = log_file::prune(
+            &folder,
+            GAME_LOG_PREFIX,
+            &self.settings,
+            &protected,
+            SystemTime::now(),
+        ) {
+            update!(warn &self.general_update => "Unable To Remove Old Logs: {}", error);
+        }
+    }
 
-        // Iterate through the old notifications
-        for old_note in self.old_notifications.drain(..) {
-            // If the notification is younger than one minute, add it back
-            if (time::now() - old_note.time()) < time::Duration::minutes(1) {
-                notifications.push(old_note);
+    /// An internal method to convert an event update into a record of the
+    /// structured game log.
+    ///
+    fn record_update(&mut self, update: &EventUpdate, scene: &Option<ItemPair>) {
+        match update {
+            // Record the event and any data
+            &EventUpdate::Broadcast(ref event, ref data) => self.record(
+                "broadcast",
+                Some(event),
+                match data[..] {
+                    [] => None,
+                    [number] => Some(RecordData::Number(number)),
+                    _ => Some(RecordData::Numbers(data.clone())),
+                },
+                None,
+                scene,
+            ),
+            &EventUpdate::Current(ref event) => {
+                self.record("current", Some(event), None, None, scene)
             }
+
+            // Record the message and the event which caused it
+            &EventUpdate::Error(ref message, ref event) => self.record(
+                "error",
+                None,
+                Some(RecordData::Text(message.clone())),
+                event.as_ref().map(|event| event.id()),
+                scene,
+            ),
+            &EventUpdate::Warning(ref message, ref event) => self.record(
+                "warning",
+                None,
+                Some(RecordData::Text(message.clone())),
+                event.as_ref().map(|event| event.id()),
+                scene,
+            ),
+
+            // Record the saved data and other updates
+            &EventUpdate::Save(ref data) => self.record(
+                "save",
+                None,
+                Some(RecordData::Text(data.clone())),
+                None,
+                scene,
+            ),
+            &EventUpdate::Update(ref message) => self.record(
+                "update",
+                None,
+                Some(RecordData::Text(message.clone())),
+                None,
+                scene,
+            ),
+
+            // Record the status and the description of the new state
+            &EventUpdate::Status(ref status, ref state) => self.record(
+                "status",
+                Some(status),
+                Some(RecordData::Text(state.description())),
+                None,
+                scene,
+            ),
         }
+    }
 
-        // Update the old notifications list
-        self.old_notifications = notifications.clone();
+    /// An internal method to write one record to the structured game log.
+    /// Errors writing to the log are ignored.
+    ///
+    fn record(
+        &mut self,
+        kind: &'static str,
+        event: Option<&ItemPair>,
+        data: Option<RecordData>,
+        cause: Option<u32>,
+        scene: &Option<ItemPair>,
+    ) {
+        // If the structured log exists
+        if let Some(ref mut file) = self.game_record {
+            // Compose the record
+            let record = GameRecord {
+                time: time::now().rfc3339().to_string(),
+                session: self.session.clone(),
+                kind,
+                event_id: event.map(|event| event.id()),
+                description: event.map(|event| event.description()),
+                data,
+                cause,
+                scene_id: scene.as_ref().map(|scene| scene.id()),
+                scene: scene.as_ref().map(|scene| scene.description()),
+                simulated: self.is_simulated,
+            };
 
-        // Return the refreshed list of notifications
-        return notifications;
+            // Write the record as a single line
+            if let Ok(line) = serde_json::to_string(&record) {
+                file.write(&format!("{}\n", line)).unwrap_or(());
+            }
+        }
     }
 
     /// An internal method to unpack any event updates.
@@ -298,18 +905,15 @@ impl Logger {
                 // Try to write it to the file
                 if let Some(ref mut file) = self.error_log {
                     // Ignore errors writing to the file
-                    file.write_all(
-                        format!(
-                            "{:04}-{:02}-{:02} {:02}:{:02} — ERROR: {}\n",
-                            now.tm_year + 1900,
-                            now.tm_mon + 1,
-                            now.tm_mday,
-                            now.tm_hour,
-                            now.tm_min,
-                            &error
-                        )
-                        .as_bytes(),
-                    )
+                    file.write(&format!(
+                        "{:04}-{:02}-{:02} {:02}:{:02} — ERROR: {}\n",
+                        now.tm_year + 1900,
+                        now.tm_mon + 1,
+                        now.tm_mday,
+                        now.tm_hour,
+                        now.tm_min,
+                        &error
+                    ))
                     .unwrap_or(());
 
                 // Warn that there is no file
@@ -342,8 +946,14 @@ impl Logger {
 
             // Broadcast events and display them
             EventUpdate::Broadcast(id, data) => {
-                // Broadcast the event and data, if specified
-                self.general_update.send_broadcast(id.get_id(), data);
+                // Broadcast the event and each piece of data, if specified
+                if data.is_empty() {
+                    self.general_update.send_broadcast(id.get_id(), None);
+                }
+                for number in data {
+                    self.general_update
+                        .send_broadcast(id.get_id(), Some(number));
+                }
 
                 // Send a current update with the item pair
                 Current {
@@ -386,21 +996,20 @@ impl Logger {
                 // Get the current time
                 let now = time::now();
 
-                // Try to write the data to the game log
+                // Try to write the data to the game log (tagged if simulated)
+                let tag = if self.is_simulated { SIMULATED_TAG } else { "" };
                 if let Some(ref mut file) = self.game_log {
                     // Ignore errors writing to the file
-                    file.write_all(
-                        format!(
-                            "{:04}-{:02}-{:02} {:02}:{:02} — {}\n",
-                            now.tm_year + 1900,
-                            now.tm_mon + 1,
-                            now.tm_mday,
-                            now.tm_hour,
-                            now.tm_min,
-                            &data
-                        )
-                        .as_bytes(),
-                    )
+                    file.write(&format!(
+                        "{:04}-{:02}-{:02} {:02}:{:02} — {}{}\n",
+                        now.tm_year + 1900,
+                        now.tm_mon + 1,
+                        now.tm_mday,
+                        now.tm_hour,
+                        now.tm_min,
+                        tag,
+                        &data
+                    ))
                     .unwrap_or(());
 
                 // Warn that there is no file
@@ -422,11 +1031,169 @@ impl Logger {
     }
 }
 
+// Implement drop for the logger
+impl Drop for Logger {
+    /// This method marks the end of the session in the structured game log
+    /// and the error log.
+    ///
+    fn drop(&mut self) {
+        self.record("session_end", None, None, None, &None);
+        self.mark_error_log("Session Ended");
+    }
+}
+
 // Tests of the logging module
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Test the structured game log
+    #[test]
+    fn structured_log() {
+        // Import libraries for testing
+        use super::super::{GeneralUpdate, Hidden};
+        use std::fs;
+
+        // Create a logger in a new temporary folder
+        let folder = std::env::temp_dir().join(format!("minerva_log_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let (general_update, _general_receive) = GeneralUpdate::new();
+        let (interface_send, _interface_receive) = mpsc::channel();
+        let mut logger =
+            Logger::new(Some(folder.clone()), None, general_update, interface_send).unwrap();
+
+        // Log an event during a simulation (which is left out of the session record)
+        let event = ItemPair::new(10, "Test Event", Hidden).unwrap();
+        let scene = ItemPair::new(100, "Test Scene", Hidden).unwrap();
+        logger.set_simulated(true);
+        logger.update(EventUpdate::Current(event.clone()), None);
+        logger.set_simulated(false);
+        assert!(logger.session_record().unwrap().is_empty());
+
+        // Log the event in a scene and then close the session
+        logger.update(EventUpdate::Current(event), Some(scene));
+        assert!(!logger.session_record().unwrap().is_empty());
+        drop(logger);
+
+        // Find and read the structured log
+        let path = fs::read_dir(&folder)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension() == Some("jsonl".as_ref()))
+            .unwrap();
+        let records: Vec<serde_json::Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        fs::remove_dir_all(&folder).unwrap_or(());
+
+        // Verify the session markers and the event record
+        assert_eq!(records.len(), 6);
+        assert_eq!(records[0]["kind"], "session_start");
+        assert_eq!(records[4]["kind"], "current");
+        assert_eq!(records[4]["event_id"], 10);
+        assert_eq!(records[4]["description"], "Test Event");
+        assert_eq!(records[4]["scene_id"], 100);
+        assert_eq!(records[4]["session"], records[0]["session"]);
+        assert_eq!(records[4]["simulated"], false);
+        assert_eq!(records[5]["kind"], "session_end");
+
+        // Verify the simulation markers and the tagged record
+        assert_eq!(records[1]["kind"], "simulation_start");
+        assert_eq!(records[1]["simulated"], true);
+        assert_eq!(records[2]["kind"], "current");
+        assert_eq!(records[2]["simulated"], true);
+        assert_eq!(records[3]["kind"], "simulation_end");
+    }
+
+    // Test the notification history, the filter and the acknowledgement
+    #[test]
+    fn notification_history() {
+        // Import libraries for testing
+        use super::super::{GeneralUpdate, Hidden};
+        use std::fs;
+
+        // Create a logger in a new temporary folder
+        let folder = std::env::temp_dir().join(format!("minerva_history_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let (general_update, _general_receive) = GeneralUpdate::new();
+        let (interface_send, _interface_receive) = mpsc::channel();
+        let mut logger = Logger::new(
+            Some(folder.clone()),
+            Some(folder.join("debug_log.txt")),
+            general_update,
+            interface_send,
+        )
+        .unwrap();
+
+        // Log two errors, a warning and an event
+        let door = ItemPair::new(10, "Open Door", Hidden).unwrap();
+        let light = ItemPair::new(20, "Lights On", Hidden).unwrap();
+        logger.update(
+            EventUpdate::Error("Door Jammed".to_string(), Some(door.clone())),
+            None,
+        );
+        logger.update(
+            EventUpdate::Error("Lost Connection".to_string(), None),
+            None,
+        );
+        logger.update(
+            EventUpdate::Warning("Slow Response".to_string(), Some(light.clone())),
+            None,
+        );
+        logger.update(EventUpdate::Current(light), None);
+
+        // Verify the filter by severity, event and text
+        let filter = |severity, event: &str, text: &str| HistoryFilter {
+            severity,
+            event: event.to_string(),
+            text: text.to_string(),
+        };
+        let count = |filter: HistoryFilter| {
+            logger
+                .notification_history()
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .count()
+        };
+        assert_eq!(count(filter(None, "", "")), 4);
+        assert_eq!(count(filter(Some(Severity::Error), "", "")), 2);
+        assert_eq!(count(filter(None, "20", "")), 2);
+        assert_eq!(count(filter(None, "door", "")), 1);
+        assert_eq!(count(filter(Some(Severity::Warning), "lights", "SLOW")), 1);
+        assert_eq!(count(filter(Some(Severity::Error), "", "slow")), 0);
+
+        // Verify that both errors are pinned until acknowledged
+        let pinned = logger.pinned_notifications();
+        assert_eq!(pinned.len(), 2);
+        assert_eq!(pinned[0].notification.message(), "Lost Connection");
+        logger.acknowledge(Some(pinned[1].index));
+        assert_eq!(logger.pinned_notifications().len(), 1);
+        logger.acknowledge(None);
+        assert!(logger.pinned_notifications().is_empty());
+        drop(logger);
+
+        // Verify that the acknowledgements were recorded in the game log
+        let path = fs::read_dir(&folder)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.extension() == Some("txt".as_ref()) && path != &folder.join("debug_log.txt")
+            })
+            .unwrap();
+        let game_log = fs::read_to_string(&path).unwrap();
+        let error_log = fs::read_to_string(folder.join("debug_log.txt")).unwrap();
+        fs::remove_dir_all(&folder).unwrap_or(());
+        assert!(game_log.contains("Acknowledged Error: Door Jammed"));
+        assert!(game_log.contains("Acknowledged Error: Lost Connection"));
+
+        // Verify the session markers of the error log
+        assert!(error_log.contains("Session Started"));
+        assert!(error_log.contains("ERROR: Door Jammed"));
+        assert!(error_log.contains("Session Ended"));
+    }
+
     // Test the logging module
     /*#[test]
     fn test_logging() {
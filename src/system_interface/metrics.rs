@@ -0,0 +1,465 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to count the activity of the program and to serve the counts
+//! to a monitoring system.
+//!
+//! The counters and gauges are shared between the threads of the program.
+//! When a metrics address is specified in the configuration, they are served
+//! over HTTP in the Prometheus text exposition format so that several
+//! instances can be watched from a single dashboard.
+
+// Import standard library features
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Import the failure features
+use failure::Error;
+
+// Define module constants
+const ACCEPT_DELAY: u64 = 100; // the delay between checks for a new connection, in ms
+const READ_TIMEOUT: u64 = 1000; // the longest wait for a request to arrive, in ms
+
+/// An enum to identify where an event to process came from
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventSource {
+    /// A variant for events triggered by the operator
+    Interface,
+
+    /// A variant for events triggered by the event queue
+    Queue,
+
+    /// A variant for events received from the underlying system
+    System,
+}
+
+// Implement key features of the event source
+impl EventSource {
+    /// A method to return the label of the event source
+    ///
+    fn label(self) -> &'static str {
+        match self {
+            EventSource::Interface => "interface",
+            EventSource::Queue => "queue",
+            EventSource::System => "system",
+        }
+    }
+}
+
+/// An internal structure to hold the current counts and gauges
+///
+#[derive(Debug)]
+struct MetricsData {
+    events: BTreeMap<&'static str, u64>, // the number of events by source
+    broadcasts: BTreeMap<String, u64>,   // the number of broadcasts by connection
+    retries: BTreeMap<String, u64>,      // the number of write retries by connection
+    failures: BTreeMap<String, u64>,     // the number of write failures by connection
+    read_errors: u64,                    // the number of read errors from the system
+    comm_errors: u64,                    // the number of communication errors from the system
+    queue_length: usize,                 // the number of events in the queue
+    scene: u32,                          // the id of the current scene
+    session_start: Instant,              // the start of the current game session
+    address: Option<String>,             // the address of the metrics endpoint, if serving
+}
+
+/// A structure to count the activity of the program, shared between all
+/// the threads which report to it
+///
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    data: Arc<Mutex<MetricsData>>, // the shared counts and gauges
+}
+
+// Implement key features of the metrics
+impl Metrics {
+    /// A function to create a new set of metrics with every count at zero
+    ///
+    pub fn new() -> Metrics {
+        // Start every source at zero so that the series always appear
+        let mut events = BTreeMap::new();
+        for source in [
+            EventSource::Interface,
+            EventSource::Queue,
+            EventSource::System,
+        ]
+        .iter()
+        {
+            events.insert(source.label(), 0);
+        }
+
+        // Return the new metrics
+        Metrics {
+            data: Arc::new(Mutex::new(MetricsData {
+                events,
+                broadcasts: BTreeMap::new(),
+                retries: BTreeMap::new(),
+                failures: BTreeMap::new(),
+                read_errors: 0,
+                comm_errors: 0,
+                queue_length: 0,
+                scene: 0,
+                session_start: Instant::now(),
+                address: None,
+            })),
+        }
+    }
+
+    /// A method to count an event which was processed successfully
+    ///
+    pub fn count_event(&self, source: EventSource) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.events.entry(source.label()).or_insert(0) += 1;
+        }
+    }
+
+    /// A method to count an event broadcast on the provided connection
+    ///
+    pub fn count_broadcast(&self, connection: &str) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.broadcasts.entry(connection.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// A method to count a write which was retried on the provided connection
+    ///
+    pub fn count_retry(&self, connection: &str) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.retries.entry(connection.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// A method to count a write which failed on the provided connection
+    ///
+    pub fn count_failure(&self, connection: &str) {
+        if let Ok(mut data) = self.data.lock() {
+            *data.failures.entry(connection.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// A method to count a read error from the underlying system
+    ///
+    pub fn count_read_error(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.read_errors += 1;
+        }
+    }
+
+    /// A method to count a communication error from the underlying system
+    ///
+    pub fn count_comm_error(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.comm_errors += 1;
+        }
+    }
+
+    /// A method to set the number of events in the queue
+    ///
+    pub fn set_queue_length(&self, length: usize) {
+        if let Ok(mut data) = self.data.lock() {
+            data.queue_length = length;
+        }
+    }
+
+    /// A method to set the id of the current scene
+    ///
+    pub fn set_scene(&self, scene: u32) {
+        if let Ok(mut data) = self.data.lock() {
+            data.scene = scene;
+        }
+    }
+
+    /// A method to mark the start of a new game session, resetting the
+    /// elapsed time
+    ///
+    pub fn start_session(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.session_start = Instant::now();
+        }
+    }
+
+    /// A method to compose the current metrics in the Prometheus text
+    /// exposition format
+    ///
+    pub fn render(&self) -> String {
+        // Lock the data (a poisoned lock still holds valid counts)
+        let data = match self.data.lock() {
+            Ok(data) => data,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        // Compose the counters with labels
+        let mut text = String::new();
+        let events: Vec<(String, u64)> = data
+            .events
+            .iter()
+            .map(|(source, count)| (source.to_string(), *count))
+            .collect();
+        add_family(
+            &mut text,
+            "minerva_events_total",
+            "counter",
+            "Events processed successfully, by source.",
+            "source",
+            &events,
+        );
+        for (name, help, counts) in [
+            (
+                "minerva_broadcasts_total",
+                "Events broadcast to the underlying system, by connection.",
+                &data.broadcasts,
+            ),
+            (
+                "minerva_write_retries_total",
+                "Writes to the underlying system which were retried, by connection.",
+                &data.retries,
+            ),
+            (
+                "minerva_write_failures_total",
+                "Writes to the underlying system which failed, by connection.",
+                &data.failures,
+            ),
+        ]
+        .iter()
+        {
+            let counts: Vec<(String, u64)> = counts
+                .iter()
+                .map(|(connection, count)| (connection.clone(), *count))
+                .collect();
+            add_family(&mut text, name, "counter", help, "connection", &counts);
+        }
+
+        // Compose the counters and gauges without labels
+        let elapsed = data.session_start.elapsed().as_secs();
+        for (name, kind, help, value) in [
+            (
+                "minerva_read_errors_total",
+                "counter",
+                "Read errors reported by the underlying system.",
+                data.read_errors,
+            ),
+            (
+                "minerva_comm_errors_total",
+                "counter",
+                "Communication errors reported by the underlying system.",
+                data.comm_errors,
+            ),
+            (
+                "minerva_queue_length",
+                "gauge",
+                "Events waiting in the event queue.",
+                data.queue_length as u64,
+            ),
+            (
+                "minerva_current_scene",
+                "gauge",
+                "The id of the current scene.",
+                u64::from(data.scene),
+            ),
+            (
+                "minerva_elapsed_seconds",
+                "gauge",
+                "Seconds since the start of the current game session.",
+                elapsed,
+            ),
+        ]
+        .iter()
+        {
+            text.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n{} {}\n",
+                name, help, name, kind, name, value
+            ));
+        }
+
+        // Return the completed text
+        text
+    }
+
+    /// A method to serve the metrics at the provided address (e.g.
+    /// 127.0.0.1:9185). If the metrics are already served at a different
+    /// address, that endpoint is closed.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to listen at the
+    /// provided address.
+    ///
+    pub fn listen(&self, address: &str) -> Result<(), Error> {
+        // Do nothing if the metrics are already served at this address
+        if self.address().as_deref() == Some(address) {
+            return Ok(());
+        }
+
+        // Open the new endpoint (closing the old one)
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        if let Ok(mut data) = self.data.lock() {
+            data.address = Some(address.to_string());
+        }
+
+        // Answer requests on a separate thread until the address changes
+        let metrics = self.clone();
+        let address = address.to_string();
+        thread::spawn(move || loop {
+            // Stop if the metrics have moved or are no longer served
+            if metrics.address().as_ref() != Some(&address) {
+                break;
+            }
+
+            // Answer any new connection
+            match listener.accept() {
+                Ok((stream, _)) => metrics.respond(stream).unwrap_or(()),
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(ACCEPT_DELAY));
+                }
+                Err(_) => break,
+            }
+        });
+        Ok(())
+    }
+
+    /// A method to stop serving the metrics, if they are served
+    ///
+    pub fn stop(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.address = None;
+        }
+    }
+
+    /// An internal method to return the address of the metrics endpoint
+    ///
+    fn address(&self) -> Option<String> {
+        self.data.lock().ok().and_then(|data| data.address.clone())
+    }
+
+    /// An internal method to answer one HTTP request with the current metrics
+    ///
+    fn respond(&self, mut stream: TcpStream) -> Result<(), Error> {
+        // Read the request line (the rest of the request is ignored)
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_millis(READ_TIMEOUT)))?;
+        let mut buffer = [0; 1024];
+        let length = stream.read(&mut buffer)?;
+        let request = String::from_utf8_lossy(&buffer[..length]);
+        let path = request.split_whitespace().nth(1).unwrap_or("");
+
+        // Reply with the metrics or indicate that the path was not found
+        let (status, body) = match path {
+            "/" | "/metrics" => ("200 OK", self.render()),
+            _ => ("404 Not Found", String::from("Not Found\n")),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )?;
+        Ok(())
+    }
+}
+
+/// A helper function to add a family of labelled values to the text
+///
+fn add_family(
+    text: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    label: &str,
+    values: &[(String, u64)],
+) {
+    text.push_str(&format!(
+        "# HELP {} {}\n# TYPE {} {}\n",
+        name, help, name, kind
+    ));
+    for (value_label, value) in values.iter() {
+        text.push_str(&format!(
+            "{}{{{}=\"{}\"}} {}\n",
+            name,
+            label,
+            escape(value_label),
+            value
+        ));
+    }
+}
+
+/// A helper function to escape a label value for the exposition format
+///
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Tests of the metrics module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test the counts and the served metrics
+    #[test]
+    fn count_and_serve() {
+        // Count some activity
+        let metrics = Metrics::new();
+        metrics.count_event(EventSource::Queue);
+        metrics.count_event(EventSource::Queue);
+        metrics.count_event(EventSource::System);
+        metrics.count_broadcast("serial \"/dev/ttyACM0\"");
+        metrics.count_retry("serial \"/dev/ttyACM0\"");
+        metrics.count_comm_error();
+        metrics.set_queue_length(4);
+        metrics.set_scene(100);
+
+        // Verify the rendered text
+        let text = metrics.render();
+        assert!(text.contains("# TYPE minerva_events_total counter\n"));
+        assert!(text.contains("minerva_events_total{source=\"interface\"} 0\n"));
+        assert!(text.contains("minerva_events_total{source=\"queue\"} 2\n"));
+        assert!(text
+            .contains("minerva_broadcasts_total{connection=\"serial \\\"/dev/ttyACM0\\\"\"} 1\n"));
+        assert!(text.contains("minerva_write_retries_total{connection="));
+        assert!(!text.contains("minerva_write_failures_total{"));
+        assert!(text.contains("minerva_read_errors_total 0\n"));
+        assert!(text.contains("minerva_comm_errors_total 1\n"));
+        assert!(text.contains("# TYPE minerva_queue_length gauge\nminerva_queue_length 4\n"));
+        assert!(text.contains("minerva_current_scene 100\n"));
+        assert!(text.contains("minerva_elapsed_seconds 0\n"));
+
+        // Serve the metrics on a free port and request them
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        metrics.listen(&address).unwrap();
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("minerva_queue_length 4\n"));
+        metrics.stop();
+    }
+}
@@ -20,46 +20,72 @@
 //! to the application window.
 
 // Reexport the key structures and types
-pub use self::event_handler::event::{
-    DataType, EventAction, EventDelay, EventDetail, EventUpdate, UpcomingEvent,
-};
+pub use self::clock::{Clock, ClockMode};
+pub use self::event_handler::event::{EventDelay, EventDetail, EventUpdate, UpcomingEvent};
 pub use self::event_handler::item::{
-    DisplayControl, DisplayDebug, DisplayType, DisplayWith, Hidden, ItemDescription, ItemId,
-    ItemPair, LabelControl, LabelHidden,
+    DisplayControl, DisplayDebug, DisplayWith, Hidden, ItemDescription, ItemId, ItemPair,
+    LabelControl,
 };
-pub use self::event_handler::{FullStatus, KeyMap, StatusDescription};
-pub use self::logging::{Current, Error, Logger, Notification, Update, Warning};
+pub use self::event_handler::{compose_schema, BackupSummary, FullStatus, KeyMap, ResumeMode};
+pub use self::log_file::LogSettings;
+pub use self::logging::{HistoryEntry, Logger, Notification, Severity};
+pub use self::report::ReportSettings;
+pub use self::statistics::StatisticsSummary;
+
+// Reexport the structures used only by the user interface window
+#[allow(unused_imports)]
+pub use self::event_handler::event::{DataType, EventAction};
+#[allow(unused_imports)]
+pub use self::event_handler::item::{DisplayType, LabelHidden};
+#[allow(unused_imports)]
+pub use self::event_handler::StatusDescription;
+#[allow(unused_imports)]
+pub use self::logging::{Current, Error, HistoryFilter, Update, Warning};
 
 // Define private submodules
 #[macro_use]
 mod test;
-mod logging;
+mod clock;
+mod config_watcher;
 #[macro_use]
 mod event_handler;
+mod log_file;
+mod logging;
+mod metrics;
+mod report;
+#[cfg(test)]
+mod scenario;
+mod statistics;
 mod system_connection;
+mod web_console;
 
 // Import the relevant structures into the correct namespace
+use self::config_watcher::ConfigWatcher;
 use self::event_handler::{ComingEvent, EventHandler};
+use self::metrics::{EventSource, Metrics};
 use self::system_connection::SystemConnection;
+use self::web_console::{ConsoleSettings, WebConsole};
 
 // Import standard library features
 use std::env;
 use std::fs::DirBuilder;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::thread;
 use std::time::{Duration, Instant};
 
 // Import the failure features
 use failure::Error as FailureError;
 
-// Import GTK library
-extern crate gtk;
+// Import the eternal time library
+extern crate time;
 
 // Define module constants
 const POLLING_RATE: u64 = 1; // the polling rate for the system in ms
-const DEFAULT_FILE: &str = "default.mnv"; // the default configuration filename
+pub const DEFAULT_FILE: &str = "default.mnv"; // the default configuration filename
 const LOG_FOLDER: &str = "log/"; // the default log folder
 const ERROR_LOG: &str = "debug_log.txt"; // the default logging filename
+const LEADERBOARD_LENGTH: usize = 10; // the number of entries in the leaderboard
 
 /// A structure to contain the system interface and handle all updates to the
 /// to the interface.
@@ -71,40 +97,68 @@ const ERROR_LOG: &str = "debug_log.txt"; // the default logging filename
 ///
 pub struct SystemInterface {
     event_handler: Option<EventHandler>, // the event handler instance for the program, if it exists
+    config_path: Option<PathBuf>,        // the location of the current configuration, if it exists
+    watcher: Option<ConfigWatcher>,      // the watcher of the configuration files, if watching
     logger: Logger,                      // the logging instance for the program
     system_connection: SystemConnection, // the system connection instance for the program
+    web_console: WebConsole,             // the browser-based operator console
     interface_send: mpsc::Sender<InterfaceUpdate>, // a sending line to pass interface updates to the main program
     general_receive: mpsc::Receiver<GeneralUpdateType>, // a receiving line for all system updates
     general_update: GeneralUpdate, // a sending structure to pass new general updates
+    clock: Clock,                  // the clock for timing all events
+    captured_broadcasts: Vec<(ItemId, Option<u32>)>, // the broadcasts captured during a simulation
+    is_recorded: bool,             // a flag to indicate the result of the current game was recorded
     is_debug_mode: bool,           // a flag to indicate debug mode
+    is_read_only: bool,            // a flag to refuse any changes to the configuration
+    is_watching: bool,             // a flag to reload the configuration when the files change
 }
 
 // Implement key SystemInterface functionality
 impl SystemInterface {
     /// A function to create a new, blank instance of the system interface.
+    /// All events are timed according to the provided clock. The startup
+    /// options choose the configuration and the log folder (otherwise
+    /// default.mnv and log/ in the current directory) and whether the
+    /// configuration may be changed.
     ///
     pub fn new(
         interface_send: mpsc::Sender<InterfaceUpdate>,
+        clock: Clock,
+        options: StartupOptions,
     ) -> Result<(SystemInterface, SystemSend), FailureError> {
         // Create the new general update structure and receive channel
         let (general_update, general_receive) = GeneralUpdate::new();
 
-        // Try to load the default logging file
-        let (log_folder, error_log) = match env::current_dir() {
-            // If the path loads
-            Ok(mut path) => {
-                // Create the log folder path
-                path.push(LOG_FOLDER); // append the log folder
+        // Create the web console and pass every interface update through it
+        let web_console = WebConsole::new(SystemSend::from_general(&general_update), clock.clone());
+        let (relay_send, relay_receive) = mpsc::channel();
+        let relay_console = web_console.clone();
+        thread::spawn(move || {
+            relay_console.relay(relay_receive, interface_send);
+        });
+        let interface_send = relay_send;
 
-                // Make sure the log folder exists
-                let builder = DirBuilder::new();
-                builder.create(path.clone()).unwrap_or(()); // ignore if it already exits
+        // Try to load the default logging file (in the log folder, if specified)
+        let log_folder = match options.log_folder {
+            Some(path) => Ok(path),
+            None => env::current_dir().map(|path| path.join(LOG_FOLDER)),
+        };
+        let (log_folder, error_log) = match log_folder {
+            // If the path loads, make sure the log folder (and any parent folders) exists
+            Ok(path) => match DirBuilder::new().recursive(true).create(&path) {
+                Ok(()) => {
+                    // Create the error log path
+                    let mut error_path = path.clone();
+                    error_path.push(ERROR_LOG); // append the dafault error log filename
+                    (Some(path), Some(error_path))
+                }
 
-                // Create the error log path
-                let mut error_path = path.clone();
-                error_path.push(ERROR_LOG); // append the dafault error log filename
-                (Some(path), Some(error_path))
-            }
+                // Otherwise, continue without the log files
+                Err(error) => {
+                    update!(err &general_update => "Unable To Create Log Folder {}: {}", path.display(), error);
+                    (None, None)
+                }
+            },
             _ => (None, None),
         };
 
@@ -125,16 +179,28 @@ impl SystemInterface {
         // Create the new system interface instance
         let mut sys_interface = SystemInterface {
             event_handler: None,
+            config_path: None,
+            watcher: None,
             logger,
             system_connection,
+            web_console,
             interface_send,
             general_receive,
             general_update: general_update,
+            clock,
+            captured_broadcasts: Vec::new(),
+            is_recorded: false,
             is_debug_mode: false,
+            is_read_only: options.is_read_only,
+            is_watching: options.is_watching,
         };
 
-        // Try to load a default configuration, if it exists
-        if let Ok(mut path) = env::current_dir() {
+        // Try to load the provided configuration
+        if let Some(path) = options.config_path {
+            sys_interface.load_config(path, true);
+
+        // Otherwise, try to load a default configuration, if it exists
+        } else if let Ok(mut path) = env::current_dir() {
             path.push(DEFAULT_FILE); // append the default filename
             sys_interface.load_config(path, false);
         }
@@ -151,11 +217,21 @@ impl SystemInterface {
         match self.general_receive.recv() {
             // Broadcast the event via the system connection
             Ok(GeneralUpdateType::BroadcastEvent(event_id, data)) => {
-                self.system_connection.broadcast(event_id, data);
+                // Capture the broadcast instead of sending it during a simulation
+                if self.clock.is_simulated() {
+                    self.captured_broadcasts.push((event_id, data));
+
+                // Otherwise, send it normally
+                } else {
+                    self.system_connection.broadcast(event_id, data);
+                }
             }
 
             // Update the timeline with the new list of coming events
             Ok(GeneralUpdateType::ComingEvents(events)) => {
+                // Note the length of the queue
+                self.general_update.metrics.set_queue_length(events.len());
+
                 // If the event handler exists
                 if let Some(ref mut handler) = self.event_handler {
                     // Repackage the coming events into upcoming events
@@ -180,6 +256,16 @@ impl SystemInterface {
                     .unwrap_or(());
             }
 
+            // Process an event from the queue or the underlying system
+            Ok(GeneralUpdateType::ProcessEvent {
+                event,
+                check_scene,
+                broadcast,
+                source,
+            }) => {
+                self.process_event(event, check_scene, broadcast, source);
+            }
+
             // Process the system update
             Ok(GeneralUpdateType::System(update)) => {
                 // Return the result of the update
@@ -188,12 +274,44 @@ impl SystemInterface {
 
             // Pass the information update to the logger
             Ok(GeneralUpdateType::Update(event_update)) => {
-                // Find the most recent notifications
-                let notifications = self.logger.update(event_update);
+                // Note whether this is the event which starts a new game (the
+                // start event or the reset event of the scene) or ends the game
+                let (is_start, is_end) = match (&event_update, &self.event_handler) {
+                    (EventUpdate::Current(event), Some(handler)) => {
+                        let settings = handler.report_settings();
+                        let id = event.get_id();
+                        let is_reset = handler.get_current_scene().get_id() == id;
+                        (
+                            settings.start == Some(id) || is_reset,
+                            settings.end == Some(id),
+                        )
+                    }
+                    _ => (false, false),
+                };
+
+                // Allow the result of the new game to be recorded
+                if is_start {
+                    self.is_recorded = false;
+                }
+
+                // Find the most recent notifications (noting the current scene)
+                let scene = match self.event_handler {
+                    Some(ref handler) => Some(handler.get_current_scene()),
+                    None => None,
+                };
+                let notifications = self.logger.update(event_update, scene);
+
+                // Record the result of the session when the game ends
+                if is_end {
+                    self.record_session();
+                }
 
                 // Send a notification update to the system
                 self.interface_send
-                    .send(UpdateNotifications { notifications })
+                    .send(UpdateNotifications {
+                        notifications,
+                        pinned: self.logger.pinned_notifications(),
+                    })
                     .unwrap_or(());
             }
 
@@ -224,6 +342,93 @@ impl SystemInterface {
         drop(self);
     }
 
+    /// An internal method to pass an event to the event handler. Only events
+    /// which are processed successfully are counted in the metrics.
+    ///
+    fn process_event(
+        &mut self,
+        event: ItemId,
+        check_scene: bool,
+        broadcast: bool,
+        source: EventSource,
+    ) {
+        // If the event handler exists
+        if let Some(ref mut handler) = self.event_handler {
+            // Try to process the event
+            if handler.process_event(&event, check_scene, broadcast) {
+                // Count the processed event
+                self.general_update.metrics.count_event(source);
+
+                // Notify the user interface of the event
+                let description = handler.get_description(&event);
+                self.interface_send
+                    .send(Notify {
+                        message: description.description,
+                    })
+                    .unwrap_or(());
+            }
+
+        // Otherwise notify the user that a configuration faild to load
+        } else {
+            update!(err &self.general_update => "Event Could Not Be Processed. No Active Configuration.");
+        }
+    }
+
+    /// An internal method to record the result of the current session in the
+    /// statistics, if it has not already been recorded. Sessions without any
+    /// events (or without a game log) are not recorded.
+    ///
+    fn record_session(&mut self) {
+        // Skip a session which was already recorded
+        if self.is_recorded {
+            return;
+        }
+
+        // Rebuild the session from the game log, skipping an empty session
+        let record = match self.logger.session_record() {
+            Some(record) if !record.is_empty() => record,
+            _ => return,
+        };
+
+        // If the event handler exists, compose the report and record the result
+        if let Some(ref mut handler) = self.event_handler {
+            let settings = handler.report_settings();
+            let statuses = handler.get_full_status();
+            let report = record.compose(&settings, statuses, time::now(), |item_id| {
+                handler.get_description(item_id)
+            });
+            handler.record_result(report.result(&settings));
+            self.is_recorded = true;
+        }
+    }
+
+    /// An internal method to report the broadcasts captured during a
+    /// simulation, with their data, and clear the list.
+    ///
+    fn report_captured(&mut self) {
+        // Describe each captured broadcast and its data
+        let mut captured = Vec::new();
+        for (event_id, data) in self.captured_broadcasts.drain(..) {
+            let event = match self.event_handler {
+                Some(ref handler) => {
+                    ItemPair::from_item(event_id, handler.get_description(&event_id)).to_string()
+                }
+                None => event_id.to_string(),
+            };
+            match data {
+                Some(data) => captured.push(format!("{} With Data {}", event, data)),
+                None => captured.push(event),
+            }
+        }
+
+        // Report the count and the list of broadcasts
+        if captured.is_empty() {
+            update!(update &self.general_update => "Simulation Ended. No Broadcasts Were Captured.");
+        } else {
+            update!(update &self.general_update => "Simulation Ended. {} Broadcasts Were Captured: {}.", captured.len(), captured.join(", "));
+        }
+    }
+
     /// An internal method to unpack system updates from the main program thread.
     ///
     /// When the update is the Close variant, the function will return false,
@@ -232,6 +437,17 @@ impl SystemInterface {
     fn unpack_system_update(&mut self, update: SystemUpdate) -> bool {
         // Unpack the different variant types
         match update {
+            // Acknowledge one or every error and refresh the notifications
+            Acknowledge { index } => {
+                self.logger.acknowledge(index);
+                self.interface_send
+                    .send(UpdateNotifications {
+                        notifications: self.logger.recent_notifications(),
+                        pinned: self.logger.pinned_notifications(),
+                    })
+                    .unwrap_or(());
+            }
+
             // Change the delay for all events in the queue
             AllEventChange {
                 adjustment,
@@ -252,7 +468,7 @@ impl SystemInterface {
                 }
 
                 // Send the all stop event via the logger
-                update!(broadcast &self.general_update => ItemPair::all_stop(), None);
+                update!(broadcast &self.general_update => ItemPair::all_stop(), Vec::new());
 
                 // Place an error in the debug log
                 update!(err &self.general_update => "An All Stop was triggered by the operator.");
@@ -270,7 +486,7 @@ impl SystemInterface {
             // GeneralUpdate::BroadcastEvent)
             BroadcastEvent { event, data } => {
                 // Broadcast the event via the logger
-                update!(broadcast &self.general_update => event.clone(), data);
+                update!(broadcast &self.general_update => event.clone(), data.into_iter().collect());
 
                 // Notify the user interface of the event
                 self.interface_send
@@ -288,8 +504,11 @@ impl SystemInterface {
                 }
             }
 
-            // Close the system interface thread.
-            Close => return false,
+            // Record the result of the session and close the system interface thread.
+            Close => {
+                self.record_session();
+                return false;
+            }
 
             // Update the configuration provided to the underlying system
             ConfigFile { filepath } => {
@@ -298,8 +517,10 @@ impl SystemInterface {
                     handler.clear_events();
                 }
 
-                // Drop the old event handler
+                // Drop the old event handler (and stop watching its files)
                 self.event_handler = None;
+                self.config_path = None;
+                self.watcher = None;
 
                 // Check to see if a new filepath was specified
                 if let Some(path) = filepath {
@@ -308,6 +529,9 @@ impl SystemInterface {
                 }
             }
 
+            // Reload the current configuration, keeping the live state
+            ReloadConfig => self.reload_config(),
+
             // Swtich between normal mode and debug mode
             DebugMode(mode) => {
                 // Switch the mode (redraw triggered by the user interface)
@@ -316,8 +540,12 @@ impl SystemInterface {
 
             // Modify the underlying configuration
             Edit { mut actions } => {
+                // Refuse any changes in read-only mode
+                if self.is_read_only {
+                    update!(warn &self.general_update => "Change Not Made. Minerva Is In Read-Only Mode.");
+
                 // Check to see if there is an active configuration
-                if let Some(ref mut handler) = self.event_handler {
+                } else if let Some(ref mut handler) = self.event_handler {
                     // Process each action in order
                     for action in actions.drain(..) {
                         // Match the specified action
@@ -360,33 +588,51 @@ impl SystemInterface {
             ErrorLog { filepath } => self.logger.set_error_log(filepath),
 
             // Update the game log provided to the underlying system
-            GameLog { filepath } => self.logger.set_game_log(filepath),
+            GameLog { filepath } => {
+                // Record the result of the previous session
+                self.record_session();
+
+                // Start a new session with the new game log
+                self.is_recorded = false;
+                self.logger.set_game_log(filepath);
+                self.general_update.metrics.start_session();
+            }
 
-            // Pass an event to the event_handler
-            ProcessEvent {
-                event,
-                check_scene,
-                broadcast,
-            } => {
+            // Change the rotation, retention and location of the log files
+            LogOptions { settings } => {
+                // Apply the settings and keep them in the configuration, if it exists
+                self.logger.set_settings(settings.clone());
+                if let Some(ref mut handler) = self.event_handler {
+                    handler.set_log_settings(settings);
+                }
+            }
+
+            // Mirror the backup of the primary instance
+            MirrorPrimary => {
                 // If the event handler exists
                 if let Some(ref mut handler) = self.event_handler {
-                    // Try to process the event
-                    if handler.process_event(&event, check_scene, broadcast) {
-                        // Notify the user interface of the event
-                        let description = handler.get_description(&event);
+                    // If the backup of the primary has changed
+                    if let Some(events) = handler.mirror_primary() {
+                        // Send the queued events of the primary to the interface
                         self.interface_send
-                            .send(Notify {
-                                message: description.description,
-                            })
+                            .send(UpdateTimeline { events })
                             .unwrap_or(());
-                    }
 
-                // Otherwise notify the user that a configuration faild to load
-                } else {
-                    update!(err &self.general_update => "Event Could Not Be Processed. No Active Configuration.");
+                        // Trigger a redraw of the window
+                        self.general_update.send_redraw();
+                    }
                 }
             }
 
+            // Pass an event from the operator to the event_handler
+            ProcessEvent {
+                event,
+                check_scene,
+                broadcast,
+            } => {
+                self.process_event(event, check_scene, broadcast, EventSource::Interface);
+            }
+
             // Pass an event to the queue
             QueueEvent { event_delay } => {
                 // If the event handler exists
@@ -404,6 +650,12 @@ impl SystemInterface {
             Redraw => {
                 // Try to redraw the current window
                 if let Some(ref mut handler) = self.event_handler {
+                    // Note the current scene
+                    let current_scene = handler.get_current_scene();
+                    self.general_update
+                        .metrics
+                        .set_scene(current_scene.get_id().id());
+
                     // Compose the new event window and status items
                     let (window, statuses) = SystemInterface::sort_items(
                         handler.get_items(),
@@ -414,7 +666,7 @@ impl SystemInterface {
                     // Send the update with the new event window
                     self.interface_send
                         .send(UpdateWindow {
-                            current_scene: handler.get_current_scene(),
+                            current_scene,
                             window,
                             statuses,
                             key_map: handler.get_key_map(),
@@ -425,8 +677,31 @@ impl SystemInterface {
 
             // Reply to the request for information
             Request { reply_to, request } => {
-                // If the event handler exists
-                if let Some(ref mut handler) = self.event_handler {
+                // Answer the requests for the logger, even without a configuration
+                let logger_reply = match request {
+                    // Reply to a request for the log settings
+                    RequestType::LogSettings => Some(ReplyType::LogSettings {
+                        settings: self.logger.settings(),
+                    }),
+
+                    // Reply to a request for the notification history
+                    RequestType::Notifications => Some(ReplyType::Notifications {
+                        history: self.logger.notification_history(),
+                    }),
+
+                    // Leave the other requests for the event handler
+                    _ => None,
+                };
+                if let Some(reply) = logger_reply {
+                    self.interface_send
+                        .send(Reply {
+                            reply_to, // echo the display component
+                            reply,
+                        })
+                        .unwrap_or(());
+
+                // Otherwise, if the event handler exists
+                } else if let Some(ref mut handler) = self.event_handler {
                     // Match the type of information request
                     match request {
                         // Reply to a request for the item description
@@ -456,6 +731,37 @@ impl SystemInterface {
                                 })
                                 .unwrap_or(());
                         }
+
+                        // Reply to a request for the session statistics
+                        RequestType::Statistics => {
+                            // Summarize the session results
+                            let statistics = handler.get_statistics(LEADERBOARD_LENGTH);
+
+                            // Send them back to the user interface
+                            self.interface_send
+                                .send(Reply {
+                                    reply_to, // echo the display component
+                                    reply: ReplyType::Statistics { statistics },
+                                })
+                                .unwrap_or(());
+                        }
+
+                        // Reply to a request for the saved checkpoints
+                        RequestType::Checkpoints => {
+                            // Collect the names of the checkpoints
+                            let checkpoints = handler.list_checkpoints();
+
+                            // Send them back to the user interface
+                            self.interface_send
+                                .send(Reply {
+                                    reply_to, // echo the display component
+                                    reply: ReplyType::Checkpoints { checkpoints },
+                                })
+                                .unwrap_or(());
+                        }
+
+                        // The logger requests were answered above
+                        RequestType::LogSettings | RequestType::Notifications => (),
                     }
 
                 // Otherwise noity the user that a configuration failed to load
@@ -464,12 +770,134 @@ impl SystemInterface {
                 }
             }
 
+            // Resume the selected parts of the lingering backup data
+            ResumeBackup {
+                scene,
+                statuses,
+                events,
+                resume_mode,
+            } => {
+                // If the event handler exists
+                if let Some(ref mut handler) = self.event_handler {
+                    // Resume or discard the backup (automatically triggers a redraw)
+                    handler.resume_lingering(scene, statuses, events, resume_mode);
+                }
+            }
+
+            // Ask the operator again about any lingering backup data
+            ReviewBackup => {
+                // If there is lingering backup data, relaunch the dialog
+                match self
+                    .event_handler
+                    .as_ref()
+                    .and_then(|handler| handler.backup_summary())
+                {
+                    Some(summary) => self
+                        .interface_send
+                        .send(LaunchWindow {
+                            window_type: WindowType::ResumeBackup(summary),
+                        })
+                        .unwrap_or(()),
+
+                    // Otherwise notify the user
+                    None => update!(update &self.general_update => "No Lingering Backup Data."),
+                }
+            }
+
+            // Restore the named checkpoint
+            RestoreCheckpoint { name, rebroadcast } => {
+                // If the event handler exists
+                if let Some(ref mut handler) = self.event_handler {
+                    // Restore the checkpoint (automatically triggers a redraw)
+                    handler.restore_checkpoint(&name, rebroadcast);
+
+                // Otherwise notify the user that there is no active configuration
+                } else {
+                    update!(warn &self.general_update => "Checkpoint Not Restored. No Active Configuration.");
+                }
+            }
+
+            // Save a named checkpoint of the current state
+            SaveCheckpoint { name } => {
+                // If the event handler exists
+                if let Some(ref handler) = self.event_handler {
+                    // Save the checkpoint
+                    handler.save_checkpoint(&name);
+
+                // Otherwise notify the user that there is no active configuration
+                } else {
+                    update!(warn &self.general_update => "Checkpoint Not Saved. No Active Configuration.");
+                }
+            }
+
+            // Delete a named checkpoint
+            DeleteCheckpoint { name } => {
+                // If the event handler exists
+                if let Some(ref handler) = self.event_handler {
+                    // Delete the checkpoint
+                    handler.delete_checkpoint(&name);
+
+                // Otherwise notify the user that there is no active configuration
+                } else {
+                    update!(warn &self.general_update => "Checkpoint Not Deleted. No Active Configuration.");
+                }
+            }
+
             // Save the current configuration to the provided file
             SaveConfig { filepath } => {
+                // Refuse to save in read-only mode
+                if self.is_read_only {
+                    update!(warn &self.general_update => "Configuration Not Saved. Minerva Is In Read-Only Mode.");
+
                 // Extract the current event handler (if it exists)
-                if let Some(ref handler) = self.event_handler {
+                } else if let Some(ref handler) = self.event_handler {
                     // Save the current configuration
                     handler.save_config(filepath);
+
+                    // Ignore the changes from saving in the configuration watcher
+                    self.watch_config();
+                }
+            }
+
+            // Export the event flow graph of the current configuration
+            SaveGraph { filepath } => {
+                // Extract the current event handler (if it exists)
+                if let Some(ref handler) = self.event_handler {
+                    // Save the event graph
+                    handler.save_graph(filepath);
+
+                // Otherwise notify the user that there is no active configuration
+                } else {
+                    update!(warn &self.general_update => "Graph Not Exported. No Active Configuration.");
+                }
+            }
+
+            // Save the game report of the current session
+            SaveReport { filepath } => {
+                // Extract the current event handler (if it exists)
+                if let Some(ref mut handler) = self.event_handler {
+                    // Rebuild the session from the game log
+                    let record = match self.logger.session_record() {
+                        Some(record) => record,
+                        None => {
+                            update!(warn &self.general_update => "Report Not Saved. No Active Game Log.");
+                            return true;
+                        }
+                    };
+
+                    // Compose the report from the session record
+                    let settings = handler.report_settings();
+                    let statuses = handler.get_full_status();
+                    let report = record.compose(&settings, statuses, time::now(), |item_id| {
+                        handler.get_description(item_id)
+                    });
+
+                    // Save the report (the result is recorded when the session ends)
+                    self.logger.save_report(&report, filepath);
+
+                // Otherwise notify the user that there is no active configuration
+                } else {
+                    update!(warn &self.general_update => "Report Not Saved. No Active Configuration.");
                 }
             }
 
@@ -482,6 +910,41 @@ impl SystemInterface {
                 }
             }
 
+            // Switch between real time and a simulation at the provided rate
+            SimulationMode(rate) => {
+                // Report the captured broadcasts when a simulation ends
+                let was_simulated = self.clock.is_simulated();
+                if rate.is_none() && was_simulated {
+                    self.report_captured();
+                }
+
+                // Change the mode of the clock
+                match rate {
+                    Some(rate) => self.clock.set_mode(ClockMode::Simulated { rate }),
+                    None => self.clock.set_mode(ClockMode::RealTime),
+                }
+
+                // Pause the backup and tag the logs while simulating
+                if rate.is_some() != was_simulated {
+                    if let Some(ref mut handler) = self.event_handler {
+                        handler.set_simulated(rate.is_some());
+                    }
+                    self.logger.set_simulated(rate.is_some());
+                }
+
+                // Have the queue recalculate the time until the next event
+                if let Some(ref handler) = self.event_handler {
+                    handler.refresh_clock();
+                }
+
+                // Notify the user interface of the change
+                let message = match rate {
+                    Some(rate) => format!("Simulating At {}x. Broadcasts Will Not Be Sent.", rate),
+                    None => "Running In Real Time.".to_string(),
+                };
+                self.interface_send.send(Notify { message }).unwrap_or(());
+            }
+
             // Change the state of a particular status
             StatusChange { status_id, state } => {
                 // Change the status, if event handler exists
@@ -490,6 +953,35 @@ impl SystemInterface {
                     handler.modify_status(&status_id, &state);
                 }
             }
+
+            // Take over from the primary instance
+            TakeOver => {
+                // If the event handler exists
+                if let Some(ref mut handler) = self.event_handler {
+                    // Take over the backup and the queue
+                    handler.take_over();
+
+                    // Take over the connection to the underlying system
+                    self.system_connection
+                        .update_system_connection(Some(handler.system_connection()));
+
+                    // Notify the user interface of the change
+                    self.interface_send
+                        .send(Notify {
+                            message: "Primary Instance Lost. This Instance Has Taken Over."
+                                .to_string(),
+                        })
+                        .unwrap_or(());
+                }
+            }
+
+            // Record the string provided by the user for the game report
+            UserString { event, string } => {
+                if let Some(ref handler) = self.event_handler {
+                    let event = ItemPair::from_item(event, handler.get_description(&event));
+                    self.logger.add_string(event, string);
+                }
+            }
         }
         true // indicate to continue
     }
@@ -504,25 +996,44 @@ impl SystemInterface {
     /// all other types of errors will be logged on the general_send line.
     ///
     fn load_config(&mut self, filepath: PathBuf, log_failure: bool) {
+        // Note the location of the configuration (to reload it later)
+        self.config_path = Some(filepath.clone());
+
         // Create a new event handler
         let mut event_handler = match EventHandler::new(
             filepath,
             self.general_update.clone(),
             self.interface_send.clone(),
+            self.clock.clone(),
             log_failure,
         ) {
             Ok(evnt_hdlr) => evnt_hdlr,
             Err(_) => return, // errors will be logged separately if log_failure is true
         };
 
-        // Create a new connection to the underlying system
-        if !self
-            .system_connection
-            .update_system_connection(Some(event_handler.system_connection()))
-        {
+        // Create a new connection to the underlying system (none while on standby)
+        let connections = if event_handler.is_standby() {
+            None
+        } else {
+            Some(event_handler.system_connection())
+        };
+        if !self.system_connection.update_system_connection(connections) {
             return;
         }
 
+        // Apply the log settings of the configuration, if specified
+        if let Some(settings) = event_handler.log_settings() {
+            self.logger.set_settings(settings);
+
+        // Otherwise, remove any old logs with the default settings
+        } else {
+            self.logger.prune();
+        }
+
+        // Serve the metrics and the web console at the addresses of the configuration
+        self.serve_metrics(event_handler.metrics_address());
+        self.serve_web_console(event_handler.web_console());
+
         // Send the newly available scenes and full status to the user interface
         self.interface_send
             .send(UpdateConfig {
@@ -531,11 +1042,152 @@ impl SystemInterface {
             })
             .unwrap_or(());
 
+        // Ask the operator what to do with any lingering backup data
+        if let Some(summary) = event_handler.backup_summary() {
+            self.interface_send
+                .send(LaunchWindow {
+                    window_type: WindowType::ResumeBackup(summary),
+                })
+                .unwrap_or(());
+        }
+
         // Trigger a redraw of the system
         self.general_update.send_redraw();
 
-        // Update the event handler
+        // Keep the backup paused if a simulation is running
+        if self.clock.is_simulated() {
+            event_handler.set_simulated(true);
+        }
+
+        // Update the event handler and watch its files, if requested
         self.event_handler = Some(event_handler);
+        self.watch_config();
+    }
+
+    /// An internal method to reload the current configuration while keeping
+    /// the live state (the current scene, the status states and the queued
+    /// events). The system connections, metrics and web console are only
+    /// restarted if they changed in the configuration. If there is no active
+    /// configuration, the configuration is loaded normally.
+    ///
+    /// # Errors
+    ///
+    /// Like load_config, this method will log any errors on the general_send
+    /// line and leave the current configuration in place.
+    ///
+    fn reload_config(&mut self) {
+        // Find the location of the current configuration
+        let filepath = match self.config_path.clone() {
+            Some(filepath) => filepath,
+            None => {
+                update!(warn &self.general_update => "Configuration Not Reloaded. No Active Configuration.");
+                return;
+            }
+        };
+
+        // Load the configuration normally if there is no active configuration
+        let handler = match self.event_handler {
+            Some(ref mut handler) => handler,
+            None => return self.load_config(filepath, true),
+        };
+
+        // Note the current settings and try to reload the configuration
+        let connections = handler.system_connection();
+        let log_settings = handler.log_settings();
+        let metrics_address = handler.metrics_address();
+        let web_console = handler.web_console();
+        if handler
+            .reload_config(&filepath, self.interface_send.clone())
+            .is_err()
+        {
+            return;
+        }
+
+        // Restart the system connections only if they changed (and not while on standby)
+        if !handler.is_standby() && (handler.system_connection() != connections) {
+            self.system_connection
+                .update_system_connection(Some(handler.system_connection()));
+        }
+
+        // Apply the log settings only if they changed
+        if handler.log_settings() != log_settings {
+            if let Some(settings) = handler.log_settings() {
+                self.logger.set_settings(settings);
+            }
+        }
+
+        // Send the new scenes and full status to the user interface
+        self.interface_send
+            .send(UpdateConfig {
+                scenes: handler.get_scenes(),
+                full_status: handler.get_full_status(),
+            })
+            .unwrap_or(());
+
+        // Serve the metrics and the web console again only if their settings changed
+        let new_metrics_address = handler.metrics_address();
+        let new_web_console = handler.web_console();
+        if new_metrics_address != metrics_address {
+            self.serve_metrics(new_metrics_address);
+        }
+        if new_web_console != web_console {
+            self.serve_web_console(new_web_console);
+        }
+
+        // Watch the files of the new configuration, if requested
+        self.watch_config();
+    }
+
+    /// An internal method to serve the metrics at the provided address, or
+    /// stop serving them if there is no address.
+    ///
+    fn serve_metrics(&mut self, address: Option<String>) {
+        match address {
+            Some(address) => {
+                if let Err(error) = self.general_update.metrics.listen(&address) {
+                    update!(err &self.general_update => "Unable To Serve Metrics At {}: {}", address, error);
+                }
+            }
+            None => self.general_update.metrics.stop(),
+        }
+    }
+
+    /// An internal method to serve the web console with the provided settings,
+    /// or stop serving it if there are no settings.
+    ///
+    fn serve_web_console(&mut self, settings: Option<ConsoleSettings>) {
+        match settings {
+            Some(settings) => {
+                if let Err(error) = self.web_console.listen(&settings) {
+                    update!(err &self.general_update => "Unable To Serve The Web Console At {}: {}", settings.address, error);
+                }
+            }
+            None => self.web_console.stop(),
+        }
+    }
+
+    /// An internal method to watch the files of the current configuration
+    /// (the configuration and any included files) for changes, if requested.
+    /// Any previous watcher is replaced, so that only later changes trigger a
+    /// reload.
+    ///
+    fn watch_config(&mut self) {
+        // Only watch if requested and there is an active configuration
+        self.watcher = None;
+        if !self.is_watching {
+            return;
+        }
+        if let (Some(filepath), Some(handler)) = (&self.config_path, &self.event_handler) {
+            // Find the configuration and any included files
+            let folder = filepath.parent().unwrap_or_else(|| Path::new(""));
+            let mut paths = vec![filepath.clone()];
+            for included in handler.included_files() {
+                paths.push(folder.join(included));
+            }
+
+            // Start watching them
+            self.watcher = Some(ConfigWatcher::new(paths, self.general_update.clone()));
+        }
     }
 
     /// An internal to sort the available events in this current scene
@@ -626,6 +1278,82 @@ impl SystemInterface {
     }
 }
 
+/// A structure to hold the options chosen when the program starts.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartupOptions {
+    pub config_path: Option<PathBuf>, // the configuration to load in place of the default, if specified
+    pub log_folder: Option<PathBuf>, // the folder for the log files in place of the default, if specified
+    pub is_read_only: bool,          // a flag to refuse any changes to the configuration
+    pub is_watching: bool,           // a flag to reload the configuration when the files change
+}
+
+/// An enum to specify a task to perform on a configuration file without
+/// running the configuration.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigTask {
+    /// A variant to check the configuration for errors and inconsistencies
+    Validate,
+
+    /// A variant to export the event graph of the configuration to the
+    /// provided file
+    Graph(PathBuf),
+
+    /// A variant to rewrite the configuration in the layout of the current
+    /// version to the provided file
+    Migrate(PathBuf),
+
+    /// A variant to compose the game report of a session (the most recent
+    /// one, if not specified) in the provided game log, saved as CSV and HTML
+    /// files at the provided location (the extension is replaced)
+    Report {
+        log_path: PathBuf,
+        session: Option<String>,
+        report_path: PathBuf,
+    },
+}
+
+/// A function to perform a task on a configuration file without running the
+/// configuration (to check configurations from the command line or a script).
+///
+/// # Errors
+///
+/// The function returns the errors, warnings and other updates (such as the
+/// changes made to upgrade an older configuration) raised by the
+/// configuration. If the task could not be completed, these are returned as
+/// an error.
+///
+pub fn process_config(
+    config_path: PathBuf,
+    task: ConfigTask,
+) -> Result<Vec<EventUpdate>, Vec<EventUpdate>> {
+    // Create a general update to collect the errors and warnings
+    let (general_update, general_receive) = GeneralUpdate::new();
+
+    // Complete the task
+    let result = EventHandler::process_config(&general_update, config_path, task);
+
+    // Collect the errors, warnings and updates raised along the way
+    let updates = general_receive
+        .try_iter()
+        .filter_map(|update| match update {
+            GeneralUpdateType::Update(event_update @ EventUpdate::Error(..))
+            | GeneralUpdateType::Update(event_update @ EventUpdate::Warning(..))
+            | GeneralUpdateType::Update(event_update @ EventUpdate::Update(..)) => {
+                Some(event_update)
+            }
+            _ => None,
+        })
+        .collect();
+
+    // Return the updates, noting whether the task was successful
+    match result {
+        Ok(()) => Ok(updates),
+        Err(_) => Err(updates),
+    }
+}
+
 /// An private enum to provide and receive updates from the various internal
 /// components of the system interface and external updates from the interface.
 ///
@@ -644,6 +1372,15 @@ enum GeneralUpdateType {
     /// item id. FIXME Make this more generic for other user input
     GetUserString(ItemPair),
 
+    /// A variant that processes a new event from the queue or the underlying
+    /// system. The source of the event is counted once it has been processed.
+    ProcessEvent {
+        event: ItemId,
+        check_scene: bool,
+        broadcast: bool,
+        source: EventSource,
+    },
+
     /// A variant to notify the system of an update from the user interface
     System(SystemUpdate),
 
@@ -656,6 +1393,7 @@ enum GeneralUpdateType {
 #[derive(Clone, Debug)]
 pub struct GeneralUpdate {
     general_send: mpsc::Sender<GeneralUpdateType>, // the mpsc sending line to pass updates to the system interface
+    metrics: Metrics, // the counts of activity, shared with every copy of the general update
 }
 
 // Implement the key features of the general update struct
@@ -670,7 +1408,13 @@ impl GeneralUpdate {
         let (general_send, receive) = mpsc::channel();
 
         // Create and return both new items
-        (GeneralUpdate { general_send }, receive)
+        (
+            GeneralUpdate {
+                general_send,
+                metrics: Metrics::new(),
+            },
+            receive,
+        )
     }
 
     /// A method to broadcast an event via the system interface (with data,
@@ -690,16 +1434,34 @@ impl GeneralUpdate {
             .unwrap_or(());
     }
 
-    /// A method to process a new event. If the check_scene flag is not set,
-    /// the system will not check if the event is in the current scene. If
-    /// broadcast is set to true, the event will be broadcast to the system.
+    /// A method to process a new event from the underlying system. If the
+    /// check_scene flag is not set, the system will not check if the event is
+    /// in the current scene. If broadcast is set to true, the event will be
+    /// broadcast to the system.
     ///
     fn send_event(&self, event: ItemId, check_scene: bool, broadcast: bool) {
-        self.send_system(ProcessEvent {
-            event,
-            check_scene,
-            broadcast,
-        });
+        self.general_send
+            .send(GeneralUpdateType::ProcessEvent {
+                event,
+                check_scene,
+                broadcast,
+                source: EventSource::System,
+            })
+            .unwrap_or(());
+    }
+
+    /// A method to process a new event from the event queue. The flags match
+    /// those of send_event.
+    ///
+    fn send_queued_event(&self, event: ItemId, check_scene: bool, broadcast: bool) {
+        self.general_send
+            .send(GeneralUpdateType::ProcessEvent {
+                event,
+                check_scene,
+                broadcast,
+                source: EventSource::Queue,
+            })
+            .unwrap_or(());
     }
 
     /// A method to request a string from the user FIXME make this more generic
@@ -735,7 +1497,7 @@ impl GeneralUpdate {
 }
 
 /// A special, public version of the general update which only allows for a
-/// system send (without other types of updates).
+/// system send and notes in the logs (without other types of updates).
 ///
 #[derive(Clone, Debug)]
 pub struct SystemSend {
@@ -760,10 +1522,21 @@ impl SystemSend {
             .send(GeneralUpdateType::System(update))
             .unwrap_or(());
     }
+
+    /// A method to pass a note, warning or error to the logs of the system
+    /// interface (for the parts of the program without their own logger).
+    /// This version of the method fails silently.
+    ///
+    pub fn send_update(&self, update: EventUpdate) {
+        self.general_send
+            .send(GeneralUpdateType::Update(update))
+            .unwrap_or(());
+    }
 }
 
 /// An enum to execute one of the available edit actions for the configuration
 ///
+#[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum EditAction {
     /// An action to delete an existing event
@@ -778,6 +1551,7 @@ pub enum EditAction {
 
 /// An enum to specify the type of information request
 ///
+#[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RequestType {
     /// A variant for the description of an item
@@ -785,9 +1559,22 @@ pub enum RequestType {
 
     /// A variant for the detail of an event
     Detail { item_id: ItemId },
+
+    /// A variant for the names of the saved checkpoints
+    Checkpoints,
+
+    /// A variant for the statistics of the session results
+    Statistics,
+
+    /// A variant for the current log settings
+    LogSettings,
+
+    /// A variant for the notification history of the session
+    Notifications,
 }
 
 /// An enum to specify which display component has requested the information
+#[allow(dead_code)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DisplayComponent {
     /// A variant for the trigger dialog
@@ -795,6 +1582,18 @@ pub enum DisplayComponent {
 
     /// A variant for the edit item window
     EditItem,
+
+    /// A variant for the checkpoint dialog
+    CheckpointDialog,
+
+    /// A variant for the statistics window
+    StatisticsWindow,
+
+    /// A variant for the log settings dialog
+    LogSettingsDialog,
+
+    /// A variant for the notification history window
+    NotificationWindow,
 }
 
 /// An enum to provide updates from the main thread to the system interface,
@@ -802,6 +1601,10 @@ pub enum DisplayComponent {
 ///
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SystemUpdate {
+    /// A variant to acknowledge the error with the provided index in the
+    /// notification history, or every error if None is provided.
+    Acknowledge { index: Option<usize> },
+
     /// A variant to adjust all the events in the timeline
     /// NOTE: after the adjustment, events that would have already happened are discarded
     AllEventChange {
@@ -816,6 +1619,7 @@ pub enum SystemUpdate {
     /// A variant that broadcasts an event with the given item id. This event id
     /// is not processed or otherwise checked for validity. If data is provided
     /// it will be broadcast with the event.
+    #[allow(dead_code)]
     BroadcastEvent { event: ItemPair, data: Option<u32> },
 
     /// A variant to trigger all the queued events to clear
@@ -826,15 +1630,19 @@ pub enum SystemUpdate {
 
     /// A variant that provides a new configuration file for the system interface.
     /// If None is provided as the filepath, no configuration will be loaded.
+    #[allow(dead_code)]
     ConfigFile { filepath: Option<PathBuf> },
 
     /// A special variant to switch to or from debug mode for the program.
+    #[allow(dead_code)]
     DebugMode(bool),
 
     /// A variant to modify the underlying configuration
+    #[allow(dead_code)]
     Edit { actions: Vec<EditAction> },
 
     /// A variant that provides a new error log file for the system interface.
+    #[allow(dead_code)]
     ErrorLog { filepath: PathBuf },
 
     /// A variant to change the remaining delay for an existing event in the
@@ -848,6 +1656,15 @@ pub enum SystemUpdate {
     /// A variant that provides a new game log file for the system interface.
     GameLog { filepath: PathBuf },
 
+    /// A variant that provides new rotation, retention and location settings
+    /// for the log files.
+    #[allow(dead_code)]
+    LogOptions { settings: LogSettings },
+
+    /// A variant to mirror the backup of the primary instance while this
+    /// instance is on standby (sent regularly by the standby monitor)
+    MirrorPrimary,
+
     /// A variant that processes a new event with the given item id. If the
     /// check_scene flag is not set, the system will not check if the event is
     /// listed in the current scene. If broadcast is set to true, the event
@@ -865,29 +1682,90 @@ pub enum SystemUpdate {
     /// A variant that triggers a redraw of the user interface window
     Redraw,
 
+    /// A variant to reload the current configuration from its file, keeping
+    /// the current scene, status states and queued events where they still
+    /// exist (sent on request or by the configuration watcher)
+    ReloadConfig,
+
     /// A variant that requests information from the system and directs it
     /// to a specific spot on the window
+    #[allow(dead_code)]
     Request {
         reply_to: DisplayComponent,
         request: RequestType,
     },
 
+    /// A variant to resume the selected parts of the lingering backup data
+    /// found when the configuration was loaded, with the chosen resume mode
+    /// for the queued events. Any part which is not selected is discarded.
+    ResumeBackup {
+        scene: bool,             // a flag to resume the current scene
+        statuses: bool,          // a flag to resume the status states
+        events: bool,            // a flag to resume the queued events
+        resume_mode: ResumeMode, // how to resume the queued events
+    },
+
+    /// A variant to ask the operator again what to do with the lingering
+    /// backup data (after the operator chose to decide later)
+    #[allow(dead_code)]
+    ReviewBackup,
+
+    /// A variant to restore the named checkpoint. If rebroadcast is set, the
+    /// state event of every status is triggered after the checkpoint is
+    /// restored so that the connected devices resynchronize.
+    RestoreCheckpoint { name: String, rebroadcast: bool },
+
+    /// A variant to save a named checkpoint of the current scene, statuses
+    /// and queued events (replacing any checkpoint with the same name).
+    SaveCheckpoint { name: String },
+
+    /// A variant to delete the named checkpoint. Checkpoints are otherwise
+    /// kept in the backup when the program closes or the configuration
+    /// changes.
+    DeleteCheckpoint { name: String },
+
     /// A variant that provides a new configuration file to save the current
     /// configuration.
     SaveConfig { filepath: PathBuf },
 
+    /// A variant that provides a file to export the event flow graph of the
+    /// current configuration (in the Graphviz DOT format).
+    #[allow(dead_code)]
+    SaveGraph { filepath: PathBuf },
+
+    /// A variant to save the game report of the current session as CSV and
+    /// HTML files. If None is provided as the filepath, the report is saved
+    /// next to the game log.
+    SaveReport { filepath: Option<PathBuf> },
+
     /// A variant to change the selected scene provided by the user interface.
     SceneChange { scene: ItemId },
 
+    /// A variant to run the program on a simulated clock at the provided rate
+    /// (e.g. Some(10) for ten times faster), or None to return to real time.
+    /// While simulating, broadcasts are captured rather than sent to the system.
+    SimulationMode(Option<u32>),
+
     /// A variant to change the state of the indicated status.
     StatusChange { status_id: ItemId, state: ItemId },
+
+    /// A variant to take over from the primary instance after it stopped
+    /// responding (sent by the standby monitor)
+    TakeOver,
+
+    /// A variant to record the string provided by the user in response to
+    /// the prompt of the provided event (the string is broadcast separately)
+    #[allow(dead_code)]
+    UserString { event: ItemId, string: String },
 }
 
 // Reexport the system update type variants
 pub use self::SystemUpdate::{
-    AllEventChange, AllStop, BroadcastEvent, ClearQueue, Close, ConfigFile, DebugMode, Edit,
-    ErrorLog, EventChange, GameLog, ProcessEvent, QueueEvent, Redraw, Request, SaveConfig,
-    SceneChange, StatusChange,
+    Acknowledge, AllEventChange, AllStop, BroadcastEvent, ClearQueue, Close, ConfigFile, DebugMode,
+    DeleteCheckpoint, Edit, ErrorLog, EventChange, GameLog, LogOptions, MirrorPrimary,
+    ProcessEvent, QueueEvent, Redraw, ReloadConfig, Request, RestoreCheckpoint, ResumeBackup,
+    ReviewBackup, SaveCheckpoint, SaveConfig, SaveGraph, SaveReport, SceneChange, SimulationMode,
+    StatusChange, TakeOver, UserString,
 };
 
 /// A structure to list a series of event buttons that are associated with one
@@ -905,6 +1783,7 @@ pub type EventWindow = Vec<EventGroup>; // a vector of event groups that belong
 
 /// An enum to launch one of the special windows for the user interface
 ///
+#[allow(dead_code)]
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum WindowType {
     /// A variant to launch the status dialog with an optional relevant status of interest
@@ -919,12 +1798,29 @@ pub enum WindowType {
     /// A variant to show the shortcuts window
     Shortcuts,
 
+    /// A variant to launch the checkpoint dialog
+    Checkpoints,
+
+    /// A variant to launch the statistics window
+    Statistics,
+
+    /// A variant to launch the log settings dialog
+    LogSettings,
+
+    /// A variant to launch the notification history window
+    Notifications,
+
+    /// A variant to ask the operator whether to resume the summarized
+    /// lingering backup data
+    ResumeBackup(BackupSummary),
+
     /// A variant to solicit a string from the user. The string will be sent as
     /// a series of events to the system
     PromptString(ItemPair),
 }
 
 /// An enum to change one of the display settings of the user interface
+#[allow(dead_code)]
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum DisplaySetting {
     /// A variant to change the fullscreen mode of the display
@@ -949,6 +1845,19 @@ pub enum ReplyType {
 
     /// A variant for the detail of an event
     Detail { event_detail: Option<EventDetail> },
+
+    /// A variant for the names of the saved checkpoints
+    Checkpoints { checkpoints: Vec<String> },
+
+    /// A variant for the statistics of the session results
+    Statistics { statistics: StatisticsSummary },
+
+    /// A variant for the current log settings
+    LogSettings { settings: LogSettings },
+
+    /// A variant for the notification history of the session, the most
+    /// recent first
+    Notifications { history: Vec<HistoryEntry> },
 }
 
 /// An enum type to provide interface updates back to the user interface thread.
@@ -959,6 +1868,7 @@ pub enum InterfaceUpdate {
     ChangeSettings { display_setting: DisplaySetting },
 
     /// A variant to switch the interface to or from edit mode
+    #[allow(dead_code)]
     EditMode(bool),
 
     /// A variant to launch one of the special windows
@@ -995,17 +1905,23 @@ pub enum InterfaceUpdate {
         new_state: ItemPair, // the new state of the group
     },
 
-    /// A variant indicating that the system notifications should be updated.
-    UpdateNotifications { notifications: Vec<Notification> },
+    /// A variant indicating that the system notifications should be updated,
+    /// with the errors which have not been acknowledged pinned at the top.
+    UpdateNotifications {
+        notifications: Vec<Notification>,
+        pinned: Vec<HistoryEntry>,
+    },
 
     /// A variant indicating that the event timeline should be updated.
     UpdateTimeline { events: Vec<UpcomingEvent> },
 }
 
 // Reexport the interface update type variants
+#[allow(unused_imports)]
+pub use self::InterfaceUpdate::EditMode;
 pub use self::InterfaceUpdate::{
-    ChangeSettings, EditMode, LaunchWindow, Notify, Reply, UpdateConfig, UpdateNotifications,
-    UpdateStatus, UpdateTimeline, UpdateWindow,
+    ChangeSettings, LaunchWindow, Notify, Reply, UpdateConfig, UpdateNotifications, UpdateStatus,
+    UpdateTimeline, UpdateWindow,
 };
 
 // Tests of the system_interface module
@@ -1019,4 +1935,98 @@ mod tests {
         // FIXME: Implement this
         unimplemented!();
     }
+
+    // Test checking and graphing a configuration without running it
+    #[test]
+    fn process_config_tasks() {
+        // Check the default configuration (only the version may differ)
+        let updates = process_config(PathBuf::from(DEFAULT_FILE), ConfigTask::Validate)
+            .expect("Unable to check configuration.");
+        assert!(updates.iter().all(|update| match update {
+            EventUpdate::Warning(message, ..) => message.starts_with("Version Of Configuration"),
+            _ => false,
+        }));
+
+        // Export the event graph of the default configuration
+        let graph_path =
+            env::temp_dir().join(format!("minerva_process_config_{}.dot", std::process::id()));
+        assert!(process_config(
+            PathBuf::from(DEFAULT_FILE),
+            ConfigTask::Graph(graph_path.clone())
+        )
+        .is_ok());
+        let graph = std::fs::read_to_string(&graph_path).unwrap();
+        assert!(graph.starts_with("digraph minerva {"));
+        std::fs::remove_file(graph_path).unwrap_or(());
+
+        // Migrate a copy of the default configuration in place, keeping the original
+        let config_path =
+            env::temp_dir().join(format!("minerva_process_config_{}.mnv", std::process::id()));
+        let backup_path = config_path.with_extension("mnv.bak");
+        let original = std::fs::read_to_string(DEFAULT_FILE).unwrap();
+        std::fs::write(&config_path, &original).unwrap();
+        let result = process_config(
+            config_path.clone(),
+            ConfigTask::Migrate(config_path.clone()),
+        );
+        let migrated = std::fs::read_to_string(&config_path).unwrap_or_default();
+        let kept = std::fs::read_to_string(&backup_path).unwrap_or_default();
+        std::fs::remove_file(&config_path).unwrap_or(());
+        std::fs::remove_file(&backup_path).unwrap_or(());
+        assert!(result.is_ok());
+        assert!(migrated.contains("version:"));
+        assert_eq!(kept, original);
+
+        // Migrate a configuration with an included file to another folder
+        let folder = env::temp_dir().join(format!("minerva_process_config_{}", std::process::id()));
+        let new_folder = folder.join("new");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("area.mnv"),
+            "all_scenes:\n  ? id: 300\n  : events: []\n",
+        )
+        .unwrap();
+        std::fs::write(
+            folder.join("config.mnv"),
+            original + "includes:\n  - area.mnv\n",
+        )
+        .unwrap();
+        let result = process_config(
+            folder.join("config.mnv"),
+            ConfigTask::Migrate(new_folder.join("config.mnv")),
+        );
+        let checked = process_config(new_folder.join("config.mnv"), ConfigTask::Validate);
+        std::fs::remove_dir_all(&folder).unwrap_or(());
+        assert!(result.is_ok());
+        assert!(checked.is_ok());
+
+        // Compose the report of the session in a game log (and fail on a missing session)
+        let folder = env::temp_dir().join(format!("minerva_report_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("game_log.jsonl"),
+            "{\"time\":\"2020-01-01T12:00:00Z\",\"session\":\"2020-01-01T12:00:00Z\",\"kind\":\"session_start\"}\n",
+        )
+        .unwrap();
+        let task = |session: Option<&str>| ConfigTask::Report {
+            log_path: folder.join("game_log.jsonl"),
+            session: session.map(|session| session.to_string()),
+            report_path: folder.join("report"),
+        };
+        let result = process_config(PathBuf::from(DEFAULT_FILE), task(None));
+        let missing = process_config(
+            PathBuf::from(DEFAULT_FILE),
+            task(Some("2000-01-01T00:00:00Z")),
+        );
+        let is_saved = folder.join("report.csv").exists() && folder.join("report.html").exists();
+        std::fs::remove_dir_all(&folder).unwrap_or(());
+        assert!(result.is_ok());
+        assert!(missing.is_err());
+        assert!(is_saved);
+
+        // Fail on a missing configuration
+        let updates = process_config(PathBuf::from("missing.mnv"), ConfigTask::Validate)
+            .expect_err("Missing configuration was checked.");
+        assert_eq!(updates.len(), 1);
+    }
 }
@@ -0,0 +1,724 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to compose the end-of-session game report.
+//!
+//! The session record is rebuilt from the structured game log (the events,
+//! warnings, errors and status changes of one session), so the report of a
+//! past session can be composed again later. The record is composed into a
+//! report of the total time, the time of each milestone relative to the start
+//! of the game, the hints used, the statuses at the end and the warnings and
+//! errors raised. The report can be exported as CSV and as HTML, and is
+//! summarized as a session result for the statistics.
+
+// Import the relevant structures into the correct namespace
+use super::statistics::SessionResult;
+use super::{FullStatus, Hidden, ItemDescription, ItemId, ItemPair};
+
+// Import standard library features
+use std::time::Duration;
+
+// Import the eternal time library
+extern crate time;
+
+// Import the JSON processing library
+extern crate serde_json;
+
+/// A structure to define the events of interest for the game report
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSettings {
+    pub start: Option<ItemId>, // the event which starts the game, if not the start of the session
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<ItemId>, // the milestone events to time (if empty, every event is listed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<ItemId>, // the events which indicate that a hint was given
+    pub team: Option<ItemId>,  // the event which prompts for the team name, if specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<ItemId>, // the event which ends the game (and records the result), if specified
+}
+
+// Implement key features of the report settings
+impl ReportSettings {
+    /// A function to create the default report settings, which time every
+    /// event from the start of the session.
+    ///
+    pub fn new() -> ReportSettings {
+        ReportSettings {
+            start: None,
+            milestones: Vec::new(),
+            hints: Vec::new(),
+            team: None,
+            end: None,
+        }
+    }
+}
+
+/// An internal structure to read one record of the structured game log (the
+/// fields which are not needed for the report are ignored)
+///
+#[derive(Deserialize)]
+struct LogRecord {
+    time: String,                    // the local time of the record (RFC 3339)
+    session: String,                 // the start time of the session, to identify it
+    kind: String,                    // the kind of record (e.g. current, status, session_start)
+    event_id: Option<u32>,           // the id of the event or status, if relevant
+    description: Option<String>,     // the description of the event or status, if relevant
+    data: Option<serde_json::Value>, // the data, message, or new state of the record
+    #[serde(default)]
+    simulated: bool, // a flag to indicate the record was made during a simulation
+}
+
+/// A structure to hold the events, warnings and errors of one session, as
+/// rebuilt from the structured game log.
+///
+pub struct SessionRecord {
+    start: time::Tm,                                 // the start of the session
+    end: time::Tm,                                   // the time of the last record of the session
+    events: Vec<(time::Tm, ItemPair)>,               // the events in the order they occurred
+    problems: Vec<(time::Tm, &'static str, String)>, // the warnings and errors in the order they occurred
+    strings: Vec<(ItemId, String)>, // the strings provided by the user and the events which prompted them
+    states: Vec<(ItemId, String)>, // the status changes (the description of each new state) in order
+}
+
+// Implement key features of the session record
+impl SessionRecord {
+    /// A function to rebuild the record of the provided session (or of the
+    /// most recent session, if none is provided) from the contents of a
+    /// structured game log. The records made during a simulation and any
+    /// lines which cannot be read are left out. Returns None if the session
+    /// is not in the log.
+    ///
+    pub fn from_log(log: &str, session: Option<&str>) -> Option<SessionRecord> {
+        // Read each record of the log
+        let records: Vec<LogRecord> = log
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+
+        // Choose the provided session or the most recent one
+        let session = match session {
+            Some(session) => session.to_string(),
+            None => records.last()?.session.clone(),
+        };
+        let start = parse_time(&session)?;
+        let mut record = SessionRecord {
+            start,
+            end: start,
+            events: Vec::new(),
+            problems: Vec::new(),
+            strings: Vec::new(),
+            states: Vec::new(),
+        };
+
+        // Collect each record of the session
+        let mut is_found = false;
+        for log_record in records
+            .iter()
+            .filter(|log_record| log_record.session == session)
+        {
+            // Note the time of the latest record
+            is_found = true;
+            let time = match parse_time(&log_record.time) {
+                Some(time) => time,
+                None => continue,
+            };
+            record.end = time;
+
+            // Leave out the records of a simulation
+            if log_record.simulated {
+                continue;
+            }
+
+            // Sort the record by its kind
+            let text = log_record
+                .data
+                .as_ref()
+                .and_then(|data| data.as_str())
+                .map(|text| text.to_string());
+            let event = log_record.event_id.and_then(|id| {
+                ItemPair::new(
+                    id,
+                    log_record
+                        .description
+                        .as_ref()
+                        .map_or("", |text| text.as_str()),
+                    Hidden,
+                )
+            });
+            match (log_record.kind.as_str(), event, text) {
+                ("broadcast", Some(event), _) | ("current", Some(event), _) => {
+                    record.events.push((time, event))
+                }
+                ("error", _, Some(message)) => record.problems.push((time, "Error", message)),
+                ("warning", _, Some(message)) => record.problems.push((time, "Warning", message)),
+                ("string", Some(event), Some(string)) => {
+                    record.strings.push((event.get_id(), string))
+                }
+                ("status", Some(status), Some(state)) => {
+                    record.states.push((status.get_id(), state))
+                }
+                _ => (),
+            }
+        }
+
+        // Return the record if the session was found
+        if is_found {
+            Some(record)
+        } else {
+            None
+        }
+    }
+
+    /// A method to check if no events have been recorded in this session
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// A method to return the time of the last record of the session
+    ///
+    pub fn end(&self) -> time::Tm {
+        self.end
+    }
+
+    /// A method to update the provided statuses (such as the default states
+    /// of the configuration) with the status changes of the session. Each
+    /// new state is matched by its description among the allowed states.
+    ///
+    pub fn final_statuses(&self, mut statuses: FullStatus) -> FullStatus {
+        for (status_id, state) in self.states.iter() {
+            for (status, description) in statuses.iter_mut() {
+                if status.get_id() == *status_id {
+                    if let Some(allowed) = description
+                        .allowed
+                        .iter()
+                        .find(|allowed| allowed.description() == *state)
+                    {
+                        description.current = allowed.clone();
+                    }
+                }
+            }
+        }
+        statuses
+    }
+
+    /// A method to compose the game report from the session record at the
+    /// provided time.
+    ///
+    /// The game starts at the most recent occurrence of the start event, or
+    /// at the start of the session if the start event has not occurred (or
+    /// was not specified). Only the events, warnings and errors after the
+    /// start of the game are included in the report. The team name is the
+    /// most recent response to the team name prompt, even before the start.
+    ///
+    pub fn compose<F>(
+        &self,
+        settings: &ReportSettings,
+        statuses: FullStatus,
+        now: time::Tm,
+        mut get_description: F,
+    ) -> GameReport
+    where
+        F: FnMut(&ItemId) -> ItemDescription,
+    {
+        // Find the start of the game
+        let start = settings
+            .start
+            .and_then(|start_id| {
+                self.events
+                    .iter()
+                    .rev()
+                    .find(|&(_, event)| event.get_id() == start_id)
+            })
+            .map(|&(time, _)| time)
+            .unwrap_or(self.start);
+
+        // Collect the events after the start of the game
+        let events: Vec<(time::Duration, &ItemPair)> = self
+            .events
+            .iter()
+            .filter(|&(time, _)| *time >= start)
+            .map(|(time, event)| (*time - start, event))
+            .collect();
+
+        // Find the first occurrence of each milestone (or list every event)
+        let milestones = if settings.milestones.is_empty() {
+            events
+                .iter()
+                .map(|&(offset, event)| (event.clone(), Some(offset)))
+                .collect()
+        } else {
+            settings
+                .milestones
+                .iter()
+                .map(|milestone| {
+                    match events
+                        .iter()
+                        .find(|&(_, event)| event.get_id() == *milestone)
+                    {
+                        Some(&(offset, event)) => (event.clone(), Some(offset)),
+
+                        // Look up the description of a milestone which was not reached
+                        None => (
+                            ItemPair::from_item(*milestone, get_description(milestone)),
+                            None,
+                        ),
+                    }
+                })
+                .collect()
+        };
+
+        // Collect every hint that was given
+        let hints = events
+            .iter()
+            .filter(|&(_, event)| settings.hints.contains(&event.get_id()))
+            .map(|&(offset, event)| (event.clone(), offset))
+            .collect();
+
+        // Sort the statuses by id
+        let mut statuses: Vec<(ItemPair, ItemPair)> = statuses
+            .into_iter()
+            .map(|(status, description)| (status, description.current))
+            .collect();
+        statuses.sort_unstable_by_key(|(status, _)| status.id());
+
+        // Collect the warnings and errors after the start of the game
+        let problems = self
+            .problems
+            .iter()
+            .filter(|&(time, _, _)| *time >= start)
+            .map(|(time, kind, message)| (*time - start, *kind, message.clone()))
+            .collect();
+
+        // Find the most recent team name
+        let team = settings.team.and_then(|team_id| {
+            self.strings
+                .iter()
+                .rev()
+                .find(|&(event, _)| *event == team_id)
+                .map(|(_, team)| team.clone())
+        });
+
+        // Return the completed report
+        GameReport {
+            team,
+            start,
+            end: now,
+            milestones,
+            hints,
+            statuses,
+            problems,
+        }
+    }
+}
+
+/// A structure to hold the completed game report
+///
+pub struct GameReport {
+    team: Option<String>, // the name of the team, if provided
+    start: time::Tm,      // the start of the game
+    end: time::Tm,        // the time the report was composed
+    milestones: Vec<(ItemPair, Option<time::Duration>)>, // the milestones and their time, if reached
+    hints: Vec<(ItemPair, time::Duration)>,              // the hints and their time
+    statuses: Vec<(ItemPair, ItemPair)>,                 // the statuses and their final states
+    problems: Vec<(time::Duration, &'static str, String)>, // the time, kind and message of each warning and error
+}
+
+// Implement the export features of the game report
+impl GameReport {
+    /// A method to summarize the report as the result of the session. If
+    /// the milestones were not specified, the result has no milestones and
+    /// is not complete. Otherwise, the game is complete when every milestone
+    /// was reached.
+    ///
+    pub fn result(&self, settings: &ReportSettings) -> SessionResult {
+        // Convert the time of each milestone, if specified
+        let milestones: Vec<(ItemId, Option<Duration>)> = if settings.milestones.is_empty() {
+            Vec::new()
+        } else {
+            self.milestones
+                .iter()
+                .map(|(milestone, offset)| {
+                    (
+                        milestone.get_id(),
+                        offset.and_then(|offset| offset.to_std().ok()),
+                    )
+                })
+                .collect()
+        };
+
+        // The completion time is the time of the last milestone, if all were reached
+        let completion = if milestones.is_empty() {
+            None
+        } else {
+            milestones
+                .iter()
+                .map(|&(_, time)| time)
+                .collect::<Option<Vec<Duration>>>()
+                .and_then(|times| times.into_iter().max())
+        };
+
+        // Return the result
+        SessionResult {
+            start: format_time(&self.start),
+            team: self.team.clone(),
+            completion,
+            milestones,
+        }
+    }
+
+    /// A method to export the report as comma-separated values. Each row
+    /// lists the section, the time relative to the start of the game, the
+    /// item id and description, and any further detail.
+    ///
+    pub fn to_csv(&self) -> String {
+        // Add the header and the summary
+        let mut rows = vec![
+            vec![
+                "Section".to_string(),
+                "Time".to_string(),
+                "Id".to_string(),
+                "Description".to_string(),
+                "Detail".to_string(),
+            ],
+            GameReport::csv_row(
+                "Summary",
+                None,
+                None,
+                "Game Start",
+                &format_time(&self.start),
+            ),
+            GameReport::csv_row("Summary", None, None, "Game End", &format_time(&self.end)),
+            GameReport::csv_row(
+                "Summary",
+                Some(self.end - self.start),
+                None,
+                "Total Time",
+                "",
+            ),
+            GameReport::csv_row(
+                "Summary",
+                None,
+                None,
+                "Hints Used",
+                &self.hints.len().to_string(),
+            ),
+        ];
+
+        // Add the team name, if provided
+        if let Some(ref team) = self.team {
+            rows.push(GameReport::csv_row("Summary", None, None, "Team", team));
+        }
+
+        // Add the milestones and the hints
+        for (milestone, offset) in self.milestones.iter() {
+            let detail = if offset.is_some() { "" } else { "Not Reached" };
+            rows.push(GameReport::csv_row(
+                "Milestone",
+                *offset,
+                Some(milestone),
+                &milestone.description(),
+                detail,
+            ));
+        }
+        for (hint, offset) in self.hints.iter() {
+            rows.push(GameReport::csv_row(
+                "Hint",
+                Some(*offset),
+                Some(hint),
+                &hint.description(),
+                "",
+            ));
+        }
+
+        // Add the final statuses
+        for (status, state) in self.statuses.iter() {
+            rows.push(GameReport::csv_row(
+                "Status",
+                None,
+                Some(status),
+                &status.description(),
+                &state.description(),
+            ));
+        }
+
+        // Add the warnings and errors
+        for (offset, kind, message) in self.problems.iter() {
+            rows.push(GameReport::csv_row(kind, Some(*offset), None, "", message));
+        }
+
+        // Join the rows, escaping each field
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|field| escape_csv(field))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+            + "\n"
+    }
+
+    /// A method to export the report as a standalone HTML document
+    ///
+    pub fn to_html(&self) -> String {
+        // Add the summary
+        let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Game Report</title>\n</head>\n<body>\n<h1>Game Report</h1>\n<table>\n");
+        html += &html_row(&["Game Start", &format_time(&self.start)]);
+        html += &html_row(&["Game End", &format_time(&self.end)]);
+        html += &html_row(&["Total Time", &format_duration(self.end - self.start)]);
+        html += &html_row(&["Hints Used", &self.hints.len().to_string()]);
+        if let Some(ref team) = self.team {
+            html += &html_row(&["Team", team]);
+        }
+        html += "</table>\n";
+
+        // Add the milestones
+        html += "<h2>Milestones</h2>\n<table>\n";
+        for (milestone, offset) in self.milestones.iter() {
+            let time = match offset {
+                Some(offset) => format_duration(*offset),
+                None => "Not Reached".to_string(),
+            };
+            html += &html_row(&[&time, &milestone.description()]);
+        }
+        html += "</table>\n";
+
+        // Add the hints
+        html += "<h2>Hints</h2>\n<table>\n";
+        for (hint, offset) in self.hints.iter() {
+            html += &html_row(&[&format_duration(*offset), &hint.description()]);
+        }
+        html += "</table>\n";
+
+        // Add the final statuses
+        html += "<h2>Final Statuses</h2>\n<table>\n";
+        for (status, state) in self.statuses.iter() {
+            html += &html_row(&[&status.description(), &state.description()]);
+        }
+        html += "</table>\n";
+
+        // Add the warnings and errors
+        html += "<h2>Warnings And Errors</h2>\n<table>\n";
+        for (offset, kind, message) in self.problems.iter() {
+            html += &html_row(&[&format_duration(*offset), kind, message]);
+        }
+        html += "</table>\n</body>\n</html>\n";
+        html
+    }
+
+    /// An internal function to compose one row of the CSV export
+    ///
+    fn csv_row(
+        section: &str,
+        offset: Option<time::Duration>,
+        item: Option<&ItemPair>,
+        description: &str,
+        detail: &str,
+    ) -> Vec<String> {
+        vec![
+            section.to_string(),
+            offset.map(format_duration).unwrap_or_default(),
+            item.map(|item| item.id().to_string()).unwrap_or_default(),
+            description.to_string(),
+            detail.to_string(),
+        ]
+    }
+}
+
+/// A helper function to read a time of day from the structured game log (as
+/// a local time)
+///
+fn parse_time(text: &str) -> Option<time::Tm> {
+    time::strptime(text, "%Y-%m-%dT%H:%M:%S%z")
+        .ok()
+        .map(|time| time::at(time.to_timespec()))
+}
+
+/// A helper function to format a time of day for the report
+///
+fn format_time(time: &time::Tm) -> String {
+    time.strftime("%Y-%m-%d %H:%M:%S")
+        .map(|time| time.to_string())
+        .unwrap_or_else(|_| time.asctime().to_string())
+}
+
+/// A helper function to format a duration as hours, minutes and seconds
+///
+fn format_duration(duration: time::Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+/// A helper function to quote a CSV field, if necessary
+///
+fn escape_csv(field: &str) -> String {
+    if field.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A helper function to compose one row of an HTML table, escaping each cell
+///
+fn html_row(cells: &[&str]) -> String {
+    let mut row = String::from("<tr>");
+    for cell in cells {
+        row += &format!(
+            "<td>{}</td>",
+            cell.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        );
+    }
+    row + "</tr>\n"
+}
+
+// Tests of the report module
+#[cfg(test)]
+mod tests {
+    use super::super::{Hidden, StatusDescription};
+    use super::*;
+
+    // Compose a fixed time (in seconds after the first session)
+    fn at(seconds: i64) -> time::Tm {
+        time::at_utc(time::Timespec::new(1_500_000_000 + seconds, 0))
+    }
+
+    // Compose one line of a structured game log at a fixed time
+    fn line(
+        session: i64,
+        seconds: i64,
+        kind: &str,
+        event: Option<&ItemPair>,
+        data: serde_json::Value,
+        simulated: bool,
+    ) -> String {
+        serde_json::json!({
+            "time": at(seconds).rfc3339().to_string(),
+            "session": at(session).rfc3339().to_string(),
+            "kind": kind,
+            "event_id": event.map(|event| event.id()),
+            "description": event.map(|event| event.description()),
+            "data": data,
+            "simulated": simulated,
+        })
+        .to_string()
+    }
+
+    // Test rebuilding, composing and exporting a game report
+    #[test]
+    fn compose_report() {
+        // Import libraries for testing
+        use serde_json::Value::Null;
+
+        // Log the session events (with a simulated event, a hint sent with
+        // two pieces of data, and a hint in a later session)
+        let intro = ItemPair::new(1, "Intro", Hidden).unwrap();
+        let start = ItemPair::new(2, "Start Game", Hidden).unwrap();
+        let key = ItemPair::new(3, "Found The Key", Hidden).unwrap();
+        let hint = ItemPair::new(4, "Hint, Vault", Hidden).unwrap();
+        let door = ItemPair::new(20, "Door", Hidden).unwrap();
+        let log = vec![
+            line(0, 0, "session_start", None, Null, false),
+            line(0, 10, "current", Some(&intro), Null, false),
+            line(0, 20, "warning", None, "Too early".into(), false),
+            line(0, 60, "broadcast", Some(&start), Null, false),
+            line(0, 300, "broadcast", Some(&hint), vec![1, 2].into(), false),
+            line(0, 400, "current", Some(&hint), Null, true),
+            "unreadable line".to_string(),
+            line(0, 725, "current", Some(&key), Null, false),
+            line(0, 740, "status", Some(&door), "Open".into(), false),
+            line(0, 800, "error", None, "Lost <connection>".into(), false),
+            line(0, 900, "session_end", None, Null, false),
+            line(1000, 1000, "session_start", None, Null, false),
+            line(1000, 1010, "broadcast", Some(&hint), Null, false),
+        ]
+        .join("\n");
+
+        // Rebuild the first session and verify the most recent one is chosen otherwise
+        let record = SessionRecord::from_log(&log, Some(&at(0).rfc3339().to_string())).unwrap();
+        assert_eq!(record.end().to_timespec(), at(900).to_timespec());
+        assert!(SessionRecord::from_log(&log, Some("missing")).is_none());
+        let latest = SessionRecord::from_log(&log, None).unwrap();
+        assert_eq!(latest.end().to_timespec(), at(1010).to_timespec());
+
+        // Rebuild the final status from the default state
+        let closed = ItemPair::new(21, "Closed", Hidden).unwrap();
+        let open = ItemPair::new(22, "Open", Hidden).unwrap();
+        let mut statuses = FullStatus::default();
+        statuses.insert(
+            door,
+            StatusDescription {
+                current: closed.clone(),
+                allowed: vec![closed, open],
+                transitions: None,
+                is_derived: false,
+            },
+        );
+        let statuses = record.final_statuses(statuses);
+
+        // Compose the report with one reached and one missed milestone
+        let settings = ReportSettings {
+            start: Some(start.get_id()),
+            milestones: vec![key.get_id(), ItemId::new(5).unwrap()],
+            hints: vec![hint.get_id()],
+            team: None,
+            end: None,
+        };
+        let report = record.compose(&settings, statuses, at(3660), |_| {
+            ItemDescription::new("Opened The Vault", Hidden)
+        });
+
+        // Verify the CSV export
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Section,Time,Id,Description,Detail");
+        assert_eq!(lines[3], "Summary,1:00:00,,Total Time,");
+        assert_eq!(lines[4], "Summary,,,Hints Used,1");
+        assert_eq!(lines[5], "Milestone,0:11:05,3,Found The Key,");
+        assert_eq!(lines[6], "Milestone,,5,Opened The Vault,Not Reached");
+        assert_eq!(lines[7], "Hint,0:04:00,4,\"Hint, Vault\",");
+        assert_eq!(lines[8], "Status,,20,Door,Open");
+        assert_eq!(lines[9], "Error,0:12:20,,,Lost <connection>");
+        assert_eq!(lines.len(), 10);
+
+        // Verify that the HTML export is escaped
+        let html = report.to_html();
+        assert!(html.contains("<td>0:12:20</td><td>Error</td><td>Lost &lt;connection&gt;</td>"));
+        assert!(!html.contains("Too early"));
+
+        // Verify that the result is incomplete with a missed milestone
+        let result = report.result(&settings);
+        assert_eq!(
+            result.milestones,
+            vec![
+                (key.get_id(), Some(Duration::from_secs(665))),
+                (ItemId::new(5).unwrap(), None)
+            ]
+        );
+        assert_eq!(result.completion, None);
+    }
+}
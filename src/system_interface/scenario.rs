@@ -0,0 +1,532 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A testing module to run a configuration against a scenario without the
+//! user interface. The scenario loads a configuration, injects device events
+//! at specific times, and checks the resulting broadcasts, statuses, and
+//! scene against the expected values.
+//!
+//! The scenario runs on a manual clock, so a scenario which covers an hour
+//! of play completes in a fraction of a second. Scenarios are written in
+//! YAML, in the same style as the configuration files:
+//!
+//! ```yaml
+//! ---
+//! config: ../../default.mnv # relative to the scenario file
+//! steps:
+//!   - time: 0            # time since the configuration was loaded
+//!     inject: []         # device events to trigger at this time
+//!     broadcasts:        # the broadcasts since the last step (optional)
+//!       - event: {id: 100}
+//!         data: ~
+//!     statuses:          # the expected state of these statuses
+//!       - status_id: {id: 20}
+//!         state: {id: 21}
+//!     scene: {id: 100}   # the expected current scene (optional)
+//!   - time: "1:30"       # times may also be written as minutes and seconds
+//! ```
+//!
+//! Use the test_scenario! macro to run a scenario as part of a test.
+
+// Import the relevant structures into the correct namespace
+use super::event_handler::{ComingEvent, EventHandler};
+use super::{
+    Clock, ClockMode, EventUpdate, GeneralUpdate, GeneralUpdateType, InterfaceUpdate, ItemId,
+};
+
+// Import standard library features
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// Import the failure features
+use failure::Error;
+
+// Import YAML processing library
+extern crate serde_yaml;
+
+// Define module constants
+const POLLING_TIME: u64 = 1; // the time to wait for an update while the queue is busy in ms
+
+/// A structure to define a scenario to run against a configuration.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    config: PathBuf, // the location of the configuration, relative to the scenario
+    steps: Vec<ScenarioStep>, // the steps of the scenario, in order of increasing time
+    #[serde(skip)]
+    directory: Option<PathBuf>, // the directory of the scenario file, if loaded from a file
+}
+
+/// A structure to define one step of a scenario.
+///
+/// The broadcasts are compared with all the broadcasts since the previous
+/// step (order matters). If broadcasts are not specified, they are ignored.
+///
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ScenarioStep {
+    #[serde(
+        serialize_with = "serialize_time",
+        deserialize_with = "deserialize_time"
+    )]
+    time: Duration, // the time of the step, relative to the start of the scenario
+    #[serde(default)]
+    inject: Vec<ItemId>, // the device events to inject at this time
+    broadcasts: Option<Vec<ScenarioBroadcast>>, // the broadcasts expected since the last step
+    #[serde(default)]
+    statuses: Vec<ScenarioStatus>, // the states expected for these statuses
+    scene: Option<ItemId>,                      // the scene expected at this time
+}
+
+/// An enum to hold the ways a step time may be written in a scenario file.
+///
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum ScenarioTime {
+    Seconds(f64),       // a plain number of seconds
+    Text(String),       // minutes and seconds, written as mm:ss
+    Duration(Duration), // the full duration format, {secs: x, nanos: y}
+}
+
+/// A helper function to write a step time as a plain number of seconds.
+///
+fn serialize_time<S>(time: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_f64(time.as_secs_f64())
+}
+
+/// A helper function to read a step time as a plain number of seconds, as
+/// minutes and seconds (mm:ss), or in the full duration format.
+///
+fn deserialize_time<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // Parse the time in whichever format it was written
+    use serde::de::Error;
+    let seconds = match serde::Deserialize::deserialize(deserializer)? {
+        ScenarioTime::Seconds(seconds) => seconds,
+        ScenarioTime::Duration(duration) => return Ok(duration),
+        ScenarioTime::Text(text) => {
+            // Split the minutes from the seconds
+            let mut parts = text.trim().splitn(2, ':');
+            let first = parts.next().unwrap_or("");
+            match parts.next() {
+                // Combine the minutes and seconds
+                Some(second) => {
+                    let minutes: u64 = first.trim().parse().map_err(|_| {
+                        D::Error::custom(format!("Invalid Minutes In Step Time: {}", text))
+                    })?;
+                    let seconds: f64 = second.trim().parse().map_err(|_| {
+                        D::Error::custom(format!("Invalid Seconds In Step Time: {}", text))
+                    })?;
+                    if !(0.0..60.0).contains(&seconds) {
+                        return Err(D::Error::custom(format!(
+                            "Invalid Seconds In Step Time: {}",
+                            text
+                        )));
+                    }
+                    (minutes * 60) as f64 + seconds
+                }
+
+                // Otherwise, accept plain seconds written as text
+                None => first
+                    .trim()
+                    .parse()
+                    .map_err(|_| D::Error::custom(format!("Invalid Step Time: {}", text)))?,
+            }
+        }
+    };
+
+    // Reject negative or invalid times
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(D::Error::custom(format!("Invalid Step Time: {}", seconds)));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// A structure to define a broadcast that is expected (or was received).
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ScenarioBroadcast {
+    event: ItemId,     // the id of the broadcast event
+    data: Option<u32>, // the data broadcast with the event, if any
+}
+
+/// A structure to define the expected state of a status.
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ScenarioStatus {
+    status_id: ItemId, // the id of the status
+    state: ItemId,     // the expected state of the status
+}
+
+/// A structure to describe a difference between the scenario and the
+/// behavior of the configuration.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScenarioMismatch {
+    pub time: Duration,   // the time of the step which failed
+    pub check: String,    // a description of the failed check
+    pub expected: String, // the expected value
+    pub output: String,   // the value produced by the configuration
+}
+
+// Implement key features of the scenario
+impl Scenario {
+    /// A function to load a scenario from the provided file.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise an error if the scenario file could not be
+    /// opened or could not be parsed.
+    ///
+    pub fn from_file(scenario_path: PathBuf) -> Result<Scenario, Error> {
+        // Try to open the scenario file
+        let scenario_file = match File::open(&scenario_path) {
+            Ok(file) => file,
+            Err(error) => return Err(format_err!("Unable to open scenario file: {}", error)),
+        };
+
+        // Try to parse the scenario
+        let mut scenario: Scenario = match serde_yaml::from_reader(scenario_file) {
+            Ok(scenario) => scenario,
+            Err(error) => return Err(format_err!("Unable to parse scenario file: {}", error)),
+        };
+
+        // Save the directory of the scenario
+        scenario.directory = scenario_path.parent().map(|path| path.to_path_buf());
+        Ok(scenario)
+    }
+
+    /// A method to run the scenario and return any mismatches between the
+    /// expected and actual behavior of the configuration. An empty result
+    /// indicates that the scenario passed.
+    ///
+    /// # Errors
+    ///
+    /// If the configuration fails to load, this method returns a single
+    /// mismatch describing the failure.
+    ///
+    pub fn run(&self) -> Vec<ScenarioMismatch> {
+        // Locate the configuration relative to the scenario
+        let config_path = match self.directory {
+            Some(ref directory) => directory.join(&self.config),
+            None => self.config.clone(),
+        };
+
+        // Create the runner for the configuration
+        let mut runner = match ScenarioRunner::new(config_path.clone()) {
+            Ok(runner) => runner,
+            Err(error) => {
+                return vec![ScenarioMismatch {
+                    time: Duration::from_secs(0),
+                    check: "Load Configuration".to_string(),
+                    expected: format!("{:?}", config_path),
+                    output: format!("{}", error),
+                }];
+            }
+        };
+
+        // Run each step and collect the mismatches
+        let mut mismatches = Vec::new();
+        for step in self.steps.iter() {
+            mismatches.append(&mut runner.run_step(step));
+        }
+        mismatches
+    }
+}
+
+/// An internal structure to drive an event handler through a scenario.
+///
+struct ScenarioRunner {
+    handler: EventHandler,         // the event handler under test
+    general_update: GeneralUpdate, // the line to inject events
+    general_receive: mpsc::Receiver<GeneralUpdateType>, // the line to receive all updates
+    _interface_receive: mpsc::Receiver<InterfaceUpdate>, // the (unused) interface line
+    clock: Clock,                  // the manual clock for the scenario
+    start_time: Instant,           // the start time of the scenario
+    coming_events: Vec<ComingEvent>, // the latest list of coming events
+    broadcasts: Vec<ScenarioBroadcast>, // the broadcasts since the last step
+}
+
+// Implement key features of the scenario runner
+impl ScenarioRunner {
+    /// A function to load the configuration and process any startup events.
+    ///
+    fn new(config_path: PathBuf) -> Result<ScenarioRunner, Error> {
+        // Create the manual clock and the update lines
+        let clock = Clock::new_with_mode(ClockMode::Manual);
+        let (general_update, general_receive) = GeneralUpdate::new();
+        let (interface_send, interface_receive) = mpsc::channel();
+
+        // Load the configuration (without the backup, to leave any live game untouched)
+        let handler = EventHandler::new_without_backup(
+            config_path,
+            general_update.clone(),
+            interface_send,
+            clock.clone(),
+        )?;
+
+        // Create the runner and process the startup events
+        let mut runner = ScenarioRunner {
+            handler,
+            general_update,
+            general_receive,
+            _interface_receive: interface_receive,
+            start_time: clock.now(),
+            clock,
+            coming_events: Vec::new(),
+            broadcasts: Vec::new(),
+        };
+        runner.process_updates();
+        Ok(runner)
+    }
+
+    /// A method to run one step of the scenario and return any mismatches.
+    ///
+    fn run_step(&mut self, step: &ScenarioStep) -> Vec<ScenarioMismatch> {
+        // Advance to the time of the step and inject the device events
+        self.advance_to(step.time);
+        for event in step.inject.iter() {
+            self.general_update.send_event(*event, true, true);
+        }
+        self.process_updates();
+
+        // Compare the broadcasts since the last step
+        let mut mismatches = Vec::new();
+        let broadcasts: Vec<ScenarioBroadcast> = self.broadcasts.drain(..).collect();
+        if let Some(ref expected) = step.broadcasts {
+            if expected != &broadcasts {
+                mismatches.push(ScenarioMismatch {
+                    time: step.time,
+                    check: "Broadcasts".to_string(),
+                    expected: format!("{:?}", expected),
+                    output: format!("{:?}", broadcasts),
+                });
+            }
+        }
+
+        // Compare the states of the statuses
+        let full_status = self.handler.get_full_status();
+        for status in step.statuses.iter() {
+            // Find the current state of the status
+            let state = full_status
+                .iter()
+                .find(|&(pair, _)| pair.get_id() == status.status_id)
+                .map(|(_, description)| description.current.get_id());
+
+            // Compare it with the expected state
+            if state != Some(status.state) {
+                mismatches.push(ScenarioMismatch {
+                    time: step.time,
+                    check: format!("Status {}", status.status_id),
+                    expected: format!("{:?}", Some(status.state)),
+                    output: format!("{:?}", state),
+                });
+            }
+        }
+
+        // Compare the current scene
+        if let Some(scene) = step.scene {
+            let current = self.handler.get_current_scene().get_id();
+            if current != scene {
+                mismatches.push(ScenarioMismatch {
+                    time: step.time,
+                    check: "Scene".to_string(),
+                    expected: format!("{:?}", scene),
+                    output: format!("{:?}", current),
+                });
+            }
+        }
+
+        // Return the mismatches
+        mismatches
+    }
+
+    /// A method to advance the clock to the provided time in the scenario.
+    ///
+    /// The clock stops at each coming event along the way so that any events
+    /// queued by that event are timed from the correct moment.
+    ///
+    fn advance_to(&mut self, time: Duration) {
+        // Advance to each coming event before the target time
+        let target = self.start_time + time;
+        loop {
+            // Find the next coming event
+            let next = self
+                .coming_events
+                .iter()
+                .map(|event| event.start_time + event.delay)
+                .min();
+
+            // Stop if there are no more coming events before the target
+            let next = match next {
+                Some(next) if next <= target => next,
+                _ => break,
+            };
+
+            // Advance the clock to the event and process the result
+            let previous = self.coming_events.clone();
+            let now = self.clock.now();
+            if next > now {
+                self.clock.advance(next - now);
+            }
+            self.process_updates();
+
+            // Stop if the queue did not respond (to avoid an endless loop)
+            if previous == self.coming_events {
+                break;
+            }
+        }
+
+        // Advance the clock the rest of the way
+        let now = self.clock.now();
+        if target > now {
+            self.clock.advance(target - now);
+        }
+        self.process_updates();
+    }
+
+    /// A method to process all updates from the event handler until the
+    /// system is idle.
+    ///
+    /// The system is idle once the event queue is idle and every update it
+    /// sent has been processed. The event handler itself works synchronously,
+    /// so the queue is the only source of updates that may still be coming.
+    ///
+    fn process_updates(&mut self) {
+        loop {
+            // Check the queue before the line, so that no update can be missed
+            let is_idle = self.handler.is_idle();
+
+            // Process the next update, waiting briefly only if the queue is busy
+            let update = if is_idle {
+                self.general_receive.try_recv().ok()
+            } else {
+                self.general_receive
+                    .recv_timeout(Duration::from_millis(POLLING_TIME))
+                    .ok()
+            };
+
+            // Stop once the queue is idle and the line is empty
+            let update = match update {
+                Some(update) => update,
+                None if is_idle => break,
+                None => continue,
+            };
+
+            match update {
+                // Capture any broadcasts (normally sent on by the logger)
+                GeneralUpdateType::Update(EventUpdate::Broadcast(event, data)) => {
+                    if data.is_empty() {
+                        self.broadcasts.push(ScenarioBroadcast {
+                            event: event.get_id(),
+                            data: None,
+                        });
+                    }
+                    for number in data {
+                        self.broadcasts.push(ScenarioBroadcast {
+                            event: event.get_id(),
+                            data: Some(number),
+                        });
+                    }
+                }
+
+                // Save the current list of coming events
+                GeneralUpdateType::ComingEvents(events) => {
+                    self.coming_events = events;
+                }
+
+                // Process any triggered events
+                GeneralUpdateType::ProcessEvent {
+                    event,
+                    check_scene,
+                    broadcast,
+                    ..
+                } => {
+                    self.handler.process_event(&event, check_scene, broadcast);
+                }
+
+                // Ignore all other updates
+                _ => (),
+            }
+        }
+    }
+}
+
+// Tests of the scenario module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run all the example scenarios
+    #[test]
+    fn example_scenarios() {
+        // Import standard library features
+        use std::fs;
+
+        // Run each scenario in the examples folder
+        for entry in fs::read_dir("examples/scenarios").unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|extension| extension.to_str()) == Some("yaml") {
+                test_scenario!(path);
+            }
+        }
+    }
+
+    // Check that a mismatch is reported
+    #[test]
+    fn report_mismatch() {
+        // Expect the wrong scene at the start of the default configuration
+        let scenario: Scenario = serde_yaml::from_str(
+            "config: default.mnv\nsteps:\n  - time: 0\n    scene: {id: 200}\n",
+        )
+        .unwrap();
+
+        // Verify that the scene mismatch is found
+        let mismatches = scenario.run();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].check, "Scene");
+    }
+
+    // Check that step times are read in each supported format
+    #[test]
+    fn step_times() {
+        // Read a step time from the provided text
+        fn time(text: &str) -> Option<Duration> {
+            serde_yaml::from_str::<ScenarioStep>(&format!("time: {}\n", text))
+                .ok()
+                .map(|step| step.time)
+        }
+
+        // Verify the plain seconds, mm:ss, and full duration formats
+        assert_eq!(time("90"), Some(Duration::from_secs(90)));
+        assert_eq!(time("2.5"), Some(Duration::from_millis(2500)));
+        assert_eq!(time("\"1:30\""), Some(Duration::from_secs(90)));
+        assert_eq!(time("\"0:02.5\""), Some(Duration::from_millis(2500)));
+        assert_eq!(time("{secs: 90, nanos: 0}"), Some(Duration::from_secs(90)));
+
+        // Verify that invalid times are rejected
+        assert_eq!(time("-1"), None);
+        assert_eq!(time("\"1:75\""), None);
+        assert_eq!(time("\"soon\""), None);
+    }
+}
@@ -0,0 +1,339 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to keep the results of past sessions and to compile statistics
+//! across them.
+//!
+//! Whenever a game ends (at the end event of the game report, when a new game
+//! log is started or when the program closes), the result of the game (the
+//! team name, the completion time and the time of each milestone) is stored
+//! in a results file next to the configuration. The results provide a leaderboard
+//! of the completed games, the average solve time of each puzzle (the time
+//! from one milestone to the next) and the failure rate of each puzzle (the
+//! share of the teams which reached the previous milestone, but not this one).
+
+// Import the relevant structures into the correct namespace
+use super::{EventUpdate, GeneralUpdate, ItemDescription, ItemId, ItemPair};
+
+// Import standard library features
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Import the failure features
+use failure::Error;
+
+// Import YAML processing library
+extern crate serde_yaml;
+
+/// A structure to hold the result of one session
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionResult {
+    pub start: String, // the local time at the start of the game (identifies the session)
+    pub team: Option<String>, // the name of the team, if provided
+    pub completion: Option<Duration>, // the time to reach every milestone, if all were reached
+    pub milestones: Vec<(ItemId, Option<Duration>)>, // each milestone and the time it was reached, if reached
+}
+
+/// A structure to hold one entry of the leaderboard
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub team: Option<String>, // the name of the team, if provided
+    pub start: String,        // the local time at the start of the game
+    pub completion: Duration, // the time to complete the game
+}
+
+/// A structure to hold the statistics of one puzzle (the part of the game
+/// which ends at a milestone)
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PuzzleStatistics {
+    pub milestone: ItemPair, // the milestone which marks the end of the puzzle
+    pub average: Option<Duration>, // the average solve time, if the puzzle was ever solved
+    pub attempts: usize,     // the number of teams which reached the puzzle
+    pub failures: usize,     // the number of those teams which did not solve it
+}
+
+/// A structure to summarize the statistics of every session
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatisticsSummary {
+    pub sessions: usize,                    // the number of sessions recorded
+    pub leaderboard: Vec<LeaderboardEntry>, // the fastest completed games, fastest first
+    pub puzzles: Vec<PuzzleStatistics>,     // the puzzles, highest failure rate first
+}
+
+/// A structure to hold the results of past sessions and the file where they
+/// are stored.
+///
+pub struct Statistics {
+    path: PathBuf,                 // the location of the results file
+    results: Vec<SessionResult>,   // the results in the order they were recorded
+    general_update: GeneralUpdate, // the update line for warnings and errors
+}
+
+// Implement key features of the statistics
+impl Statistics {
+    /// A function to load the results stored at the provided location. The
+    /// file is created when the first result is recorded.
+    ///
+    /// # Errors
+    ///
+    /// This function will raise a warning if the existing results file could
+    /// not be read. Like all EventHandler functions and methods, this
+    /// function will fail gracefully by starting without any results.
+    ///
+    pub fn new(general_update: GeneralUpdate, path: PathBuf) -> Statistics {
+        // Read the existing results, if they exist
+        let mut results = Vec::new();
+        if path.exists() {
+            match fs::read_to_string(&path) {
+                Ok(results_string) => match serde_yaml::from_str(results_string.as_str()) {
+                    Ok(existing) => results = existing,
+                    Err(error) => {
+                        update!(warn &general_update => "Unable To Parse Session Results: {}", error)
+                    }
+                },
+                Err(error) => {
+                    update!(warn &general_update => "Unable To Read Session Results: {}", error)
+                }
+            }
+        }
+
+        // Return the new statistics
+        Statistics {
+            path,
+            results,
+            general_update,
+        }
+    }
+
+    /// A method to record the result of a game and save the results file.
+    /// A result with the same start time replaces the earlier result (i.e.
+    /// when the same game is recorded more than once).
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if it is unable to write the results
+    /// file. Like all EventHandler functions and methods, this method will
+    /// fail gracefully by notifying of errors on the update line.
+    ///
+    pub fn add_result(&mut self, result: SessionResult) {
+        // Replace or add the result
+        match self
+            .results
+            .iter_mut()
+            .find(|existing| existing.start == result.start)
+        {
+            Some(existing) => *existing = result,
+            None => self.results.push(result),
+        }
+
+        // Save the results to the file
+        if let Err(error) = self.save() {
+            update!(err &self.general_update => "Unable To Save Session Results: {}", error);
+        }
+    }
+
+    /// A method to return the provided number of the fastest completion
+    /// times, fastest first
+    ///
+    pub fn best_times(&self, count: usize) -> Vec<Duration> {
+        self.leaderboard(count)
+            .iter()
+            .map(|entry| entry.completion)
+            .collect()
+    }
+
+    /// A method to summarize the statistics of every session, with the
+    /// provided number of leaderboard entries.
+    ///
+    pub fn summary<F>(&self, count: usize, mut get_description: F) -> StatisticsSummary
+    where
+        F: FnMut(&ItemId) -> ItemDescription,
+    {
+        // Compile the statistics of each puzzle, in the order of the milestones
+        let mut puzzles: Vec<(ItemId, Duration, usize, PuzzleStatistics)> = Vec::new();
+        for result in self.results.iter() {
+            let mut previous = Some(Duration::from_secs(0));
+            for &(milestone, time) in result.milestones.iter() {
+                // Find or add the puzzle
+                let position = match puzzles.iter().position(|(id, ..)| *id == milestone) {
+                    Some(position) => position,
+                    None => {
+                        puzzles.push((
+                            milestone,
+                            Duration::from_secs(0),
+                            0,
+                            PuzzleStatistics {
+                                milestone: ItemPair::from_item(
+                                    milestone,
+                                    get_description(&milestone),
+                                ),
+                                average: None,
+                                attempts: 0,
+                                failures: 0,
+                            },
+                        ));
+                        puzzles.len() - 1
+                    }
+                };
+                let (_, ref mut total, ref mut solved, ref mut puzzle) = puzzles[position];
+
+                // Count the attempt if the team reached the puzzle
+                if let Some(start) = previous {
+                    puzzle.attempts += 1;
+                    match time {
+                        // Add the solve time (if the milestones were in order)
+                        Some(end) => {
+                            if let Some(solve_time) = end.checked_sub(start) {
+                                *total += solve_time;
+                                *solved += 1;
+                            }
+                        }
+
+                        // Otherwise, count the failure
+                        None => puzzle.failures += 1,
+                    }
+                }
+                previous = time;
+            }
+        }
+
+        // Calculate the averages and sort by the failure rate (stable for equal rates)
+        let mut puzzles: Vec<PuzzleStatistics> = puzzles
+            .drain(..)
+            .map(|(_, total, solved, mut puzzle)| {
+                if solved > 0 {
+                    puzzle.average = Some(total / solved as u32);
+                }
+                puzzle
+            })
+            .collect();
+        puzzles.sort_by(|first, second| {
+            (second.failures * first.attempts).cmp(&(first.failures * second.attempts))
+        });
+
+        // Return the summary
+        StatisticsSummary {
+            sessions: self.results.len(),
+            leaderboard: self.leaderboard(count),
+            puzzles,
+        }
+    }
+
+    /// An internal method to compose the provided number of entries of the
+    /// leaderboard from the completed sessions, fastest first
+    ///
+    fn leaderboard(&self, count: usize) -> Vec<LeaderboardEntry> {
+        let mut leaderboard: Vec<LeaderboardEntry> = self
+            .results
+            .iter()
+            .filter_map(|result| {
+                result.completion.map(|completion| LeaderboardEntry {
+                    team: result.team.clone(),
+                    start: result.start.clone(),
+                    completion,
+                })
+            })
+            .collect();
+        leaderboard.sort_by_key(|entry| entry.completion);
+        leaderboard.truncate(count);
+        leaderboard
+    }
+
+    /// An internal method to atomically replace the results file with the
+    /// current results
+    ///
+    fn save(&self) -> Result<(), Error> {
+        // Write the results to a temporary file and flush it to the disk
+        let results_string = serde_yaml::to_string(&self.results)?;
+        let temporary = self.path.with_extension("tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(results_string.as_bytes())?;
+        file.sync_all()?;
+
+        // Replace the results file with the temporary file
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+// Tests of the statistics module
+#[cfg(test)]
+mod tests {
+    use super::super::Hidden;
+    use super::*;
+
+    // Test the leaderboard and the puzzle statistics across sessions
+    #[test]
+    fn compile_statistics() {
+        // Create the statistics in the temporary directory
+        let (general_update, _receive) = GeneralUpdate::new();
+        let path =
+            std::env::temp_dir().join(format!("minerva_results_{}.yaml", std::process::id()));
+        let mut statistics = Statistics::new(general_update.clone(), path.clone());
+
+        // Record three sessions: two complete and one which failed the second puzzle
+        let first = ItemId::new(10).unwrap();
+        let second = ItemId::new(20).unwrap();
+        let minutes = |count: u64| Some(Duration::from_secs(count * 60));
+        let result =
+            |start: &str, team: &str, times: (Option<Duration>, Option<Duration>)| SessionResult {
+                start: start.to_string(),
+                team: Some(team.to_string()),
+                completion: times.1,
+                milestones: vec![(first, times.0), (second, times.1)],
+            };
+        statistics.add_result(result("1", "Slow", (minutes(20), minutes(50))));
+        statistics.add_result(result("2", "Stuck", (minutes(10), None)));
+        statistics.add_result(result("3", "Fast", (minutes(10), minutes(30))));
+
+        // Verify that a repeated session replaces the earlier result
+        statistics.add_result(result("3", "Fast", (minutes(10), minutes(25))));
+
+        // Verify that the results survive reopening the file
+        let statistics = Statistics::new(general_update, path.clone());
+        fs::remove_file(&path).unwrap_or(());
+        assert_eq!(
+            statistics.best_times(10),
+            vec![Duration::from_secs(25 * 60), Duration::from_secs(50 * 60)]
+        );
+
+        // Verify the leaderboard and the puzzles (highest failure rate first)
+        let summary = statistics.summary(1, |_| ItemDescription::new("Puzzle", Hidden));
+        assert_eq!(summary.sessions, 3);
+        assert_eq!(summary.leaderboard.len(), 1);
+        assert_eq!(summary.leaderboard[0].team, Some("Fast".to_string()));
+        assert_eq!(summary.puzzles[0].milestone.get_id(), second);
+        assert_eq!(summary.puzzles[0].attempts, 3);
+        assert_eq!(summary.puzzles[0].failures, 1);
+        assert_eq!(
+            summary.puzzles[0].average,
+            Some(Duration::from_secs(45 * 60 / 2))
+        );
+        assert_eq!(summary.puzzles[1].milestone.get_id(), first);
+        assert_eq!(summary.puzzles[1].failures, 0);
+        assert_eq!(
+            summary.puzzles[1].average,
+            Some(Duration::from_secs(40 * 60 / 3))
+        );
+    }
+}
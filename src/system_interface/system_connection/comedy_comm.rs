@@ -25,7 +25,7 @@
 //! may become completely incompatible in the furture.
 
 // Import the relevant structures into the correct namespace
-use super::{EventConnection, ItemId, COMM_ERROR, READ_ERROR};
+use super::{EventConnection, ItemId, Metrics, COMM_ERROR, READ_ERROR};
 
 // Import standard library modules and traits
 use std::io::{Cursor, Read, Write};
@@ -68,13 +68,23 @@ pub struct ComedyComm {
     outgoing: Vec<(ItemId, u32, u32)>,      // the outgoing event buffer
     last_ack: Option<Instant>, // Some(instant) if we are still waiting on ack from instant
     filter_events: Vec<(ItemId, u32, u32)>, // events to filter out
+    metrics: Metrics,          // the counts of activity of the program
+    label: String,             // the label of this connection in the metrics
 }
 
 // Implement key functionality for the CmdMessenger structure
 impl ComedyComm {
-    /// A function to create a new instance of the CmdMessenger
+    /// A function to create a new instance of the CmdMessenger. Any events
+    /// resent for lack of an acknowledgement are counted in the provided
+    /// metrics under the provided label.
     ///
-    pub fn new(path: &PathBuf, baud: usize, polling_rate: u64) -> Result<ComedyComm, Error> {
+    pub fn new(
+        path: &PathBuf,
+        baud: usize,
+        polling_rate: u64,
+        metrics: Metrics,
+        label: String,
+    ) -> Result<ComedyComm, Error> {
         // Connect to the underlying serial port
         let mut port = serial::open(path)?;
 
@@ -98,6 +108,8 @@ impl ComedyComm {
             outgoing: Vec::new(),
             last_ack: None,
             filter_events: Vec::new(),
+            metrics,
+            label,
         })
     }
 
@@ -202,6 +214,7 @@ impl EventConnection for ComedyComm {
                         events.push((ItemId::new_unchecked(COMM_ERROR), 0, 0));
 
                         // Copy and resend the current event
+                        self.metrics.count_retry(&self.label);
                         let (id, data1, data2) = self.outgoing[0];
                         self.write_event_now(id.clone(), data1.clone(), data2.clone())
                             .unwrap_or(());
@@ -402,7 +415,13 @@ mod tests {
         use std::time::Duration;
 
         // Create a new CmdMessenger instance
-        if let Some(mut cc) = ComedyComm::new(&PathBuf::from("/dev/ttyACM0"), 115200, 100) {
+        if let Some(mut cc) = ComedyComm::new(
+            &PathBuf::from("/dev/ttyACM0"),
+            115200,
+            100,
+            Metrics::new(),
+            String::from("test"),
+        ) {
             // Wait for the Arduino to boot
             thread::sleep(Duration::from_secs(3));
 
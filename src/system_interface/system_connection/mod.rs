@@ -33,6 +33,7 @@ use self::dmx_comm::{DmxComm, DmxFade, DmxMap};
 use self::zmq_comm::{EventToString, StringToEvent, ZmqBind, ZmqConnect, ZmqLookup};
 use super::event_handler::event::EventUpdate;
 use super::event_handler::item::{ItemId, COMM_ERROR, READ_ERROR};
+use super::metrics::Metrics;
 use super::GeneralUpdate;
 
 // Import standard library modules and traits
@@ -96,17 +97,44 @@ pub enum ConnectionType {
 
 // Implement key connection type features
 impl ConnectionType {
+    /// An internal method to compose a label for this connection in the
+    /// metrics (the connection type and its location)
+    ///
+    fn label(&self) -> String {
+        match self {
+            ConnectionType::ComedySerial { path, .. } => {
+                format!("comedy_serial {}", path.display())
+            }
+            ConnectionType::ZmqPrimary { send_path, .. } => {
+                format!("zmq_primary {}", send_path.display())
+            }
+            ConnectionType::ZmqSecondary { send_path, .. } => {
+                format!("zmq_secondary {}", send_path.display())
+            }
+            ConnectionType::ZmqTranslate { send_path, .. } => {
+                format!("zmq_translate {}", send_path.display())
+            }
+            ConnectionType::DmxSerial { path, .. } => format!("dmx_serial {}", path.display()),
+        }
+    }
+
     /// An internal method to create a Live Connection from this Connection
     /// Type. This method estahblishes the connection to the underlying system.
     /// If the connection fails, it will return the Error.
     ///
-    fn initialize(&self) -> Result<LiveConnection, Error> {
+    fn initialize(&self, metrics: &Metrics) -> Result<LiveConnection, Error> {
         // Switch between the different connection types
         match self {
             // Connect to a live version of the comedy serial port
             &ConnectionType::ComedySerial { ref path, ref baud } => {
-                // Create the new comedy connection
-                let connection = ComedyComm::new(path, baud.clone(), POLLING_RATE)?;
+                // Create the new comedy connection (which counts its own retries)
+                let connection = ComedyComm::new(
+                    path,
+                    baud.clone(),
+                    POLLING_RATE,
+                    metrics.clone(),
+                    self.label(),
+                )?;
                 Ok(LiveConnection::ComedySerial { connection })
             }
 
@@ -336,8 +364,8 @@ impl SystemConnection {
             let mut live_connections = Vec::new();
             for connection in conn_set {
                 // Attempt to initialize each connection
-                match connection.initialize() {
-                    Ok(conn) => live_connections.push(conn),
+                match connection.initialize(&self.general_update.metrics) {
+                    Ok(conn) => live_connections.push((connection.label(), conn)),
 
                     // If it fails, warn the user FIXME pass the error upstream
                     Err(_) => {
@@ -379,7 +407,7 @@ impl SystemConnection {
     /// An internal function to run a loop of the system connection
     ///
     fn run_loop(
-        mut connections: Vec<LiveConnection>,
+        mut connections: Vec<(String, LiveConnection)>,
         gen_update: GeneralUpdate,
         conn_recv: mpsc::Receiver<ConnectionUpdate>,
         identifier: ItemId,
@@ -391,7 +419,7 @@ impl SystemConnection {
 
             // Read all events from the system connections
             let mut events = Vec::new();
-            for connection in connections.iter_mut() {
+            for (_, connection) in connections.iter_mut() {
                 events.append(&mut connection.read_events());
             }
 
@@ -399,16 +427,18 @@ impl SystemConnection {
             for (id, game_id, data2) in events.drain(..) {
                 // If there was a read error, notify the system
                 if id == ItemId::new_unchecked(READ_ERROR) {
+                    gen_update.metrics.count_read_error();
                     update!(err &gen_update => "There Was A Read Error.");
 
                 // If there was a communication error on the network, notify the system
                 } else if id == ItemId::new_unchecked(COMM_ERROR) {
+                    gen_update.metrics.count_comm_error();
                     update!(err &gen_update => "There Was A Communication Error.");
 
                 // Echo all valid events back to the system
                 } else {
                     // Echo the event to every connection
-                    for connection in connections.iter_mut() {
+                    for (_, connection) in connections.iter_mut() {
                         connection
                             .echo_event(id.clone(), game_id.clone(), data2.clone())
                             .unwrap_or(());
@@ -437,16 +467,22 @@ impl SystemConnection {
                     };
 
                     // Try to send the new event to every connection
-                    for connection in connections.iter_mut() {
+                    for (label, connection) in connections.iter_mut() {
                         // Catch any write errors
                         if let Err(_) = connection.write_event(id, identifier.id(), data2) {
                             // Wait a little bit and try again
+                            gen_update.metrics.count_retry(label);
                             thread::sleep(Duration::from_millis(POLLING_RATE));
                             if let Err(_) = connection.write_event(id, identifier.id(), data2) {
                                 // If failed twice in a row, notify the system
+                                gen_update.metrics.count_failure(label);
                                 update!(err &gen_update => "Unable To Contact The Underlying System.");
+                                continue;
                             }
                         }
+
+                        // Count the successful broadcast
+                        gen_update.metrics.count_broadcast(label);
                     }
                 }
 
@@ -115,3 +115,38 @@ macro_rules! test_vec {
         panic!("Failed test vector comparison.");
     }};
 }
+
+/// Test_Scenario Macro
+///
+/// A macro that runs the scenario in the provided file against its
+/// configuration and reports any differences between the expected and actual
+/// behavior (see the scenario module for the format of the scenario).
+///
+#[cfg(test)]
+macro_rules! test_scenario {
+    ($path:expr) => {{
+        // Import necessary libraries
+        use crate::system_interface::scenario::Scenario;
+
+        // Load the scenario
+        let path = $path;
+        let scenario = match Scenario::from_file(path.clone()) {
+            Ok(scenario) => scenario,
+            Err(error) => panic!("Failed to load scenario {:?}: {}", path, error),
+        };
+
+        // Run the scenario and print debugging help for any differences
+        let mismatches = scenario.run();
+        for mismatch in mismatches.iter() {
+            println!(
+                "===================DEBUG==================\n\nSCENARIO\n{:?} at {:?} ({})\n\nEXPECTED\n{}\n\nOUTPUT\n{}",
+                path, mismatch.time, mismatch.check, mismatch.expected, mismatch.output
+            );
+        }
+
+        // If there were any differences, fail the test
+        if !mismatches.is_empty() {
+            panic!("Failed scenario comparison.");
+        }
+    }};
+}
@@ -0,0 +1,973 @@
+// Copyright (c) 2019 Decode Detroit
+// Author: Patton Doyle
+// Licence: GNU GPLv3
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module to serve a browser-based operator console which mirrors the
+//! main program window.
+//!
+//! Every interface update passes through the web console on its way to the
+//! user interface, so the console keeps a copy of the scenes, the event
+//! window, the statuses, the timeline and the notifications. When a console
+//! address is specified in the configuration, the console page is served
+//! over HTTP and the copy is pushed to each browser over a WebSocket. The
+//! browser sends back the operator actions, which are passed to the system
+//! as system updates.
+//!
+//! The browser must provide the token of the configuration to open the
+//! WebSocket (the page passes on the token from its own address, e.g.
+//! http://host:8080/?token=...), and a WebSocket opened by a page from any
+//! other host is refused so that other pages cannot drive the room.
+
+// Import the relevant structures into the correct namespace
+use super::{
+    Clock, EventDelay, EventWindow, FullStatus, HistoryEntry, InterfaceUpdate, ItemId, ItemPair,
+    Notification, Severity, SystemSend, SystemUpdate, UpcomingEvent,
+};
+
+// Import standard library features
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Import the failure features
+use failure::Error;
+
+// Import the serde json and websocket libraries
+extern crate serde_json;
+extern crate tungstenite;
+use self::tungstenite::{Message, WebSocket};
+
+// Define module constants
+const CONSOLE_PAGE: &str = include_str!("../../web/console.html"); // the page of the console
+const POLL_DELAY: u64 = 100; // the delay between checks for new connections and actions, in ms
+const REQUEST_LIMIT: usize = 4096; // the largest request header which is read, in bytes
+const CONNECTION_TIMEOUT: u64 = 5000; // the time allowed to read a request or write a reply, in ms
+const CONNECTION_LIMIT: usize = 16; // the most connections which are answered at once
+
+/// A structure to define where the web console is served and the token the
+/// browser must provide to connect
+///
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsoleSettings {
+    pub address: String, // the address to serve the console (e.g. 0.0.0.0:8080)
+    pub token: String,   // the token to provide in the address of the page (e.g. /?token=...)
+}
+
+/// An enum of the actions which the browser can send to the system
+///
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ConsoleAction {
+    /// A variant to trigger an event
+    Event { id: u32 },
+
+    /// A variant to change the current scene
+    Scene { id: u32 },
+
+    /// A variant to change the state of a status
+    Status { status: u32, state: u32 },
+
+    /// A variant to trigger the all stop
+    AllStop,
+
+    /// A variant to clear the event queue
+    ClearQueue,
+
+    /// A variant to acknowledge one pinned error (or all of them)
+    Acknowledge { index: Option<usize> },
+
+    /// A variant to queue an event after the delay (in seconds), if any
+    QueueEvent { id: u32, delay: Option<u64> },
+
+    /// A variant to change the remaining time (in seconds) of the next
+    /// coming event with the id, or to cancel the event if no time is provided
+    EventChange { id: u32, remaining: Option<u64> },
+
+    /// A variant to add time to (or subtract time from) all the coming events
+    AllEventChange { seconds: u64, is_negative: bool },
+
+    /// A variant to save a named checkpoint of the current state
+    SaveCheckpoint { name: String },
+
+    /// A variant to restore a named checkpoint
+    RestoreCheckpoint { name: String, rebroadcast: bool },
+
+    /// A variant to delete a named checkpoint
+    DeleteCheckpoint { name: String },
+
+    /// A variant to save the configuration (relative to the working directory)
+    SaveConfig { filepath: PathBuf },
+
+    /// A variant to start a new game log (relative to the working directory)
+    GameLog { filepath: PathBuf },
+
+    /// A variant to simulate at the provided rate, or to return to real time
+    SimulationMode { rate: Option<u32> },
+}
+
+// Implement key features of the console action
+impl ConsoleAction {
+    /// A method to convert the action to a system update, using the provided
+    /// timeline to find the coming events. Returns None if one of the
+    /// provided ids or file paths is not valid, or if there is no coming
+    /// event with the provided id.
+    ///
+    fn into_update(self, timeline: &[UpcomingEvent], clock: &Clock) -> Option<SystemUpdate> {
+        match self {
+            ConsoleAction::Event { id } => Some(SystemUpdate::ProcessEvent {
+                event: ItemId::new(id)?,
+                check_scene: true,
+                broadcast: true,
+            }),
+            ConsoleAction::Scene { id } => Some(SystemUpdate::SceneChange {
+                scene: ItemId::new(id)?,
+            }),
+            ConsoleAction::Status { status, state } => Some(SystemUpdate::StatusChange {
+                status_id: ItemId::new(status)?,
+                state: ItemId::new(state)?,
+            }),
+            ConsoleAction::AllStop => Some(SystemUpdate::AllStop),
+            ConsoleAction::ClearQueue => Some(SystemUpdate::ClearQueue),
+            ConsoleAction::Acknowledge { index } => Some(SystemUpdate::Acknowledge { index }),
+            ConsoleAction::QueueEvent { id, delay } => Some(SystemUpdate::QueueEvent {
+                event_delay: EventDelay::new(delay.map(Duration::from_secs), ItemId::new(id)?),
+            }),
+            ConsoleAction::EventChange { id, remaining } => {
+                // Find the next coming event with the id
+                let event_id = ItemId::new(id)?;
+                let event = timeline
+                    .iter()
+                    .filter(|event| event.event.get_id() == event_id)
+                    .min_by_key(|event| clock.remaining(&event.start_time, &event.delay))?;
+
+                // Change the delay from the original start time
+                Some(SystemUpdate::EventChange {
+                    event_id,
+                    start_time: event.start_time,
+                    new_delay: remaining.map(|remaining| {
+                        clock.elapsed(&event.start_time) + Duration::from_secs(remaining)
+                    }),
+                })
+            }
+            ConsoleAction::AllEventChange {
+                seconds,
+                is_negative,
+            } => Some(SystemUpdate::AllEventChange {
+                adjustment: Duration::from_secs(seconds),
+                is_negative,
+            }),
+            ConsoleAction::SaveCheckpoint { name } => Some(SystemUpdate::SaveCheckpoint { name }),
+            ConsoleAction::RestoreCheckpoint { name, rebroadcast } => {
+                Some(SystemUpdate::RestoreCheckpoint { name, rebroadcast })
+            }
+            ConsoleAction::DeleteCheckpoint { name } => {
+                Some(SystemUpdate::DeleteCheckpoint { name })
+            }
+            ConsoleAction::SaveConfig { filepath } => Some(SystemUpdate::SaveConfig {
+                filepath: local_path(filepath)?,
+            }),
+            ConsoleAction::GameLog { filepath } => Some(SystemUpdate::GameLog {
+                filepath: local_path(filepath)?,
+            }),
+            ConsoleAction::SimulationMode { rate } => Some(SystemUpdate::SimulationMode(rate)),
+        }
+    }
+}
+
+/// A helper function to accept only a file path inside the working directory
+/// (a relative path without any parent directories), so that the browser
+/// cannot write files elsewhere on the computer
+///
+fn local_path(filepath: PathBuf) -> Option<PathBuf> {
+    // Refuse empty paths and any component other than a plain name
+    if filepath.as_os_str().is_empty()
+        || !filepath
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(filepath)
+}
+
+/// An internal structure to hold the copy of the program window
+///
+struct ConsoleState {
+    version: u64,                      // a count of the changes, to detect a new copy
+    scenes: Vec<ItemPair>,             // the available scenes
+    current_scene: Option<ItemPair>,   // the current scene, if known
+    window: EventWindow,               // the event groups of the current scene
+    statuses: Vec<ItemPair>,           // the statuses shown in the current scene
+    full_status: FullStatus,           // the descriptions and states of every status
+    timeline: Vec<UpcomingEvent>,      // the events in the queue
+    notifications: Vec<Notification>,  // the most recent notifications
+    pinned: Vec<HistoryEntry>,         // the errors which have not been acknowledged
+    message: String,                   // the most recent event message
+    settings: Option<ConsoleSettings>, // the address and token of the console, if serving
+}
+
+/// A structure to mirror the program window and serve it to the browser
+///
+#[derive(Clone)]
+pub struct WebConsole {
+    state: Arc<Mutex<ConsoleState>>, // the shared copy of the program window
+    system_send: SystemSend,         // the line to pass operator actions to the system
+    clock: Clock,                    // the clock for the remaining time of coming events
+    connections: Arc<AtomicUsize>,   // the number of connections being answered
+}
+
+// Implement key features of the web console
+impl WebConsole {
+    /// A function to create a new, empty web console
+    ///
+    pub fn new(system_send: SystemSend, clock: Clock) -> WebConsole {
+        WebConsole {
+            state: Arc::new(Mutex::new(ConsoleState {
+                version: 0,
+                scenes: Vec::new(),
+                current_scene: None,
+                window: Vec::new(),
+                statuses: Vec::new(),
+                full_status: FullStatus::default(),
+                timeline: Vec::new(),
+                notifications: Vec::new(),
+                pinned: Vec::new(),
+                message: String::new(),
+                settings: None,
+            })),
+            system_send,
+            clock,
+            connections: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// A method to pass every interface update from the provided line to the
+    /// user interface, copying the updates which change the program window.
+    /// This method returns once every sender of the line has closed.
+    ///
+    pub fn relay(
+        &self,
+        interface_receive: mpsc::Receiver<InterfaceUpdate>,
+        interface_send: mpsc::Sender<InterfaceUpdate>,
+    ) {
+        for update in interface_receive.iter() {
+            self.update(&update);
+            interface_send.send(update).unwrap_or(());
+        }
+    }
+
+    /// A method to copy the relevant parts of an interface update
+    ///
+    pub fn update(&self, update: &InterfaceUpdate) {
+        // Lock the state
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+
+        // Copy the update
+        match update {
+            InterfaceUpdate::Notify { message } => state.message = message.clone(),
+            InterfaceUpdate::UpdateConfig {
+                scenes,
+                full_status,
+            } => {
+                state.scenes = scenes.clone();
+                state.full_status = full_status.clone();
+            }
+            InterfaceUpdate::UpdateWindow {
+                current_scene,
+                statuses,
+                window,
+                ..
+            } => {
+                state.current_scene = Some(current_scene.clone());
+                state.statuses = statuses.clone();
+                state.window = window.clone();
+            }
+            InterfaceUpdate::UpdateStatus {
+                status_id,
+                new_state,
+            } => {
+                if let Some(description) = state.full_status.get_mut(status_id) {
+                    description.current = new_state.clone();
+                }
+            }
+            InterfaceUpdate::UpdateNotifications {
+                notifications,
+                pinned,
+            } => {
+                state.notifications = notifications.clone();
+                state.pinned = pinned.clone();
+            }
+            InterfaceUpdate::UpdateTimeline { events } => state.timeline = events.clone(),
+
+            // Ignore the updates which only concern the program window
+            _ => return,
+        }
+
+        // Mark the change
+        state.version += 1;
+    }
+
+    /// A method to compose the copy of the program window as a JSON message
+    /// for the browser
+    ///
+    pub fn snapshot(&self) -> String {
+        // Lock the state
+        let state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return String::new(),
+        };
+
+        // Compose the event groups
+        let groups: Vec<serde_json::Value> = state
+            .window
+            .iter()
+            .map(|group| {
+                serde_json::json!({
+                    "group": group.group_id.as_ref().map(item),
+                    "events": group.group_events.iter().map(item).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+
+        // Compose the statuses of the scene and their states
+        let statuses: Vec<serde_json::Value> = state
+            .statuses
+            .iter()
+            .filter_map(|status| {
+                let description = state.full_status.get(status)?;
+                Some(serde_json::json!({
+                    "status": item(status),
+                    "current": item(&description.current),
+                    "allowed": description.allowed.iter().map(item).collect::<Vec<_>>(),
+                    "derived": description.is_derived,
+                }))
+            })
+            .collect();
+
+        // Compose the coming events with the time remaining
+        let timeline: Vec<serde_json::Value> = state
+            .timeline
+            .iter()
+            .map(|event| {
+                let remaining = self
+                    .clock
+                    .remaining(&event.start_time, &event.delay)
+                    .unwrap_or_default();
+                serde_json::json!({
+                    "event": item(&event.event),
+                    "remaining": remaining.as_secs_f64(),
+                })
+            })
+            .collect();
+
+        // Compose the notifications and the pinned errors
+        let notifications: Vec<serde_json::Value> =
+            state.notifications.iter().map(notification).collect();
+        let pinned: Vec<serde_json::Value> = state
+            .pinned
+            .iter()
+            .map(|entry| {
+                let mut value = notification(&entry.notification);
+                value["index"] = serde_json::json!(entry.index);
+                value
+            })
+            .collect();
+
+        // Return the complete message
+        serde_json::json!({
+            "scenes": state.scenes.iter().map(item).collect::<Vec<_>>(),
+            "current_scene": state.current_scene.as_ref().map(item),
+            "groups": groups,
+            "statuses": statuses,
+            "timeline": timeline,
+            "notifications": notifications,
+            "pinned": pinned,
+            "message": state.message,
+        })
+        .to_string()
+    }
+
+    /// A method to serve the console with the provided settings. If the
+    /// console is already served with different settings, that console is
+    /// closed.
+    ///
+    /// # Errors
+    ///
+    /// This method will raise an error if the token is empty or if it is
+    /// unable to listen at the provided address.
+    ///
+    pub fn listen(&self, settings: &ConsoleSettings) -> Result<(), Error> {
+        // Do nothing if the console is already served with these settings
+        if self.settings().as_ref() == Some(settings) {
+            return Ok(());
+        }
+
+        // Refuse to serve the console without a token
+        if settings.token.is_empty() {
+            return Err(format_err!("The web console token cannot be empty."));
+        }
+
+        // Open the new console (closing the old one)
+        let listener = TcpListener::bind(&settings.address)?;
+        listener.set_nonblocking(true)?;
+        if let Ok(mut state) = self.state.lock() {
+            state.settings = Some(settings.clone());
+        }
+
+        // Accept connections on a separate thread until the settings change
+        let console = self.clone();
+        let settings = settings.clone();
+        thread::spawn(move || loop {
+            // Stop if the console has changed or is no longer served
+            if console.settings().as_ref() != Some(&settings) {
+                break;
+            }
+
+            // Answer each new connection on its own thread (closing any beyond the limit)
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if console.connections.fetch_add(1, Ordering::SeqCst) >= CONNECTION_LIMIT {
+                        console.connections.fetch_sub(1, Ordering::SeqCst);
+                        continue;
+                    }
+                    let console = console.clone();
+                    thread::spawn(move || {
+                        console.serve(stream).unwrap_or(());
+                        console.connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(ref error) if error.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(POLL_DELAY));
+                }
+                Err(_) => break,
+            }
+        });
+        Ok(())
+    }
+
+    /// A method to stop serving the console, if it is served. Any open
+    /// browser connections are closed.
+    ///
+    pub fn stop(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.settings = None;
+        }
+    }
+
+    /// An internal method to return the address and token of the console
+    ///
+    fn settings(&self) -> Option<ConsoleSettings> {
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.settings.clone())
+    }
+
+    /// An internal method to return the current version of the copy
+    ///
+    fn version(&self) -> u64 {
+        self.state.lock().map(|state| state.version).unwrap_or(0)
+    }
+
+    /// An internal method to pass an action from the browser to the system.
+    /// Actions which cannot be read are ignored.
+    ///
+    fn act(&self, text: &str) {
+        // Copy the timeline to find any coming events
+        let timeline = match self.state.lock() {
+            Ok(state) => state.timeline.clone(),
+            Err(_) => return,
+        };
+
+        // Pass on the action
+        if let Some(update) = serde_json::from_str::<ConsoleAction>(text)
+            .ok()
+            .and_then(|action| action.into_update(&timeline, &self.clock))
+        {
+            self.system_send.send(update);
+        }
+    }
+
+    /// An internal method to answer one connection, either with the console
+    /// page or by opening a WebSocket
+    ///
+    fn serve(&self, mut stream: TcpStream) -> Result<(), Error> {
+        // Look at the request without removing it from the stream (giving up on slow connections)
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_millis(CONNECTION_TIMEOUT)))?;
+        stream.set_write_timeout(Some(Duration::from_millis(CONNECTION_TIMEOUT)))?;
+        let mut buffer = [0; REQUEST_LIMIT];
+        let length = stream.peek(&mut buffer)?;
+        let request = Request::parse(&String::from_utf8_lossy(&buffer[..length]));
+
+        // Open a WebSocket if requested with the token from the console page
+        if request.is_websocket() {
+            if !self.is_permitted(&request) {
+                stream.read_exact(&mut buffer[..length])?;
+                return reply(&mut stream, "403 Forbidden", "Forbidden\n");
+            }
+            let socket = tungstenite::accept(stream)
+                .map_err(|_| format_err!("Unable To Open The Console Connection."))?;
+            return self.run_socket(socket);
+        }
+
+        // Otherwise, reply with the console page or indicate the page was not found
+        stream.read_exact(&mut buffer[..length])?;
+        match request.path() {
+            "/" | "/index.html" => reply(&mut stream, "200 OK", CONSOLE_PAGE),
+            _ => reply(&mut stream, "404 Not Found", "Not Found\n"),
+        }
+    }
+
+    /// An internal method to check that a WebSocket request has the token of
+    /// the console and comes from the console page (a browser always names
+    /// the host of the page which opens a WebSocket in the origin)
+    ///
+    fn is_permitted(&self, request: &Request) -> bool {
+        // Check the token
+        let settings = match self.settings() {
+            Some(settings) => settings,
+            None => return false,
+        };
+        let is_token = match request.query("token") {
+            Some(token) => is_equal(&token, settings.token.as_bytes()),
+            None => false,
+        };
+
+        // Check that the origin (if any) is the host of the console
+        let is_origin = match (request.header("origin"), request.header("host")) {
+            (None, _) => true, // not from a browser
+            (Some(origin), Some(host)) => {
+                let origin = origin.to_lowercase();
+                let host = host.to_lowercase();
+                origin == format!("http://{}", host) || origin == format!("https://{}", host)
+            }
+            (Some(_), None) => false,
+        };
+        is_token && is_origin
+    }
+
+    /// An internal method to push the copy of the program window to the
+    /// browser whenever it changes and to pass on the actions of the browser
+    ///
+    fn run_socket(&self, mut socket: WebSocket<TcpStream>) -> Result<(), Error> {
+        // Check for actions regularly
+        socket
+            .get_mut()
+            .set_read_timeout(Some(Duration::from_millis(POLL_DELAY)))?;
+
+        // Run until the browser closes or the console changes
+        let settings = self.settings();
+        let mut sent = None;
+        while self.settings() == settings {
+            // Send the copy of the program window when it changes
+            let version = self.version();
+            if sent != Some(version) {
+                socket.write_message(Message::Text(self.snapshot()))?;
+                sent = Some(version);
+            }
+
+            // Pass on any action from the browser
+            match socket.read_message() {
+                Ok(Message::Text(text)) => self.act(&text),
+                Ok(Message::Close(_)) => break,
+                Ok(_) => (),
+                Err(tungstenite::Error::Io(ref error))
+                    if error.kind() == ErrorKind::WouldBlock
+                        || error.kind() == ErrorKind::TimedOut => {}
+                Err(_) => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An internal structure to hold the parts of an HTTP request which the
+/// console uses
+///
+struct Request {
+    target: String,                 // the path and query of the request
+    headers: Vec<(String, String)>, // the headers of the request (with lowercase names)
+}
+
+// Implement key features of the request
+impl Request {
+    /// A function to read the request line and headers of an HTTP request
+    ///
+    fn parse(text: &str) -> Request {
+        // Read the target from the request line
+        let mut lines = text.lines();
+        let target = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("")
+            .to_string();
+
+        // Read the headers until the end of the header
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                Some((name.trim().to_lowercase(), value.trim().to_string()))
+            })
+            .collect();
+        Request { target, headers }
+    }
+
+    /// A method to return the path of the request (without the query)
+    ///
+    fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or("")
+    }
+
+    /// A method to return the decoded value of the provided query parameter,
+    /// if any (a browser percent-encodes any reserved characters)
+    ///
+    fn query(&self, name: &str) -> Option<Vec<u8>> {
+        self.target
+            .split_once('?')?
+            .1
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| decode(value))
+    }
+
+    /// A method to return the value of the provided header (a lowercase
+    /// name), if any
+    ///
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// A method to check if the request asks to open a WebSocket
+    ///
+    fn is_websocket(&self) -> bool {
+        matches!(self.header("upgrade"), Some(upgrade) if upgrade.eq_ignore_ascii_case("websocket"))
+    }
+}
+
+/// A helper function to reply to an HTTP request with the provided status and
+/// page, closing the connection afterwards
+///
+fn reply(stream: &mut TcpStream, status: &str, body: &str) -> Result<(), Error> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+/// A helper function to compare a token in a constant time (so that the time
+/// to refuse a token does not reveal how much of it was correct)
+///
+fn is_equal(first: &[u8], second: &[u8]) -> bool {
+    first.len() == second.len()
+        && first
+            .iter()
+            .zip(second.iter())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// A helper function to decode a percent-encoded query value, or return None
+/// if the value has an invalid escape
+///
+fn decode(value: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            // Decode the two hexadecimal digits of an escape
+            b'%' => {
+                let digits = [bytes.next()?, bytes.next()?];
+                let digits = std::str::from_utf8(&digits).ok()?;
+                decoded.push(u8::from_str_radix(digits, 16).ok()?);
+            }
+
+            // Treat a plus as a space, as in a form
+            b'+' => decoded.push(b' '),
+
+            // Keep any other character
+            _ => decoded.push(byte),
+        }
+    }
+    Some(decoded)
+}
+
+/// A helper function to compose the id and description of an item
+///
+fn item(pair: &ItemPair) -> serde_json::Value {
+    serde_json::json!({ "id": pair.id(), "description": pair.description() })
+}
+
+/// A helper function to compose the severity, time and message of a
+/// notification
+///
+fn notification(notification: &Notification) -> serde_json::Value {
+    let severity = match notification.severity() {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Event => "event",
+        Severity::Update => "update",
+    };
+    let time = notification.time();
+    serde_json::json!({
+        "severity": severity,
+        "time": time.strftime("%a %T").map(|time| time.to_string()).unwrap_or_default(),
+        "message": notification.message(),
+    })
+}
+
+// Tests of the web console module
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        EventGroup, GeneralUpdate, GeneralUpdateType, Hidden, KeyMap, StatusDescription,
+    };
+    use super::*;
+
+    // Test the copy of the program window and the actions of the browser
+    #[test]
+    fn mirror_and_act() {
+        // Create a web console with a line to the system
+        let (general_update, receive) = GeneralUpdate::new();
+        let console = WebConsole::new(SystemSend::from_general(&general_update), Clock::new());
+
+        // Relay a few updates to the user interface
+        let status = ItemPair::new(10, "Door", Hidden).unwrap();
+        let closed = ItemPair::new(11, "Closed", Hidden).unwrap();
+        let open = ItemPair::new(12, "Open", Hidden).unwrap();
+        let mut full_status = FullStatus::default();
+        full_status.insert(
+            status.clone(),
+            StatusDescription {
+                current: closed.clone(),
+                allowed: vec![closed.clone(), open.clone()],
+                transitions: None,
+                is_derived: false,
+            },
+        );
+        let (relay_send, relay_receive) = mpsc::channel();
+        let (interface_send, interface_receive) = mpsc::channel();
+        relay_send
+            .send(InterfaceUpdate::UpdateConfig {
+                scenes: vec![ItemPair::new(100, "Intro", Hidden).unwrap()],
+                full_status,
+            })
+            .unwrap();
+        relay_send
+            .send(InterfaceUpdate::UpdateWindow {
+                current_scene: ItemPair::new(100, "Intro", Hidden).unwrap(),
+                statuses: vec![status.clone()],
+                window: vec![EventGroup {
+                    group_id: None,
+                    group_events: vec![ItemPair::new(20, "Start", Hidden).unwrap()],
+                }],
+                key_map: KeyMap::default(),
+            })
+            .unwrap();
+        relay_send
+            .send(InterfaceUpdate::UpdateStatus {
+                status_id: status,
+                new_state: open,
+            })
+            .unwrap();
+        drop(relay_send);
+        console.relay(relay_receive, interface_send);
+
+        // Verify that every update reached the user interface and was copied
+        assert_eq!(interface_receive.try_iter().count(), 3);
+        let snapshot: serde_json::Value = serde_json::from_str(&console.snapshot()).unwrap();
+        assert_eq!(snapshot["current_scene"]["description"], "Intro");
+        assert_eq!(snapshot["groups"][0]["events"][0]["id"], 20);
+        assert_eq!(snapshot["statuses"][0]["current"]["description"], "Open");
+
+        // Serve the console on a free port
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        console
+            .listen(&ConsoleSettings {
+                address: address.clone(),
+                token: "secret".to_string(),
+            })
+            .unwrap();
+
+        // Verify that a WebSocket without the token or from another page is refused
+        let handshake = |target: &str, origin: &str| {
+            let mut stream = TcpStream::connect(&address).unwrap();
+            write!(
+                stream,
+                "GET {} HTTP/1.1\r\nHost: {}\r\nOrigin: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+                target, address, origin
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap_or(0);
+            response
+        };
+        let own_origin = format!("http://{}", address);
+        assert!(handshake("/ws", &own_origin).starts_with("HTTP/1.1 403"));
+        assert!(handshake("/ws?token=wrong", &own_origin).starts_with("HTTP/1.1 403"));
+        assert!(handshake("/ws?token=secret", "http://evil.example").starts_with("HTTP/1.1 403"));
+
+        // Open a WebSocket with the token
+        let stream = TcpStream::connect(&address).unwrap();
+        let (mut socket, _) =
+            tungstenite::client(format!("ws://{}/ws?token=secret", address).as_str(), stream)
+                .unwrap();
+
+        // Verify that the copy arrives and that an action reaches the system
+        match socket.read_message().unwrap() {
+            Message::Text(text) => assert!(text.contains("\"Intro\"")),
+            _ => panic!("Expected the copy of the program window."),
+        }
+        socket
+            .write_message(Message::Text(r#"{"action":"event","id":20}"#.to_string()))
+            .unwrap();
+        match receive.recv_timeout(Duration::from_secs(5)).unwrap() {
+            GeneralUpdateType::System(SystemUpdate::ProcessEvent { event, .. }) => {
+                assert_eq!(event, ItemId::new_unchecked(20))
+            }
+            _ => panic!("Expected the event from the browser."),
+        }
+
+        // Verify that invalid actions are ignored
+        assert_eq!(
+            serde_json::from_str::<ConsoleAction>(r#"{"action":"scene","id":0}"#)
+                .unwrap()
+                .into_update(&[], &Clock::new()),
+            None
+        );
+        console.stop();
+    }
+
+    // Test the actions which control the queue, checkpoints and files
+    #[test]
+    fn console_actions() {
+        // Create a timeline with two coming events with the same id
+        let clock = Clock::new();
+        let start_time = clock.now();
+        let coming = |delay| UpcomingEvent {
+            event: ItemPair::new(30, "Coming", Hidden).unwrap(),
+            start_time,
+            delay: Duration::from_secs(delay),
+        };
+        let timeline = vec![coming(600), coming(60)];
+        let act = |text: &str| {
+            serde_json::from_str::<ConsoleAction>(text)
+                .unwrap()
+                .into_update(&timeline, &clock)
+        };
+
+        // Verify that an event is queued with the delay
+        assert_eq!(
+            act(r#"{"action":"queue_event","id":30,"delay":5}"#),
+            Some(SystemUpdate::QueueEvent {
+                event_delay: EventDelay::new(
+                    Some(Duration::from_secs(5)),
+                    ItemId::new_unchecked(30)
+                ),
+            })
+        );
+
+        // Verify that the next coming event is changed or cancelled
+        match act(r#"{"action":"event_change","id":30,"remaining":120}"#) {
+            Some(SystemUpdate::EventChange {
+                event_id,
+                start_time: event_start,
+                new_delay: Some(delay),
+            }) => {
+                assert_eq!(event_id, ItemId::new_unchecked(30));
+                assert_eq!(event_start, start_time);
+                assert!(delay >= Duration::from_secs(120));
+                assert!(delay < Duration::from_secs(130));
+            }
+            _ => panic!("Expected the change of the coming event."),
+        }
+        assert_eq!(
+            act(r#"{"action":"event_change","id":30,"remaining":null}"#),
+            Some(SystemUpdate::EventChange {
+                event_id: ItemId::new_unchecked(30),
+                start_time,
+                new_delay: None,
+            })
+        );
+        assert_eq!(act(r#"{"action":"event_change","id":31}"#), None);
+
+        // Verify the checkpoint and simulation actions
+        assert_eq!(
+            act(r#"{"action":"restore_checkpoint","name":"Intro","rebroadcast":true}"#),
+            Some(SystemUpdate::RestoreCheckpoint {
+                name: "Intro".to_string(),
+                rebroadcast: true,
+            })
+        );
+        assert_eq!(
+            act(r#"{"action":"simulation_mode","rate":10}"#),
+            Some(SystemUpdate::SimulationMode(Some(10)))
+        );
+
+        // Verify that files are only written inside the working directory
+        assert_eq!(
+            act(r#"{"action":"game_log","filepath":"log/game.jsonl"}"#),
+            Some(SystemUpdate::GameLog {
+                filepath: PathBuf::from("log/game.jsonl"),
+            })
+        );
+        assert_eq!(
+            act(r#"{"action":"save_config","filepath":"/etc/minerva.mnv"}"#),
+            None
+        );
+        assert_eq!(
+            act(r#"{"action":"save_config","filepath":"../minerva.mnv"}"#),
+            None
+        );
+        assert_eq!(act(r#"{"action":"game_log","filepath":""}"#), None);
+    }
+
+    // Test that the query of a request is decoded before the token is checked
+    #[test]
+    fn decode_query() {
+        // Decode the reserved characters of a token
+        let request = Request::parse("GET /ws?page=1&token=a%2Bb%26c+d%2f HTTP/1.1\r\n\r\n");
+        assert_eq!(request.query("token"), Some(b"a+b&c d/".to_vec()));
+        assert_eq!(request.query("page"), Some(b"1".to_vec()));
+        assert_eq!(request.query("other"), None);
+
+        // Refuse an invalid escape
+        let request = Request::parse("GET /ws?token=a%2 HTTP/1.1\r\n\r\n");
+        assert_eq!(request.query("token"), None);
+        let request = Request::parse("GET /ws?token=a%zz HTTP/1.1\r\n\r\n");
+        assert_eq!(request.query("token"), None);
+    }
+}
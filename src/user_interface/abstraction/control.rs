@@ -22,8 +22,8 @@
 
 // Import the relevant structures into the correct namespace
 use super::super::super::system_interface::{
-    AllStop, Current, Error, InterfaceUpdate, LaunchWindow, Notification, SystemSend, Update,
-    Warning, WindowType,
+    Acknowledge, AllStop, Current, Error, HistoryEntry, InterfaceUpdate, LaunchWindow,
+    Notification, SystemSend, Update, Warning, WindowType,
 };
 use super::super::utils::clean_text;
 use super::{LARGE_FONT, SMALL_FONT};
@@ -52,6 +52,7 @@ const UPDATE_NUMBER: usize = 50; // maximum number of updates to display
 pub struct ControlAbstraction {
     grid: gtk::Grid, // the grid to hold the underlying elements
     interface_send: mpsc::Sender<InterfaceUpdate>, // a copy of interface send
+    system_send: SystemSend, // a copy of system send
     notification_area_list: gtk::ListBox, // the notification area list for system notifications
     is_debug_mode: bool, // a flag to indicate whether debug-level notifications are shown
     is_font_large: bool, // a flag to indicate the font size of the items
@@ -197,6 +198,7 @@ impl ControlAbstraction {
         ControlAbstraction {
             grid,
             interface_send: interface_send.clone(),
+            system_send: system_send.clone(),
             notification_area_list,
             is_debug_mode: false,
             is_font_large: false,
@@ -229,9 +231,15 @@ impl ControlAbstraction {
         self.is_high_contrast = is_hc;
     }
 
-    /// A method to update the notification area with system notifications
+    /// A method to update the notification area with system notifications.
+    /// The pinned errors (those not yet acknowledged) are highlighted at the
+    /// top of the area, regardless of their age.
     ///
-    pub fn update_notifications(&mut self, mut notifications: Vec<Notification>) {
+    pub fn update_notifications(
+        &mut self,
+        mut notifications: Vec<Notification>,
+        pinned: Vec<HistoryEntry>,
+    ) {
         // If not debug, prefilter and throw out notifications that are debug level
         // TODO: Consider replacing with experimental function Vec::drain_filter()
         if !self.is_debug_mode {
@@ -271,6 +279,56 @@ impl ControlAbstraction {
             }
         }
 
+        // Set the font size
+        let font_size = match self.is_font_large {
+            false => SMALL_FONT,
+            true => LARGE_FONT,
+        };
+
+        // Add each pinned error, the most recent at the top
+        for entry in pinned.iter() {
+            // Unpack the notification and create a highlighted label
+            let (markup, button_opt) = self.unpack_notification(entry.notification.clone());
+            let notification_label = gtk::Label::new(None);
+            notification_label.set_markup(&format!("<span background='#5C1010'>{}</span>", markup));
+            notification_label.set_halign(gtk::Align::Start);
+
+            // Create a button to acknowledge the error
+            let tmp_label = gtk::Label::new(None);
+            tmp_label.set_markup(&format!("<span size='{}'>Acknowledge</span>", font_size));
+            let acknowledge_button = gtk::Button::new();
+            acknowledge_button.add(&tmp_label);
+            acknowledge_button.set_hexpand(false);
+            acknowledge_button.set_vexpand(false);
+            let index = entry.index;
+            let system_send = self.system_send.clone();
+            acknowledge_button.connect_clicked(move |_| {
+                system_send.send(Acknowledge { index: Some(index) });
+            });
+
+            // Add the label and the buttons to a new grid
+            let grid = gtk::Grid::new();
+            grid.set_column_spacing(5); // add some space between the columns
+            grid.set_hexpand(true);
+            grid.set_vexpand(false);
+            grid.attach(&notification_label, 0, 0, 2, 1);
+            grid.attach(&acknowledge_button, 0, 1, 1, 1);
+            if let Some(button) = button_opt {
+                grid.attach(&button, 1, 1, 1, 1);
+            }
+
+            // Show the grid components and add it to the notification list
+            grid.show_all();
+            self.notification_area_list.add(&grid);
+        }
+
+        // Skip the recent notifications which are already pinned
+        notifications.retain(|notification| {
+            !pinned
+                .iter()
+                .any(|entry| entry.notification == *notification)
+        });
+
         // Unpack each of the notification lines, the most recent at the top
         for (i, update) in notifications.drain(..).enumerate() {
             // Cap the list at the update number
@@ -101,6 +101,7 @@ impl EditActionDialog {
         action_selection.append(Some("cancelevent"), "Cancel Event");
         action_selection.append(Some("savedata"), "Save Data");
         action_selection.append(Some("senddata"), "Send Data");
+        action_selection.append(Some("savereport"), "Save Report");
         action_selection.append(Some("groupedevent"), "Grouped Event");
 
         // Create the different edit windows for the action types
@@ -110,6 +111,9 @@ impl EditActionDialog {
         let edit_cancel_event = EditCancelEvent::new();
         let edit_save_data = EditSaveData::new();
         let edit_send_data = EditSendData::new();
+        let edit_save_report = gtk::Label::new(Some(
+            "Saves the game report of the current session next to the game log.",
+        ));
         let edit_grouped_event = EditGroupedEvent::new();
 
         // Create the action stack
@@ -122,6 +126,7 @@ impl EditActionDialog {
         action_stack.add_named(edit_cancel_event.get_top_element(), "cancelevent");
         action_stack.add_named(edit_save_data.get_top_element(), "savedata");
         action_stack.add_named(edit_send_data.get_top_element(), "senddata");
+        action_stack.add_named(&edit_save_report, "savereport");
         action_stack.add_named(edit_grouped_event.get_top_element(), "groupedevent");
 
         // Connect the function to trigger action selection changes
@@ -174,6 +179,11 @@ impl EditActionDialog {
                 edit_send_data.load_action(data);
             }
 
+            // the SaveReport variant
+            EventAction::SaveReport => {
+                action_selection.set_active_id(Some("savereport"));
+            }
+
             // the GroupedEvent variant
             EventAction::GroupedEvent {
                 status_id,
@@ -266,6 +276,13 @@ impl EditActionDialog {
                             *action = edit_send_data.pack_action();
                         }
 
+                        // the SaveReport variant
+                        "savereport" => {
+                            // Update the action label and action
+                            overview.set_text("Save Report");
+                            *action = EventAction::SaveReport;
+                        }
+
                         // The GroupedEvent variant
                         "groupedevent" => {
                             // Update the action label and action
@@ -579,6 +596,7 @@ impl EditSaveData {
         );
         data_type.append(Some("staticstring"), "A hardcoded string of data");
         data_type.append(Some("userstring"), "A user-provided string");
+        data_type.append(Some("besttimes"), "The ten best completion times");
 
         // Add the button below the data list
         let grid = gtk::Grid::new();
@@ -634,6 +652,12 @@ impl EditSaveData {
                 // Change the dropdown
                 self.data_type.set_active_id(Some("userstring"));
             }
+
+            // The BestTimes variant
+            &DataType::BestTimes { .. } => {
+                // Change the dropdown
+                self.data_type.set_active_id(Some("besttimes"));
+            }
         }
     }
 
@@ -658,6 +682,9 @@ impl EditSaveData {
                     DataType::UserString // FIXME
                 }
 
+                // The BestTimes variant
+                "besttimes" => DataType::BestTimes { count: 10 },
+
                 // The UserString variant
                 _ => DataType::UserString,
             };
@@ -697,6 +724,7 @@ impl EditSendData {
         );
         data_type.append(Some("staticstring"), "A hardcoded string of data");
         data_type.append(Some("userstring"), "A user-provided string");
+        data_type.append(Some("besttimes"), "The ten best completion times");
 
         // Add the button below the data list
         let grid = gtk::Grid::new();
@@ -752,6 +780,12 @@ impl EditSendData {
                 // Change the dropdown
                 self.data_type.set_active_id(Some("userstring"));
             }
+
+            // The BestTimes variant
+            &DataType::BestTimes { .. } => {
+                // Change the dropdown
+                self.data_type.set_active_id(Some("besttimes"));
+            }
         }
     }
 
@@ -776,6 +810,9 @@ impl EditSendData {
                     DataType::UserString // FIXME
                 }
 
+                // The BestTimes variant
+                "besttimes" => DataType::BestTimes { count: 10 },
+
                 // The UserString variant
                 _ => DataType::UserString,
             };
@@ -207,6 +207,9 @@ impl EditItemAbstraction {
             ReplyType::Detail { event_detail } => {
                 self.edit_detail.load_detail(event_detail);
             }
+
+            // Ignore any other reply
+            _ => (),
         }
     }
 }
@@ -1121,6 +1124,7 @@ impl EditDetail {
                 EventAction::CancelEvent { .. } => overview.set_text("Cancel Event"),
                 EventAction::SaveData { .. } => overview.set_text("Save Data"),
                 EventAction::SendData { .. } => overview.set_text("Send Data"),
+                EventAction::SaveReport => overview.set_text("Save Report"),
                 EventAction::GroupedEvent { .. } => overview.set_text("Grouped Event"),
             }
 
@@ -31,12 +31,13 @@ mod timeline;
 use self::control::ControlAbstraction;
 use self::events::EventAbstraction;
 use self::operation_dialogs::{
-    JumpDialog, PromptStringDialog, ShortcutsDialog, StatusDialog, TriggerDialog,
+    CheckpointDialog, JumpDialog, LogSettingsDialog, NotificationWindow, PromptStringDialog,
+    ResumeBackupDialog, ShortcutsDialog, StatisticsWindow, StatusDialog, TriggerDialog,
 };
 use self::timeline::TimelineAbstraction;
 use super::super::system_interface::{
-    EventWindow, FullStatus, Hidden, InterfaceUpdate, ItemPair, KeyMap, Notification, ReplyType,
-    StatusDescription, SystemSend, UpcomingEvent,
+    BackupSummary, Clock, EventWindow, FullStatus, Hidden, HistoryEntry, InterfaceUpdate, ItemPair,
+    KeyMap, Notification, ReplyType, StatusDescription, SystemSend, UpcomingEvent,
 };
 use super::utils::clean_text;
 use edit_item::EditItemAbstraction;
@@ -82,6 +83,11 @@ pub struct InterfaceAbstraction {
     shortcuts_dialog: ShortcutsDialog, // the shortcuts dialog
     trigger_dialog: TriggerDialog, // the trigger dialog
     prompt_string_dialog: PromptStringDialog, // the prompt string dialog
+    checkpoint_dialog: CheckpointDialog, // the checkpoint dialog
+    resume_backup_dialog: ResumeBackupDialog, // the resume backup dialog
+    statistics_window: StatisticsWindow, // the statistics window
+    log_settings_dialog: LogSettingsDialog, // the log settings dialog
+    notification_window: NotificationWindow, // the notification history window
     is_debug: bool,          // a flag to indicate whether the program is in debug mode
 }
 
@@ -98,6 +104,7 @@ impl InterfaceAbstraction {
     pub fn new(
         system_send: &SystemSend,
         interface_send: &mpsc::Sender<InterfaceUpdate>,
+        clock: &Clock,
         window: &gtk::ApplicationWindow,
     ) -> InterfaceAbstraction {
         // Create the top-level element of the program, a stack to hold both
@@ -119,7 +126,7 @@ impl InterfaceAbstraction {
         operations_grid.set_margin_end(10);
 
         // Create the timeline abstraction and add it to the primary grid
-        let timeline = TimelineAbstraction::new(system_send, window);
+        let timeline = TimelineAbstraction::new(system_send, clock, window);
         operations_grid.attach(timeline.get_top_element(), 0, 0, 3, 1);
 
         // Create the control abstraction and add it to the primary grid
@@ -199,6 +206,11 @@ impl InterfaceAbstraction {
         let shortcuts_dialog = ShortcutsDialog::new(system_send, window);
         let trigger_dialog = TriggerDialog::new(window);
         let prompt_string_dialog = PromptStringDialog::new(window);
+        let checkpoint_dialog = CheckpointDialog::new(window);
+        let resume_backup_dialog = ResumeBackupDialog::new(window);
+        let statistics_window = StatisticsWindow::new(window);
+        let log_settings_dialog = LogSettingsDialog::new(window);
+        let notification_window = NotificationWindow::new(system_send, window);
 
         // Return a copy of the interface abstraction
         InterfaceAbstraction {
@@ -221,6 +233,11 @@ impl InterfaceAbstraction {
             shortcuts_dialog,
             trigger_dialog,
             prompt_string_dialog,
+            checkpoint_dialog,
+            resume_backup_dialog,
+            statistics_window,
+            log_settings_dialog,
+            notification_window,
             is_debug: false,
         }
     }
@@ -305,8 +322,12 @@ impl InterfaceAbstraction {
 
     /// A method to update the notifications in the control abstraction
     ///
-    pub fn update_notifications(&mut self, notifications: Vec<Notification>) {
-        self.control.update_notifications(notifications);
+    pub fn update_notifications(
+        &mut self,
+        notifications: Vec<Notification>,
+        pinned: Vec<HistoryEntry>,
+    ) {
+        self.control.update_notifications(notifications, pinned);
     }
 
     // Methods to update the event abstraction
@@ -457,4 +478,66 @@ impl InterfaceAbstraction {
     pub fn launch_prompt_string(&self, event: ItemPair) {
         self.prompt_string_dialog.launch(&self.system_send, event);
     }
+
+    // Methods to update the checkpoint dialog
+    //
+    /// A method to launch the checkpoint dialog
+    ///
+    pub fn launch_checkpoints(&mut self) {
+        self.checkpoint_dialog.launch(&self.system_send);
+    }
+    //
+    /// A method to send an information reply to the checkpoint dialog
+    ///
+    pub fn update_checkpoints(&self, reply: ReplyType) {
+        self.checkpoint_dialog.update_info(reply);
+    }
+
+    // Methods to update the statistics window
+    //
+    /// A method to launch the statistics window
+    ///
+    pub fn launch_statistics(&mut self) {
+        self.statistics_window.launch(&self.system_send);
+    }
+    //
+    /// A method to send an information reply to the statistics window
+    ///
+    pub fn update_statistics(&self, reply: ReplyType) {
+        self.statistics_window.update_info(reply);
+    }
+
+    // Methods to update the log settings dialog
+    //
+    /// A method to launch the log settings dialog
+    ///
+    pub fn launch_log_settings(&mut self) {
+        self.log_settings_dialog.launch(&self.system_send);
+    }
+    //
+    /// A method to send an information reply to the log settings dialog
+    ///
+    pub fn update_log_settings(&self, reply: ReplyType) {
+        self.log_settings_dialog.update_info(reply);
+    }
+
+    // Methods to update the notification history window
+    //
+    /// A method to launch the notification history window
+    ///
+    pub fn launch_notifications(&mut self) {
+        self.notification_window.launch();
+    }
+    //
+    /// A method to send an information reply to the notification history
+    /// window
+    ///
+    pub fn update_notification_history(&self, reply: ReplyType) {
+        self.notification_window.update_info(reply);
+    }
+
+    /// A method to launch the resume backup dialog
+    pub fn launch_resume_backup(&self, summary: BackupSummary) {
+        self.resume_backup_dialog.launch(&self.system_send, summary);
+    }
 }
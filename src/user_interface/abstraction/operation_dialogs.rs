@@ -20,9 +20,11 @@
 
 // Import the relevant structures into the correct namespace
 use super::super::super::system_interface::{
-    BroadcastEvent, DisplayComponent, EventDelay, FullStatus, Hidden, ItemId, ItemPair, KeyMap,
-    ProcessEvent, QueueEvent, ReplyType, Request, RequestType, SceneChange, StatusChange,
-    StatusDescription, SystemSend,
+    Acknowledge, BackupSummary, BroadcastEvent, DeleteCheckpoint, DisplayComponent, EventDelay,
+    FullStatus, Hidden, HistoryEntry, HistoryFilter, ItemId, ItemPair, KeyMap, LogOptions,
+    LogSettings, ProcessEvent, QueueEvent, ReplyType, Request, RequestType, RestoreCheckpoint,
+    ResumeBackup, ResumeMode, ReviewBackup, SaveCheckpoint, SceneChange, Severity, StatusChange,
+    SystemSend, UserString,
 };
 use super::super::utils::{clean_text, decorate_label};
 use super::NORMAL_FONT;
@@ -142,9 +144,9 @@ impl StatusDialog {
                         if let Ok(full_status) = protected_status.try_borrow() {
 
                             // Find the corresponding detail
-                            if let Some(&StatusDescription { ref current, ref allowed }) = full_status.get(&id) {
-                                // Extract the allowed ids and add them to the states
-                                for (num, state_pair) in allowed.iter().enumerate() {
+                            if let Some(description) = full_status.get(&id) {
+                                // Extract the states which may follow the current state and add them to the states
+                                for (num, state_pair) in description.available().iter().enumerate() {
 
                                     // Create a new flow box child and add the label
                                     let child = gtk::FlowBoxChild::new();
@@ -165,11 +167,12 @@ impl StatusDialog {
                                     map.insert(state_pair.get_id(), num as i32);
                                 }
 
-                                // Show all the state box items
+                                // Show all the state box items (read-only for a derived status)
                                 state_box.show_all();
+                                state_box.set_sensitive(!description.is_derived);
 
                                 // Set the current state
-                                if let Some(num) = map.get(&current.get_id()) {
+                                if let Some(num) = map.get(&description.current.get_id()) {
                                     if let Some(child) = state_box.get_child_at_index(num.clone()) {
                                         state_box.select_child(&child);
                                     }
@@ -217,8 +220,8 @@ impl StatusDialog {
                 Err(_) => return,
             };
 
-            // Notify the system of the event change
-            if id == gtk::ResponseType::Ok {
+            // Notify the system of the event change (unless the status is read-only)
+            if (id == gtk::ResponseType::Ok) & state_box.get_sensitive() {
 
                 // Identify and forward the selected status
                 if let Some(id_status) = status_selection.get_active_id() {
@@ -745,6 +748,915 @@ impl TriggerDialog {
     }
 }
 
+/// A structure to contain the dialog for saving and restoring checkpoints.
+///
+pub struct CheckpointDialog {
+    window: gtk::ApplicationWindow, // a copy of the primary window
+    checkpoint_selection: Option<gtk::ComboBoxText>, // the dropdown which lists the saved checkpoints
+}
+
+// Implement key features for the checkpoint dialog
+impl CheckpointDialog {
+    /// A function to create a new checkpoint dialog structure.
+    ///
+    pub fn new(window: &gtk::ApplicationWindow) -> CheckpointDialog {
+        CheckpointDialog {
+            window: window.clone(),
+            checkpoint_selection: None,
+        }
+    }
+
+    /// A method to launch the new checkpoint dialog. The dialog either saves
+    /// a new checkpoint or (after a confirmation) restores or deletes an
+    /// existing one.
+    ///
+    pub fn launch(&mut self, system_send: &SystemSend) {
+        // Create the new dialog
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some("Checkpoints"),
+            Some(&self.window),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Delete", gtk::ResponseType::Reject),
+                ("Save", gtk::ResponseType::Apply),
+                ("Restore", gtk::ResponseType::Ok),
+            ],
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Access the content area and add the grid
+        let content = dialog.get_content_area();
+        let grid = gtk::Grid::new();
+        content.add(&grid);
+
+        // Add some space between the rows and columns
+        grid.set_column_spacing(10);
+        grid.set_row_spacing(10);
+
+        // Add some space on all the sides
+        grid.set_margin_top(10);
+        grid.set_margin_bottom(10);
+        grid.set_margin_start(10);
+        grid.set_margin_end(10);
+
+        // Add the name entry for a new checkpoint
+        let name_entry = gtk::Entry::new();
+        name_entry.set_placeholder_text(Some("Checkpoint Name"));
+        grid.attach(&gtk::Label::new(Some("  Save As:  ")), 0, 0, 1, 1);
+        grid.attach(&name_entry, 1, 0, 1, 1);
+
+        // Add a separator
+        let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
+        separator.set_hexpand(true);
+        separator.set_halign(gtk::Align::Fill);
+        grid.attach(&separator, 0, 1, 2, 1);
+
+        // Add the dropdown of the existing checkpoints (filled by the reply)
+        let checkpoint_selection = gtk::ComboBoxText::new();
+        self.checkpoint_selection = Some(checkpoint_selection.clone());
+        grid.attach(&gtk::Label::new(Some("  Restore:  ")), 0, 2, 1, 1);
+        grid.attach(&checkpoint_selection, 1, 2, 1, 1);
+
+        // Add the checkbox to resynchronize the devices after restoring
+        let rebroadcast_checkbox = gtk::CheckButton::new_with_label("Re-broadcast Status States");
+        rebroadcast_checkbox.set_active(true);
+        grid.attach(&rebroadcast_checkbox, 1, 3, 1, 1);
+
+        // Request the list of existing checkpoints from the system
+        system_send.send(Request {
+            reply_to: DisplayComponent::CheckpointDialog,
+            request: RequestType::Checkpoints,
+        });
+
+        // Connect the close event for when the dialog is complete
+        let window = self.window.clone();
+        dialog.connect_response(clone!(system_send, name_entry, checkpoint_selection, rebroadcast_checkbox => move |modal, id| {
+            // Save a new checkpoint with the provided name
+            if id == gtk::ResponseType::Apply {
+                if let Some(name) = name_entry.get_text() {
+                    if !name.is_empty() {
+                        system_send.send(SaveCheckpoint { name: name.to_string() });
+                    }
+                }
+
+            // Confirm before restoring the selected checkpoint
+            } else if id == gtk::ResponseType::Ok {
+                if let Some(name) = checkpoint_selection.get_active_id() {
+                    // Create the confirmation dialog
+                    let confirm = gtk::MessageDialog::new(
+                        Some(&window),
+                        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+                        gtk::MessageType::Warning,
+                        gtk::ButtonsType::OkCancel,
+                        &format!("Restore checkpoint \"{}\"? The current scene, statuses and timeline will be replaced.", name),
+                    );
+                    confirm.set_position(gtk::WindowPosition::Center);
+
+                    // Restore the checkpoint only if confirmed
+                    let name = name.to_string();
+                    let rebroadcast = rebroadcast_checkbox.get_active();
+                    confirm.connect_response(clone!(system_send => move |confirm, id| {
+                        if id == gtk::ResponseType::Ok {
+                            system_send.send(RestoreCheckpoint { name: name.clone(), rebroadcast });
+                        }
+
+                        // Close the window either way
+                        confirm.destroy();
+                    }));
+                    confirm.show_all();
+                }
+
+            // Confirm before deleting the selected checkpoint
+            } else if id == gtk::ResponseType::Reject {
+                if let Some(name) = checkpoint_selection.get_active_id() {
+                    // Create the confirmation dialog
+                    let confirm = gtk::MessageDialog::new(
+                        Some(&window),
+                        gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+                        gtk::MessageType::Warning,
+                        gtk::ButtonsType::OkCancel,
+                        &format!("Delete checkpoint \"{}\"? The checkpoint cannot be recovered.", name),
+                    );
+                    confirm.set_position(gtk::WindowPosition::Center);
+
+                    // Delete the checkpoint only if confirmed
+                    let name = name.to_string();
+                    confirm.connect_response(clone!(system_send => move |confirm, id| {
+                        if id == gtk::ResponseType::Ok {
+                            system_send.send(DeleteCheckpoint { name: name.clone() });
+                        }
+
+                        // Close the window either way
+                        confirm.destroy();
+                    }));
+                    confirm.show_all();
+                }
+            }
+
+            // Close the window either way
+            modal.destroy();
+        }));
+
+        // Show the dialog and return
+        dialog.show_all();
+    }
+
+    // A method to update the information displayed in the dialog
+    pub fn update_info(&self, reply: ReplyType) {
+        // Update the list of checkpoints, ignore others
+        if let ReplyType::Checkpoints { checkpoints } = reply {
+            // Update the dropdown, if it exists
+            if let Some(ref selection) = self.checkpoint_selection {
+                selection.remove_all();
+                for name in checkpoints.iter() {
+                    selection.append(Some(name.as_str()), name);
+                }
+
+                // Select the most recent checkpoint
+                if let Some(name) = checkpoints.last() {
+                    selection.set_active_id(Some(name.as_str()));
+                }
+            }
+        }
+    }
+}
+
+/// A structure to contain the dialog for changing the location, rotation
+/// and retention of the log files.
+///
+pub struct LogSettingsDialog {
+    window: gtk::ApplicationWindow, // a copy of the primary window
+    log_fields: Option<(
+        gtk::FileChooserButton,
+        gtk::SpinButton,
+        gtk::SpinButton,
+        gtk::SpinButton,
+    )>, // the folder, size, age and file count fields (filled by the reply)
+}
+
+// Implement key features for the log settings dialog
+impl LogSettingsDialog {
+    /// A function to create a new log settings dialog structure.
+    ///
+    pub fn new(window: &gtk::ApplicationWindow) -> LogSettingsDialog {
+        LogSettingsDialog {
+            window: window.clone(),
+            log_fields: None,
+        }
+    }
+
+    /// A method to launch the new log settings dialog. The current settings
+    /// are filled in when the system replies.
+    ///
+    pub fn launch(&mut self, system_send: &SystemSend) {
+        // Create the new dialog
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some("Log Settings"),
+            Some(&self.window),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Confirm", gtk::ResponseType::Ok),
+            ],
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Access the content area and add the grid
+        let content = dialog.get_content_area();
+        let grid = gtk::Grid::new();
+        content.add(&grid);
+
+        // Add some space between the rows and columns
+        grid.set_column_spacing(10);
+        grid.set_row_spacing(10);
+
+        // Add some space on all the sides
+        grid.set_margin_top(10);
+        grid.set_margin_bottom(10);
+        grid.set_margin_start(10);
+        grid.set_margin_end(10);
+
+        // Add the log folder and the rotation and retention limits
+        let folder_button =
+            gtk::FileChooserButton::new("Choose Log Folder", gtk::FileChooserAction::SelectFolder);
+        let size_spin = gtk::SpinButton::new_with_range(1.0, 1048576.0, 1.0);
+        let age_spin = gtk::SpinButton::new_with_range(1.0, 3650.0, 1.0);
+        let files_spin = gtk::SpinButton::new_with_range(1.0, 1000.0, 1.0);
+        grid.attach(&gtk::Label::new(Some("Log Folder:")), 0, 0, 1, 1);
+        grid.attach(&folder_button, 1, 0, 1, 1);
+        grid.attach(&gtk::Label::new(Some("Rotate Logs At (kB):")), 0, 1, 1, 1);
+        grid.attach(&size_spin, 1, 1, 1, 1);
+        grid.attach(&gtk::Label::new(Some("Remove After (Days):")), 0, 2, 1, 1);
+        grid.attach(&age_spin, 1, 2, 1, 1);
+        grid.attach(&gtk::Label::new(Some("Old Logs To Keep:")), 0, 3, 1, 1);
+        grid.attach(&files_spin, 1, 3, 1, 1);
+        self.log_fields = Some((
+            folder_button.clone(),
+            size_spin.clone(),
+            age_spin.clone(),
+            files_spin.clone(),
+        ));
+
+        // Request the current settings from the system
+        system_send.send(Request {
+            reply_to: DisplayComponent::LogSettingsDialog,
+            request: RequestType::LogSettings,
+        });
+
+        // Connect the close event for when the dialog is complete
+        dialog.connect_response(clone!(system_send => move |modal, id| {
+            // Notify the system of the new settings
+            if id == gtk::ResponseType::Ok {
+                system_send.send(LogOptions {
+                    settings: LogSettings {
+                        directory: folder_button.get_filename(),
+                        max_size: size_spin.get_value() as u64,
+                        max_age: age_spin.get_value() as u64,
+                        max_files: files_spin.get_value() as usize,
+                    },
+                });
+            }
+
+            // Close the window either way
+            modal.destroy();
+        }));
+
+        // Show the dialog and return
+        dialog.show_all();
+    }
+
+    // A method to update the information displayed in the dialog
+    pub fn update_info(&self, reply: ReplyType) {
+        // Update the current settings, ignore others
+        if let ReplyType::LogSettings { settings } = reply {
+            // Update the fields, if they exist
+            if let Some((ref folder_button, ref size_spin, ref age_spin, ref files_spin)) =
+                self.log_fields
+            {
+                if let Some(ref directory) = settings.directory {
+                    folder_button.set_filename(directory);
+                }
+                size_spin.set_value(settings.max_size as f64);
+                age_spin.set_value(settings.max_age as f64);
+                files_spin.set_value(settings.max_files as f64);
+            }
+        }
+    }
+}
+
+/// A structure to contain the window for the statistics of past sessions.
+///
+pub struct StatisticsWindow {
+    window: gtk::ApplicationWindow,     // a copy of the primary window
+    statistics_grid: Option<gtk::Grid>, // the grid which lists the statistics
+}
+
+// Implement key features for the statistics window
+impl StatisticsWindow {
+    /// A function to create a new statistics window structure.
+    ///
+    pub fn new(window: &gtk::ApplicationWindow) -> StatisticsWindow {
+        StatisticsWindow {
+            window: window.clone(),
+            statistics_grid: None,
+        }
+    }
+
+    /// A method to launch the new statistics window. The statistics are
+    /// filled in when the system replies.
+    ///
+    pub fn launch(&mut self, system_send: &SystemSend) {
+        // Create the new dialog
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some("Statistics"),
+            Some(&self.window),
+            gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[("Close", gtk::ResponseType::Ok)],
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Create the grid inside a scrollable window
+        let statistics_grid = gtk::Grid::new();
+        statistics_grid.set_column_spacing(20);
+        statistics_grid.set_row_spacing(5);
+        statistics_grid.set_margin_top(10);
+        statistics_grid.set_margin_bottom(10);
+        statistics_grid.set_margin_start(10);
+        statistics_grid.set_margin_end(10);
+        let statistics_window = gtk::ScrolledWindow::new(
+            Some(&gtk::Adjustment::new(0.0, 0.0, 100.0, 0.1, 100.0, 100.0)),
+            Some(&gtk::Adjustment::new(0.0, 0.0, 100.0, 0.1, 100.0, 100.0)),
+        ); // Should be None, None, but the compiler has difficulty inferring types
+        statistics_window.add(&statistics_grid);
+        statistics_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        statistics_window.set_size_request(500, 400);
+        self.statistics_grid = Some(statistics_grid);
+
+        // Access the content area and add the window
+        let content = dialog.get_content_area();
+        content.add(&statistics_window);
+
+        // Request the statistics from the system
+        system_send.send(Request {
+            reply_to: DisplayComponent::StatisticsWindow,
+            request: RequestType::Statistics,
+        });
+
+        // Close the window when complete
+        dialog.connect_response(|modal, _| modal.destroy());
+
+        // Show the dialog and return
+        dialog.show_all();
+    }
+
+    // A method to update the information displayed in the window
+    pub fn update_info(&self, reply: ReplyType) {
+        // Update the statistics, ignore others
+        if let ReplyType::Statistics { statistics } = reply {
+            // Update the grid, if it exists
+            if let Some(ref grid) = self.statistics_grid {
+                // Remove the old statistics
+                for item in grid.get_children() {
+                    item.destroy();
+                }
+
+                // Add the number of sessions
+                let mut row = 0;
+                let sessions =
+                    gtk::Label::new(Some(&format!("{} Sessions Recorded", statistics.sessions)));
+                sessions.set_halign(gtk::Align::Start);
+                grid.attach(&sessions, 0, row, 4, 1);
+                row += 1;
+
+                // Add the leaderboard
+                let title = gtk::Label::new(None);
+                title.set_markup("<b>Leaderboard</b>");
+                title.set_halign(gtk::Align::Start);
+                grid.attach(&title, 0, row, 4, 1);
+                row += 1;
+                for (rank, entry) in statistics.leaderboard.iter().enumerate() {
+                    let team = match entry.team {
+                        Some(ref team) => clean_text(team, DESCRIPTION_LIMIT, false, false, true),
+                        None => "Unnamed Team".to_string(),
+                    };
+                    let seconds = entry.completion.as_secs();
+                    let cells = [
+                        format!("{}.", rank + 1),
+                        team,
+                        format!("{}:{:02}", seconds / 60, seconds % 60),
+                        entry.start.clone(),
+                    ];
+                    for (column, cell) in cells.iter().enumerate() {
+                        let label = gtk::Label::new(Some(cell));
+                        label.set_halign(gtk::Align::Start);
+                        grid.attach(&label, column as i32, row, 1, 1);
+                    }
+                    row += 1;
+                }
+
+                // Add the puzzles, highest failure rate first
+                let title = gtk::Label::new(None);
+                title.set_markup("<b>Puzzles (Highest Failure Rate First)</b>");
+                title.set_halign(gtk::Align::Start);
+                grid.attach(&title, 0, row, 4, 1);
+                row += 1;
+                for puzzle in statistics.puzzles.iter() {
+                    let average = match puzzle.average {
+                        Some(average) => format!(
+                            "Average {}:{:02}",
+                            average.as_secs() / 60,
+                            average.as_secs() % 60
+                        ),
+                        None => "Never Solved".to_string(),
+                    };
+                    let failures = format!(
+                        "Failed {} Of {} ({}%)",
+                        puzzle.failures,
+                        puzzle.attempts,
+                        (puzzle.failures * 100) / puzzle.attempts.max(1)
+                    );
+                    let cells = [
+                        clean_text(
+                            &puzzle.milestone.description,
+                            DESCRIPTION_LIMIT,
+                            false,
+                            false,
+                            true,
+                        ),
+                        average,
+                        failures,
+                    ];
+                    for (column, cell) in cells.iter().enumerate() {
+                        let label = gtk::Label::new(Some(cell));
+                        label.set_halign(gtk::Align::Start);
+                        grid.attach(&label, column as i32, row, 1, 1);
+                    }
+                    row += 1;
+                }
+
+                // Show the new statistics
+                grid.show_all();
+            }
+        }
+    }
+}
+
+/// A structure to contain the window for the notification history of the
+/// session, with filters for the severity, the related event and the text.
+///
+pub struct NotificationWindow {
+    window: gtk::ApplicationWindow, // a copy of the primary window
+    system_send: SystemSend,        // a copy of system send
+    history: Rc<RefCell<Vec<HistoryEntry>>>, // the most recent copy of the history
+    history_fields: Option<(gtk::Grid, gtk::ComboBoxText, gtk::Entry, gtk::SearchEntry)>, // the history grid and the filter fields
+}
+
+// Implement key features for the notification window
+impl NotificationWindow {
+    /// A function to create a new notification window structure.
+    ///
+    pub fn new(system_send: &SystemSend, window: &gtk::ApplicationWindow) -> NotificationWindow {
+        NotificationWindow {
+            window: window.clone(),
+            system_send: system_send.clone(),
+            history: Rc::new(RefCell::new(Vec::new())),
+            history_fields: None,
+        }
+    }
+
+    /// A method to launch the new notification window. The history is
+    /// filled in when the system replies.
+    ///
+    pub fn launch(&mut self) {
+        // Create the new dialog
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some("Notification History"),
+            Some(&self.window),
+            gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Acknowledge All Errors", gtk::ResponseType::Apply),
+                ("Close", gtk::ResponseType::Ok),
+            ],
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Access the content area and add the grid
+        let content = dialog.get_content_area();
+        let grid = gtk::Grid::new();
+        content.add(&grid);
+
+        // Add some space between the rows and columns
+        grid.set_column_spacing(10);
+        grid.set_row_spacing(10);
+
+        // Add some space on all the sides
+        grid.set_margin_top(10);
+        grid.set_margin_bottom(10);
+        grid.set_margin_start(10);
+        grid.set_margin_end(10);
+
+        // Add the filter fields
+        let severity_selection = gtk::ComboBoxText::new();
+        severity_selection.append(Some("all"), "All Notifications");
+        severity_selection.append(Some("error"), "Errors");
+        severity_selection.append(Some("warning"), "Warnings");
+        severity_selection.append(Some("event"), "Events");
+        severity_selection.append(Some("update"), "Updates");
+        severity_selection.set_active_id(Some("all"));
+        let event_entry = gtk::Entry::new();
+        event_entry.set_placeholder_text(Some("Event Id Or Description"));
+        let search_entry = gtk::SearchEntry::new();
+        grid.attach(&severity_selection, 0, 0, 1, 1);
+        grid.attach(&event_entry, 1, 0, 1, 1);
+        grid.attach(&search_entry, 2, 0, 1, 1);
+
+        // Create the history grid inside a scrollable window
+        let history_grid = gtk::Grid::new();
+        history_grid.set_column_spacing(10);
+        history_grid.set_row_spacing(5);
+        let history_window = gtk::ScrolledWindow::new(
+            Some(&gtk::Adjustment::new(0.0, 0.0, 100.0, 0.1, 100.0, 100.0)),
+            Some(&gtk::Adjustment::new(0.0, 0.0, 100.0, 0.1, 100.0, 100.0)),
+        ); // Should be None, None, but the compiler has difficulty inferring types
+        history_window.add(&history_grid);
+        history_window.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        history_window.set_size_request(700, 400);
+        grid.attach(&history_window, 0, 1, 3, 1);
+
+        // Redraw the history whenever a filter changes
+        let fields = (
+            history_grid,
+            severity_selection.clone(),
+            event_entry.clone(),
+            search_entry.clone(),
+        );
+        self.history_fields = Some(fields.clone());
+        let history = self.history.clone();
+        let system_send = self.system_send.clone();
+        severity_selection.connect_changed(clone!(fields, history, system_send => move |_| {
+            NotificationWindow::draw_history(&system_send, &history.borrow(), &fields);
+        }));
+        event_entry.connect_changed(clone!(fields, history, system_send => move |_| {
+            NotificationWindow::draw_history(&system_send, &history.borrow(), &fields);
+        }));
+        search_entry.connect_search_changed(clone!(fields, history, system_send => move |_| {
+            NotificationWindow::draw_history(&system_send, &history.borrow(), &fields);
+        }));
+
+        // Request the history from the system
+        NotificationWindow::request_history(&self.system_send);
+
+        // Acknowledge every error or close the window
+        dialog.connect_response(clone!(system_send => move |modal, id| {
+            if id == gtk::ResponseType::Apply {
+                system_send.send(Acknowledge { index: None });
+                NotificationWindow::request_history(&system_send);
+            } else {
+                modal.destroy();
+            }
+        }));
+
+        // Show the dialog and return
+        dialog.show_all();
+    }
+
+    // A method to update the information displayed in the window
+    pub fn update_info(&self, reply: ReplyType) {
+        // Update the history, ignore others
+        if let ReplyType::Notifications { history } = reply {
+            // Save the history and redraw it, if the window exists
+            *self.history.borrow_mut() = history;
+            if let Some(ref fields) = self.history_fields {
+                NotificationWindow::draw_history(&self.system_send, &self.history.borrow(), fields);
+            }
+        }
+    }
+
+    /// An internal function to request the notification history from the
+    /// system
+    ///
+    fn request_history(system_send: &SystemSend) {
+        system_send.send(Request {
+            reply_to: DisplayComponent::NotificationWindow,
+            request: RequestType::Notifications,
+        });
+    }
+
+    /// An internal function to fill the history grid with the entries which
+    /// match the filter fields
+    ///
+    fn draw_history(
+        system_send: &SystemSend,
+        history: &[HistoryEntry],
+        fields: &(gtk::Grid, gtk::ComboBoxText, gtk::Entry, gtk::SearchEntry),
+    ) {
+        // Remove the old entries
+        let (grid, severity_selection, event_entry, search_entry) = fields;
+        for item in grid.get_children() {
+            item.destroy();
+        }
+
+        // Compose the filter from the fields
+        let severity = match severity_selection.get_active_id() {
+            Some(ref id) if id.as_str() == "error" => Some(Severity::Error),
+            Some(ref id) if id.as_str() == "warning" => Some(Severity::Warning),
+            Some(ref id) if id.as_str() == "event" => Some(Severity::Event),
+            Some(ref id) if id.as_str() == "update" => Some(Severity::Update),
+            _ => None,
+        };
+        let filter = HistoryFilter {
+            severity,
+            event: event_entry
+                .get_text()
+                .map(|text| text.to_string())
+                .unwrap_or_default(),
+            text: search_entry
+                .get_text()
+                .map(|text| text.to_string())
+                .unwrap_or_default(),
+        };
+
+        // Add each matching entry, the most recent first
+        for (row, entry) in history
+            .iter()
+            .filter(|entry| filter.matches(entry))
+            .enumerate()
+        {
+            // Compose the time, severity, message and event of the entry
+            let time = entry.notification.time();
+            let time = time.strftime("%a %T").unwrap_or_else(|_| time.asctime()); // Fallback on other time format
+            let severity = match entry.notification.severity() {
+                Severity::Error => "<span color='#FF3333'><b>Error</b></span>",
+                Severity::Warning => "<span color='#FFEE44'>Warning</span>",
+                Severity::Event => "<span color='#338DD6'>Event</span>",
+                Severity::Update => "Update",
+            };
+            let message = clean_text(
+                &entry.notification.message(),
+                DESCRIPTION_LIMIT,
+                true,
+                false,
+                true,
+            );
+            let event = match entry.event {
+                Some(ref event) => {
+                    clean_text(&event.to_string(), DESCRIPTION_LIMIT, false, false, true)
+                }
+                None => String::new(),
+            };
+
+            // Highlight the errors which have not been acknowledged
+            let is_pinned = entry.notification.severity() == Severity::Error && !entry.acknowledged;
+            let message = match is_pinned {
+                true => format!("<span background='#5C1010'>{}</span>", message),
+                false => message,
+            };
+
+            // Add the labels to the grid
+            let cells = [time.to_string(), severity.to_string(), message, event];
+            for (column, cell) in cells.iter().enumerate() {
+                let label = gtk::Label::new(None);
+                label.set_markup(cell);
+                label.set_halign(gtk::Align::Start);
+                grid.attach(&label, column as i32, row as i32, 1, 1);
+            }
+
+            // Add a button to acknowledge the error, if it is pinned
+            if is_pinned {
+                let button = gtk::Button::new_with_label("Acknowledge");
+                let index = entry.index;
+                button.connect_clicked(clone!(system_send => move |_| {
+                    system_send.send(Acknowledge { index: Some(index) });
+                    NotificationWindow::request_history(&system_send);
+                }));
+                grid.attach(&button, 4, row as i32, 1, 1);
+            }
+        }
+
+        // Show the new history
+        grid.show_all();
+    }
+}
+
+/// A structure to contain the dialog for resuming lingering backup data.
+///
+pub struct ResumeBackupDialog {
+    window: gtk::ApplicationWindow, // a copy of the primary window
+}
+
+// Implement key features for the resume backup dialog
+impl ResumeBackupDialog {
+    /// A function to create a new resume backup dialog structure.
+    ///
+    pub fn new(window: &gtk::ApplicationWindow) -> ResumeBackupDialog {
+        ResumeBackupDialog {
+            window: window.clone(),
+        }
+    }
+
+    /// A method to launch the new resume backup dialog with a summary of the
+    /// lingering backup data. The operator chooses how to resume the queued
+    /// events (starting from the configured resume mode). The backup data is
+    /// discarded only after a confirmation. Closing the dialog any other way
+    /// leaves the backup data in place to decide later.
+    ///
+    pub fn launch(&self, system_send: &SystemSend, summary: BackupSummary) {
+        // Create the new dialog
+        let dialog = gtk::Dialog::new_with_buttons(
+            Some("Lingering Backup Data"),
+            Some(&self.window),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Decide Later", gtk::ResponseType::Cancel),
+                ("Discard", gtk::ResponseType::Reject),
+                ("Resume Selected", gtk::ResponseType::Ok),
+            ],
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Access the content area and add the grid
+        let content = dialog.get_content_area();
+        let grid = gtk::Grid::new();
+        content.add(&grid);
+
+        // Add some space between the rows and columns
+        grid.set_column_spacing(10);
+        grid.set_row_spacing(10);
+
+        // Add some space on all the sides
+        grid.set_margin_top(10);
+        grid.set_margin_bottom(10);
+        grid.set_margin_start(10);
+        grid.set_margin_end(10);
+
+        // Describe the age of the backup
+        let age = match summary.age {
+            Some(age) => {
+                let minutes = age.as_secs() / 60;
+                format!(
+                    " The Backup Was Last Updated {}:{:02} (h:mm) Ago. ",
+                    minutes / 60,
+                    minutes % 60
+                )
+            }
+            None => " The Age Of The Backup Is Unknown. ".to_string(),
+        };
+        grid.attach(&gtk::Label::new(Some(&age)), 0, 0, 2, 1);
+
+        // Add a separator
+        let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
+        separator.set_hexpand(true);
+        separator.set_halign(gtk::Align::Fill);
+        grid.attach(&separator, 0, 1, 2, 1);
+
+        // Add the checkbox for the scene
+        let scene_checkbox = gtk::CheckButton::new_with_label("Resume Scene");
+        scene_checkbox.set_active(true);
+        grid.attach(&scene_checkbox, 0, 2, 1, 1);
+        let scene_label = gtk::Label::new(Some(&clean_text(
+            &summary.scene.description(),
+            DESCRIPTION_LIMIT,
+            false,
+            false,
+            true,
+        )));
+        scene_label.set_halign(gtk::Align::Start);
+        grid.attach(&scene_label, 1, 2, 1, 1);
+
+        // List the statuses which differ from their defaults
+        let status_checkbox = gtk::CheckButton::new_with_label("Resume Statuses");
+        status_checkbox.set_active(true);
+        grid.attach(&status_checkbox, 0, 3, 1, 1);
+        let mut status_lines = Vec::new();
+        for &(ref status_pair, ref state_pair) in summary.statuses.iter() {
+            status_lines.push(format!(
+                "{}: {}",
+                clean_text(
+                    &status_pair.description(),
+                    DESCRIPTION_LIMIT,
+                    false,
+                    false,
+                    true
+                ),
+                clean_text(&state_pair.description(), STATE_LIMIT, false, false, true)
+            ));
+        }
+        if status_lines.is_empty() {
+            status_lines.push("No Changes From The Defaults".to_string());
+        }
+        let status_label = gtk::Label::new(Some(&status_lines.join("\n")));
+        status_label.set_halign(gtk::Align::Start);
+        grid.attach(&status_label, 1, 3, 1, 1);
+
+        // List the queued events and their remaining time (as backed up and until the deadline)
+        let events_checkbox = gtk::CheckButton::new_with_label("Resume Queued Events");
+        events_checkbox.set_active(true);
+        grid.attach(&events_checkbox, 0, 4, 1, 1);
+        let mut event_lines = Vec::new();
+        for &(ref event_pair, ref frozen, ref remaining) in summary.events.iter() {
+            let time = match remaining {
+                &Some(remaining) => format!(
+                    "{}:{:02}",
+                    remaining.as_secs() / 60,
+                    remaining.as_secs() % 60
+                ),
+                &None => "Expired".to_string(),
+            };
+            event_lines.push(format!(
+                "{} ({}:{:02} As Backed Up, {} Now)",
+                clean_text(
+                    &event_pair.description(),
+                    DESCRIPTION_LIMIT,
+                    false,
+                    false,
+                    true
+                ),
+                frozen.as_secs() / 60,
+                frozen.as_secs() % 60,
+                time
+            ));
+        }
+        if event_lines.is_empty() {
+            event_lines.push("No Queued Events".to_string());
+        }
+        let events_label = gtk::Label::new(Some(&event_lines.join("\n")));
+        events_label.set_halign(gtk::Align::Start);
+        grid.attach(&events_label, 1, 4, 1, 1);
+
+        // Add the choice of how to resume the queued events (defaulting to the configuration)
+        let mode_selection = gtk::ComboBoxText::new();
+        mode_selection.append(Some("frozen"), "Frozen (Times As Backed Up)");
+        mode_selection.append(Some("catch_up"), "Catch Up (Trigger Expired Events)");
+        mode_selection.append(Some("skip"), "Skip Expired (Drop Expired Events)");
+        mode_selection.set_active_id(Some(match summary.resume_mode {
+            ResumeMode::Frozen => "frozen",
+            ResumeMode::Deadlines {
+                skip_expired: false,
+            } => "catch_up",
+            ResumeMode::Deadlines { skip_expired: true } => "skip",
+        }));
+        grid.attach(&gtk::Label::new(Some("Queued Events:")), 0, 5, 1, 1);
+        grid.attach(&mode_selection, 1, 5, 1, 1);
+
+        // Connect the close event for when the dialog is complete
+        let window = self.window.clone();
+        let default_mode = summary.resume_mode;
+        dialog.connect_response(clone!(system_send, scene_checkbox, status_checkbox, events_checkbox, mode_selection => move |modal, id| {
+            // Resume the selected parts of the backup with the chosen resume mode
+            if id == gtk::ResponseType::Ok {
+                let resume_mode = match mode_selection.get_active_id() {
+                    Some(ref id) if id.as_str() == "frozen" => ResumeMode::Frozen,
+                    Some(ref id) if id.as_str() == "catch_up" => ResumeMode::Deadlines { skip_expired: false },
+                    Some(ref id) if id.as_str() == "skip" => ResumeMode::Deadlines { skip_expired: true },
+                    _ => default_mode,
+                };
+                system_send.send(ResumeBackup {
+                    scene: scene_checkbox.get_active(),
+                    statuses: status_checkbox.get_active(),
+                    events: events_checkbox.get_active(),
+                    resume_mode,
+                });
+
+            // Confirm before discarding the backup
+            } else if id == gtk::ResponseType::Reject {
+                // Create the confirmation dialog
+                let confirm = gtk::MessageDialog::new(
+                    Some(&window),
+                    gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+                    gtk::MessageType::Warning,
+                    gtk::ButtonsType::OkCancel,
+                    "Discard the lingering backup data? The previous game cannot be resumed afterwards.",
+                );
+                confirm.set_position(gtk::WindowPosition::Center);
+
+                // Discard the backup only if confirmed
+                confirm.connect_response(clone!(system_send => move |confirm, id| {
+                    if id == gtk::ResponseType::Ok {
+                        system_send.send(ResumeBackup { scene: false, statuses: false, events: false, resume_mode: default_mode });
+
+                    // Otherwise, ask again
+                    } else {
+                        system_send.send(ReviewBackup);
+                    }
+
+                    // Close the window either way
+                    confirm.destroy();
+                }));
+                confirm.show_all();
+            }
+
+            // Otherwise (e.g. the window was closed), leave the backup data to decide later
+
+            // Close the window either way
+            modal.destroy();
+        }));
+
+        // Show the dialog and return
+        dialog.show_all();
+    }
+}
+
 /// A structure to contain the dialog for soliciting a string from the user.
 ///
 pub struct PromptStringDialog {
@@ -818,6 +1730,12 @@ impl PromptStringDialog {
                 let end = buffer.get_end_iter();
                 if let Some(gtext) = buffer.get_text(&start, &end, false) {
 
+                    // Record the string for the game report
+                    system_send.send(UserString {
+                        event: event.get_id(),
+                        string: gtext.to_string(),
+                    });
+
                     // Convert the text into bytes
                     let mut bytes = gtext.to_string().into_bytes();
 
@@ -22,8 +22,8 @@
 
 // Import the relevant structures into the correct namespace
 use super::super::super::system_interface::{
-    AllEventChange, DisplayControl, DisplayDebug, DisplayWith, EventChange, ItemPair, LabelControl,
-    LabelHidden, SystemSend, UpcomingEvent,
+    AllEventChange, Clock, DisplayControl, DisplayDebug, DisplayWith, EventChange, ItemPair,
+    LabelControl, LabelHidden, SystemSend, UpcomingEvent,
 };
 use super::super::super::FONT;
 use super::super::utils::clean_text;
@@ -66,6 +66,7 @@ struct TimelineEvent {
     event: ItemPair,     // the name and id of the event associated with this event
     start_time: Instant, // the original start time of the event
     delay: Duration,     // the delay of the event (relative to the original start time)
+    clock: Clock,        // the clock for calculating the remaining time of the event
     unique_id: String,   // a unique identifier, composed from both the event id and the start_time
     location: f64,       // the location on the timeline in pixels
     in_focus: bool,      // a flag to indicate that this event has been clicked on
@@ -76,7 +77,7 @@ impl TimelineEvent {
     /// A function to create a new timeline event. This method provides a regular
     /// (and reliable) method of creating a unique id.
     ///
-    fn new(event: UpcomingEvent) -> TimelineEvent {
+    fn new(event: UpcomingEvent, clock: &Clock) -> TimelineEvent {
         // Create the unique identifier from the event id and the start_time
         let unique_id = TimelineEvent::new_unique_id(&event.event, &event.start_time);
 
@@ -85,6 +86,7 @@ impl TimelineEvent {
             event: event.event,
             start_time: event.start_time,
             delay: event.delay,
+            clock: clock.clone(),
             unique_id,
             location: 0.0,
             in_focus: false,
@@ -104,7 +106,7 @@ impl TimelineEvent {
     ///
     fn remaining(&self) -> Option<(f64, f64)> {
         // Find the amount of time remaining
-        let remaining = match self.delay.checked_sub(self.start_time.elapsed()) {
+        let remaining = match self.clock.remaining(&self.start_time, &self.delay) {
             Some(time) => time,
             None => return None,
         };
@@ -120,7 +122,7 @@ impl TimelineEvent {
     ///
     fn remaining_precise(&self) -> Option<f64> {
         // Find the amount of time remaining
-        let remaining = match self.delay.checked_sub(self.start_time.elapsed()) {
+        let remaining = match self.clock.remaining(&self.start_time, &self.delay) {
             Some(time) => time,
             None => return None,
         };
@@ -298,7 +300,7 @@ impl TimelineAdjustment {
                         } else {
                             // Use that information to create the new duration
                             let mut new_delay = Duration::from_secs((minutes.get_value() as u64) * 60 + (seconds.get_value() as u64));
-                            new_delay += event.start_time.elapsed();
+                            new_delay += event.clock.elapsed(&event.start_time);
 
                             // Send an event update to the system
                             system_send.send(EventChange {
@@ -341,6 +343,7 @@ pub struct TimelineAbstraction {
     timeline_area: gtk::DrawingArea, // the timeline draw area for upcoming events
     timeline_events: Rc<RefCell<FnvHashMap<String, TimelineEvent>>>, // a hash map which stores references to the events currently in the timeline
     timeline_info: Rc<RefCell<TimelineInfo>>, // the timeline start time and duration
+    clock: Clock,                             // the clock for timing the events in the timeline
 }
 
 // Implement key features for the Timeline
@@ -349,7 +352,11 @@ impl TimelineAbstraction {
     /// all the default widgets into the interface and returns a new copy to
     /// allow insertion into higher levels.
     ///
-    pub fn new(system_send: &SystemSend, window: &gtk::ApplicationWindow) -> TimelineAbstraction {
+    pub fn new(
+        system_send: &SystemSend,
+        clock: &Clock,
+        window: &gtk::ApplicationWindow,
+    ) -> TimelineAbstraction {
         // Create the timeline title
         let timeline_title = gtk::Label::new(None);
         timeline_title.set_markup("<span color='#338DD6' size='16000'>Timeline</span>");
@@ -527,6 +534,7 @@ impl TimelineAbstraction {
             timeline_area,
             timeline_events,
             timeline_info,
+            clock: clock.clone(),
         }
     }
 
@@ -553,7 +561,7 @@ impl TimelineAbstraction {
         // Pass the events into the timeline events
         for event in events.drain(..) {
             // Convert each to a new timeline event
-            let mut new_event = TimelineEvent::new(event);
+            let mut new_event = TimelineEvent::new(event, &self.clock);
 
             // Check to see if the event already existed in the timeline
             if let Some(existing) = old_events.get(&new_event.unique_id) {
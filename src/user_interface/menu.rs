@@ -23,7 +23,8 @@
 // Import the relevant structures into the correct namespace
 use super::super::system_interface::{
     ChangeSettings, ClearQueue, Close, ConfigFile, DisplaySetting, EditMode, ErrorLog, GameLog,
-    InterfaceUpdate, LaunchWindow, SaveConfig, SystemSend, WindowType,
+    InterfaceUpdate, LaunchWindow, ReloadConfig, ReviewBackup, SaveConfig, SaveGraph, SaveReport,
+    SimulationMode, SystemSend, WindowType,
 };
 
 // Import standard library features
@@ -55,13 +56,16 @@ pub struct MenuAbstraction {
 }
 
 impl MenuAbstraction {
-    /// A function to build a new default menu for the application.
+    /// A function to build a new default menu for the application. In
+    /// read-only mode, the edit mode action is left out (which disables the
+    /// whole edit menu).
     ///
     pub fn build_menu(
         application: &gtk::Application,
         window: &gtk::ApplicationWindow,
         system_send: &SystemSend,
         interface_send: &mpsc::Sender<InterfaceUpdate>,
+        is_read_only: bool,
     ) -> MenuAbstraction {
         // Create the menu bar and the different submenus
         let menu_bar = gio::Menu::new();
@@ -75,13 +79,18 @@ impl MenuAbstraction {
         let quit_section = gio::Menu::new();
         let settings_section = gio::Menu::new();
         let window_section = gio::Menu::new();
+        let simulation_section = gio::Menu::new();
         let edit_section = gio::Menu::new();
         let modify_section = gio::Menu::new();
 
         // Organize the file section of the menu
         config_section.append(Some("Choose Configuration"), Some("app.config"));
+        config_section.append(Some("Reload Configuration"), Some("app.reload"));
         config_section.append(Some("Choose Game Log"), Some("app.game_log"));
         config_section.append(Some("Choose Error Log"), Some("app.error_log"));
+        config_section.append(Some("Log Settings ..."), Some("app.log_settings"));
+        config_section.append(Some("Export Event Graph"), Some("app.graph"));
+        config_section.append(Some("Export Game Report"), Some("app.report"));
         quit_section.append(Some("Quit"), Some("app.quit"));
         file_menu.append_item(&gio::MenuItem::new_section(None, &config_section));
         file_menu.append_item(&gio::MenuItem::new_section(None, &quit_section));
@@ -95,9 +104,17 @@ impl MenuAbstraction {
         window_section.append(Some("Jump To ..."), Some("app.jump"));
         window_section.append(Some("Modify Status"), Some("app.status"));
         window_section.append(Some("Trigger Event"), Some("app.trigger"));
+        window_section.append(Some("Checkpoints ..."), Some("app.checkpoints"));
+        window_section.append(Some("Lingering Backup ..."), Some("app.lingering"));
+        window_section.append(Some("Statistics ..."), Some("app.statistics"));
+        window_section.append(Some("Notification History ..."), Some("app.notifications"));
         window_section.append(Some("Clear Timeline"), Some("app.clear"));
+        simulation_section.append(Some("Run In Real Time"), Some("app.real_time"));
+        simulation_section.append(Some("Simulate At 10x"), Some("app.simulate_ten"));
+        simulation_section.append(Some("Simulate At 100x"), Some("app.simulate_hundred"));
         run_menu.append_item(&gio::MenuItem::new_section(None, &settings_section));
         run_menu.append_item(&gio::MenuItem::new_section(None, &window_section));
+        run_menu.append_item(&gio::MenuItem::new_section(None, &simulation_section));
 
         // Organize the run section of the menu
         edit_section.append(Some("_Edit Mode"), Some("app.edit_mode"));
@@ -149,6 +166,13 @@ impl MenuAbstraction {
             dialog.show_all();
         }));
 
+        // Create the reload configuration action
+        let reload = gio::SimpleAction::new("reload", None);
+        reload.connect_activate(clone!(system_send => move |_, _| {
+            // Reload the current configuration, keeping the live state
+            system_send.send(ReloadConfig);
+        }));
+
         // Create the game log dialog action
         let game_log = gio::SimpleAction::new("game_log", None);
         game_log.connect_activate(clone!(window, system_send => move |_, _| {
@@ -205,6 +229,76 @@ impl MenuAbstraction {
             dialog.show_all();
         }));
 
+        // Create the log settings dialog action
+        let log_settings = gio::SimpleAction::new("log_settings", None);
+        let interface_clone = interface_send.clone();
+        log_settings.connect_activate(move |_, _| {
+            // Launch the log settings dialog
+            interface_clone
+                .send(LaunchWindow {
+                    window_type: WindowType::LogSettings,
+                })
+                .unwrap_or(());
+        });
+
+        // Create the export graph dialog action
+        let graph = gio::SimpleAction::new("graph", None);
+        graph.connect_activate(clone!(window, system_send => move |_, _| {
+
+            // Creaate and launch a new graph chooser dialog
+            let dialog = gtk::FileChooserDialog::new(Some("Export Event Graph To File"), Some(&window), gtk::FileChooserAction::Save);
+            dialog.set_position(gtk::WindowPosition::Center);
+            dialog.set_current_name("event_graph.dot");
+
+            // Connect the close event for when the dialog is complete
+            dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+            dialog.add_button("Confirm", gtk::ResponseType::Ok);
+            dialog.connect_response(clone!(system_send => move |chooser, id| {
+
+                // Notify the system of the new graph file
+                if id == gtk::ResponseType::Ok {
+                    if let Some(filepath) = chooser.get_filename() {
+                        system_send.send(SaveGraph { filepath, });
+                    }
+                }
+
+                // Close the window either way
+                chooser.destroy();
+            }));
+
+            // Show the dialog
+            dialog.show_all();
+        }));
+
+        // Create the export report dialog action
+        let report = gio::SimpleAction::new("report", None);
+        report.connect_activate(clone!(window, system_send => move |_, _| {
+
+            // Creaate and launch a new report chooser dialog
+            let dialog = gtk::FileChooserDialog::new(Some("Export Game Report To File (CSV And HTML)"), Some(&window), gtk::FileChooserAction::Save);
+            dialog.set_position(gtk::WindowPosition::Center);
+            dialog.set_current_name("game_report.csv");
+
+            // Connect the close event for when the dialog is complete
+            dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+            dialog.add_button("Confirm", gtk::ResponseType::Ok);
+            dialog.connect_response(clone!(system_send => move |chooser, id| {
+
+                // Notify the system of the new report file
+                if id == gtk::ResponseType::Ok {
+                    if let Some(filepath) = chooser.get_filename() {
+                        system_send.send(SaveReport { filepath: Some(filepath), });
+                    }
+                }
+
+                // Close the window either way
+                chooser.destroy();
+            }));
+
+            // Show the dialog
+            dialog.show_all();
+        }));
+
         // Create the quit action
         let quit = gio::SimpleAction::new("quit", None);
         quit.connect_activate(clone!(system_send, window => move |_, _| {
@@ -332,6 +426,25 @@ impl MenuAbstraction {
             system_send.send(ClearQueue);
         }));
 
+        // Create the real time action
+        let real_time = gio::SimpleAction::new("real_time", None);
+        real_time.connect_activate(clone!(system_send => move |_, _| {
+            // Return the clock to real time
+            system_send.send(SimulationMode(None));
+        }));
+
+        // Create the simulation actions
+        let simulate_ten = gio::SimpleAction::new("simulate_ten", None);
+        simulate_ten.connect_activate(clone!(window, system_send => move |_, _| {
+            // Run the clock ten times faster, once confirmed
+            MenuAbstraction::confirm_simulation(&window, &system_send, 10);
+        }));
+        let simulate_hundred = gio::SimpleAction::new("simulate_hundred", None);
+        simulate_hundred.connect_activate(clone!(window, system_send => move |_, _| {
+            // Run the clock one hundred times faster, once confirmed
+            MenuAbstraction::confirm_simulation(&window, &system_send, 100);
+        }));
+
         // Create the trigger event to dialog action
         let trigger = gio::SimpleAction::new("trigger", None);
         let interface_clone = interface_send.clone();
@@ -344,6 +457,49 @@ impl MenuAbstraction {
                 .unwrap_or(());
         });
 
+        // Create the checkpoints dialog action
+        let checkpoints = gio::SimpleAction::new("checkpoints", None);
+        let interface_clone = interface_send.clone();
+        checkpoints.connect_activate(move |_, _| {
+            // Launch the checkpoint dialog
+            interface_clone
+                .send(LaunchWindow {
+                    window_type: WindowType::Checkpoints,
+                })
+                .unwrap_or(());
+        });
+
+        // Create the lingering backup dialog action
+        let lingering = gio::SimpleAction::new("lingering", None);
+        lingering.connect_activate(clone!(system_send => move |_, _| {
+            // Ask the system to relaunch the resume backup dialog
+            system_send.send(ReviewBackup);
+        }));
+
+        // Create the statistics window action
+        let statistics = gio::SimpleAction::new("statistics", None);
+        let interface_clone = interface_send.clone();
+        statistics.connect_activate(move |_, _| {
+            // Launch the statistics window
+            interface_clone
+                .send(LaunchWindow {
+                    window_type: WindowType::Statistics,
+                })
+                .unwrap_or(());
+        });
+
+        // Create the notification history window action
+        let notifications = gio::SimpleAction::new("notifications", None);
+        let interface_clone = interface_send.clone();
+        notifications.connect_activate(move |_, _| {
+            // Launch the notification history window
+            interface_clone
+                .send(LaunchWindow {
+                    window_type: WindowType::Notifications,
+                })
+                .unwrap_or(());
+        });
+
         // Create the edit mode action (toggles availability of the other edit actions)
         let edit = gio::SimpleAction::new_stateful("edit_mode", None, &false.to_variant());
 
@@ -506,19 +662,32 @@ impl MenuAbstraction {
 
         // Add the actions to the application
         application.add_action(&config);
+        application.add_action(&reload);
         application.add_action(&game_log);
         application.add_action(&error_log);
+        application.add_action(&log_settings);
+        application.add_action(&graph);
+        application.add_action(&report);
         application.add_action(&quit);
         application.add_action(&fullscreen);
         application.add_action(&debug);
         application.add_action(&font);
         application.add_action(&contrast);
-        application.add_action(&edit);
+        if !is_read_only {
+            application.add_action(&edit);
+        }
         application.add_action(&shortcuts);
         application.add_action(&jump);
         application.add_action(&status);
         application.add_action(&trigger);
+        application.add_action(&checkpoints);
+        application.add_action(&lingering);
+        application.add_action(&statistics);
+        application.add_action(&notifications);
         application.add_action(&clear);
+        application.add_action(&real_time);
+        application.add_action(&simulate_ten);
+        application.add_action(&simulate_hundred);
         application.add_action(&help);
         application.add_action(&about);
 
@@ -531,6 +700,37 @@ impl MenuAbstraction {
         }
     }
 
+    /// An internal function to ask the operator to confirm a simulation at the
+    /// provided rate before it starts
+    ///
+    fn confirm_simulation(window: &gtk::ApplicationWindow, system_send: &SystemSend, rate: u32) {
+        // Create the confirmation dialog
+        let dialog = gtk::MessageDialog::new(
+            Some(window),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            gtk::MessageType::Warning,
+            gtk::ButtonsType::OkCancel,
+            &format!(
+                "Simulate At {}x? Broadcasts Will Not Be Sent, The Backup Will Be Paused And The Game Log Will Be Tagged Until Returning To Real Time.",
+                rate
+            ),
+        );
+        dialog.set_position(gtk::WindowPosition::Center);
+
+        // Start the simulation only if confirmed
+        dialog.connect_response(clone!(system_send => move |dialog, id| {
+            if id == gtk::ResponseType::Ok {
+                system_send.send(SimulationMode(Some(rate)));
+            }
+
+            // Close the window either way
+            dialog.destroy();
+        }));
+
+        // Show the dialog
+        dialog.show_all();
+    }
+
     /// Helper function to change the current state of the fullscreen checkbox
     pub fn set_fullscreen(&mut self, is_fullscreen: bool) {
         self.fullscreen.change_state(&(is_fullscreen).to_variant());
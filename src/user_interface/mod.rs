@@ -31,7 +31,7 @@ mod menu;
 use self::abstraction::InterfaceAbstraction;
 use self::menu::MenuAbstraction;
 use super::system_interface::{
-    ChangeSettings, DebugMode, DisplayComponent, DisplaySetting, EditMode, InterfaceUpdate,
+    ChangeSettings, Clock, DebugMode, DisplayComponent, DisplaySetting, EditMode, InterfaceUpdate,
     LaunchWindow, Notify, Redraw, Reply, SystemSend, SystemUpdate, UpdateConfig,
     UpdateNotifications, UpdateStatus, UpdateTimeline, UpdateWindow, WindowType,
 };
@@ -65,7 +65,7 @@ pub struct UserInterface {
 impl UserInterface {
     /// A function to create a new, blank instance of the user interface. The
     /// window provided to the function should be the top-level window for the
-    /// program.
+    /// program. In read-only mode, edit mode is not available.
     ///
     pub fn new(
         application: &gtk::Application,
@@ -73,17 +73,25 @@ impl UserInterface {
         system_send: SystemSend,
         interface_send: mpsc::Sender<InterfaceUpdate>,
         interface_receive: mpsc::Receiver<InterfaceUpdate>,
+        clock: Clock,
+        is_read_only: bool,
     ) -> UserInterface {
         // Create a new interface abstraction and add the top element to the window
         let interface_abstraction =
-            InterfaceAbstraction::new(&system_send, &interface_send, window);
+            InterfaceAbstraction::new(&system_send, &interface_send, &clock, window);
         window.add(interface_abstraction.get_top_element());
 
         // Wrap the interface abstraction in a rc and refcell
         let interface_abstraction = Rc::new(RefCell::new(interface_abstraction));
 
         // Create the menu bar for the window
-        let menu = MenuAbstraction::build_menu(application, window, &system_send, &interface_send);
+        let menu = MenuAbstraction::build_menu(
+            application,
+            window,
+            &system_send,
+            &interface_send,
+            is_read_only,
+        );
 
         // Wrap the menu abstraction in a rc and refcell
         let menu_abstraction = Rc::new(RefCell::new(menu));
@@ -232,6 +240,23 @@ impl UserInterface {
 
                         // Launch the prompt string dialog
                         WindowType::PromptString(event) => interface.launch_prompt_string(event),
+
+                        // Launch the checkpoint dialog
+                        WindowType::Checkpoints => interface.launch_checkpoints(),
+
+                        // Launch the statistics window
+                        WindowType::Statistics => interface.launch_statistics(),
+
+                        // Launch the log settings dialog
+                        WindowType::LogSettings => interface.launch_log_settings(),
+
+                        // Launch the notification history window
+                        WindowType::Notifications => interface.launch_notifications(),
+
+                        // Launch the resume backup dialog
+                        WindowType::ResumeBackup(summary) => {
+                            interface.launch_resume_backup(summary)
+                        }
                     }
                 }
 
@@ -251,6 +276,26 @@ impl UserInterface {
                         DisplayComponent::EditItem => {
                             interface.update_edit_item(reply);
                         }
+
+                        // Pass the reply to the checkpoint dialog
+                        DisplayComponent::CheckpointDialog => {
+                            interface.update_checkpoints(reply);
+                        }
+
+                        // Pass the reply to the statistics window
+                        DisplayComponent::StatisticsWindow => {
+                            interface.update_statistics(reply);
+                        }
+
+                        // Pass the reply to the log settings dialog
+                        DisplayComponent::LogSettingsDialog => {
+                            interface.update_log_settings(reply);
+                        }
+
+                        // Pass the reply to the notification history window
+                        DisplayComponent::NotificationWindow => {
+                            interface.update_notification_history(reply);
+                        }
                     }
                 }
 
@@ -288,9 +333,10 @@ impl UserInterface {
                 } => interface.update_state(status_id, new_state),
 
                 // Update the notifications in the notification window
-                UpdateNotifications { notifications } => {
-                    interface.update_notifications(notifications)
-                }
+                UpdateNotifications {
+                    notifications,
+                    pinned,
+                } => interface.update_notifications(notifications, pinned),
 
                 // Update the events in the timeline area
                 UpdateTimeline { events } => interface.update_events(events),